@@ -18,7 +18,7 @@ if [[ "${TARGET}" == *"windows"* ]]; then
   BIN_EXT=".exe"
 fi
 
-required_bins=(docs_manager drive_manager sheets_manager)
+required_bins=(gws docs_manager drive_manager sheets_manager)
 for bin in "${required_bins[@]}"; do
   if [[ ! -f "${BIN_DIR}/${bin}${BIN_EXT}" ]]; then
     echo "Missing binary: ${BIN_DIR}/${bin}${BIN_EXT}" >&2
@@ -53,7 +53,7 @@ EOF
 
 done
 
-chmod +x "${PKG_DIR}/scripts/docs_manager" "${PKG_DIR}/scripts/drive_manager" "${PKG_DIR}/scripts/sheets_manager"
+chmod +x "${PKG_DIR}/scripts/gws" "${PKG_DIR}/scripts/docs_manager" "${PKG_DIR}/scripts/drive_manager" "${PKG_DIR}/scripts/sheets_manager"
 
 if [[ "${TARGET}" == *"windows"* ]]; then
   for bin in "${required_bins[@]}"; do
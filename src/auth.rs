@@ -1,11 +1,19 @@
+use crate::config::{Endpoints, SkillConfig};
+use crate::google_api::{GoogleApiError, GoogleClient};
+use crate::service_account::{ensure_service_account_token, load_service_account_key};
+use crate::token_store::{TokenStore, TokenStoreError, open_token_store};
 use anyhow::{Context, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use chrono::Utc;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use serde_yaml::{Mapping, Value as YamlValue};
+use std::cell::RefCell;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use url::Url;
 
 pub const DOCS_SCOPE: &str = "https://www.googleapis.com/auth/documents";
@@ -14,7 +22,14 @@ pub const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
 pub const CALENDAR_SCOPE: &str = "https://www.googleapis.com/auth/calendar";
 pub const CONTACTS_SCOPE: &str = "https://www.googleapis.com/auth/contacts";
 pub const GMAIL_SCOPE: &str = "https://www.googleapis.com/auth/gmail.modify";
+pub const DRIVE_METADATA_READONLY_SCOPE: &str =
+    "https://www.googleapis.com/auth/drive.metadata.readonly";
 
+/// Scopes needed by `whoami`, which reads the account from Drive `about`.
+pub const WHOAMI_SCOPES: &[&str] = &[DRIVE_METADATA_READONLY_SCOPE];
+
+/// Every scope used by this skill and the related Google skills. The tools
+/// themselves request only what they need; see each binary's `SCOPES`.
 pub const SHARED_SCOPES: &[&str] = &[
     DRIVE_SCOPE,
     SHEETS_SCOPE,
@@ -26,29 +41,196 @@ pub const SHARED_SCOPES: &[&str] = &[
 
 const DEFAULT_AUTH_URI: &str = "https://accounts.google.com/o/oauth2/auth";
 const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const DEFAULT_DEVICE_AUTH_URI: &str = "https://oauth2.googleapis.com/device/code";
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const DEFAULT_REVOKE_URI: &str = "https://oauth2.googleapis.com/revoke";
 const OOB_REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
 
+pub const DEFAULT_PROFILE: &str = "default";
+
 #[derive(Debug, Clone)]
 pub struct AuthPaths {
     pub credentials_path: PathBuf,
     pub token_path: PathBuf,
+    pub config_path: PathBuf,
+    /// Key under which this profile's token lives in the token file.
+    pub profile: String,
 }
 
 impl AuthPaths {
-    pub fn from_home(home: &Path) -> Self {
-        Self {
-            credentials_path: home.join(".claude/.google/client_secret.json"),
-            token_path: home.join(".claude/.google/token.json"),
+    /// Paths for the active profile, as chosen by `GOOGLE_SKILL_PROFILE` or
+    /// `active_profile` in the config file.
+    pub fn from_home(home: &Path) -> Result<Self> {
+        Self::for_profile(home, None)
+    }
+
+    /// Paths for `profile`, falling back to the active profile when `None`.
+    /// A profile may ship its own client secret under
+    /// `~/.claude/.google/profiles/<name>/client_secret.json`; otherwise the
+    /// shared one is used.
+    pub fn for_profile(home: &Path, profile: Option<&str>) -> Result<Self> {
+        let google_dir = home.join(".claude/.google");
+        let config_path = std::env::var_os("GOOGLE_SKILL_CONFIG")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| google_dir.join("config.json"));
+
+        let profile = match profile {
+            Some(name) => name.to_string(),
+            None => active_profile(&config_path)?,
+        };
+        validate_profile_name(&profile)?;
+
+        let profile_secret = google_dir
+            .join("profiles")
+            .join(&profile)
+            .join("client_secret.json");
+        let credentials_path = if profile_secret.exists() {
+            profile_secret
+        } else {
+            google_dir.join("client_secret.json")
+        };
+
+        Ok(Self {
+            credentials_path,
+            token_path: google_dir.join("token.json"),
+            config_path,
+            profile,
+        })
+    }
+
+    /// The token backend selected by `token_store` in the config file.
+    pub fn token_store(&self) -> Result<Box<dyn TokenStore>> {
+        open_token_store(&self.token_path, &self.config_path)
+    }
+
+    pub fn load_token(&self) -> Result<StoredToken> {
+        self.token_store()?.load(&self.profile)
+    }
+
+    pub fn save_token(&self, token: &StoredToken) -> Result<()> {
+        self.token_store()?.save(&self.profile, token)
+    }
+
+    /// Takes the cross-process token lock, blocking until other processes
+    /// release it. Held while refreshing so parallel invocations share one
+    /// refresh instead of racing to rewrite the token.
+    pub fn lock_tokens(&self) -> Result<TokenLock> {
+        lock_token_file(&self.token_path)
+    }
+
+    /// Removes this profile's token; returns whether one was stored.
+    pub fn delete_token(&self) -> Result<bool> {
+        self.token_store()?.delete(&self.profile)
+    }
+}
+
+/// Exclusive advisory lock on the token store, released on drop.
+pub struct TokenLock {
+    /// `None` when this thread already held the lock; the outer guard
+    /// releases it.
+    held: Option<(PathBuf, fs::File)>,
+}
+
+impl Drop for TokenLock {
+    fn drop(&mut self) {
+        if let Some((lock_path, _)) = self.held.take() {
+            HELD_TOKEN_LOCKS.with_borrow_mut(|held| held.retain(|path| *path != lock_path));
         }
     }
 }
 
+thread_local! {
+    /// Token locks this thread holds. A second `flock` on a new descriptor
+    /// would wait for the first, so nested callers reuse the outer lock.
+    static HELD_TOKEN_LOCKS: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Locks the token file at `token_path` against other processes, or returns
+/// a no-op guard when this thread holds the lock already.
+fn lock_token_file(token_path: &Path) -> Result<TokenLock> {
+    let lock_path = token_path.with_file_name("token.lock");
+    if HELD_TOKEN_LOCKS.with_borrow(|held| held.contains(&lock_path)) {
+        return Ok(TokenLock { held: None });
+    }
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create token directory: {}", parent.display()))?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open token lock: {}", lock_path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+    HELD_TOKEN_LOCKS.with_borrow_mut(|held| held.push(lock_path.clone()));
+    Ok(TokenLock {
+        held: Some((lock_path, file)),
+    })
+}
+
+/// Resolves the active profile: `GOOGLE_SKILL_PROFILE`, then `active_profile`
+/// in the config file, then `default`.
+pub fn active_profile(config_path: &Path) -> Result<String> {
+    if let Some(profile) = std::env::var("GOOGLE_SKILL_PROFILE")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    {
+        return Ok(profile);
+    }
+
+    Ok(SkillConfig::load(config_path)?
+        .active_profile
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string()))
+}
+
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && !name.starts_with('.');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Invalid profile name '{name}': use letters, digits, '-', '_' or '.'"
+        ))
+    }
+}
+
+/// Lists profile names known from the token file and `profiles/` directory.
+pub fn list_profiles(paths: &AuthPaths) -> Result<Vec<String>> {
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+
+    names.extend(paths.token_store()?.profiles()?);
+
+    if let Some(google_dir) = paths.token_path.parent()
+        && let Ok(entries) = fs::read_dir(google_dir.join("profiles"))
+    {
+        names.extend(
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().to_str().map(ToString::to_string)),
+        );
+    }
+
+    names.retain(|n| validate_profile_name(n).is_ok());
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
 #[derive(Debug, Clone)]
 pub struct OAuthClientConfig {
     pub client_id: String,
     pub client_secret: String,
     pub auth_uri: String,
     pub token_uri: String,
+    pub device_auth_uri: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +240,42 @@ pub enum ScopeField {
     Multiple(Vec<String>),
 }
 
+impl ScopeField {
+    pub fn scopes(&self) -> Vec<String> {
+        match self {
+            Self::Single(scope) => scope.split_whitespace().map(ToString::to_string).collect(),
+            Self::Multiple(scopes) => scopes.clone(),
+        }
+    }
+}
+
+/// Returns the entries of `required` that `token` was not granted. A broader
+/// scope covers its narrower variants (`drive` covers `drive.readonly`).
+/// Tokens that never recorded their scopes are assumed to be sufficient.
+pub fn missing_scopes(token: &StoredToken, required: &[&str]) -> Vec<String> {
+    let granted = token
+        .scope
+        .as_ref()
+        .map(ScopeField::scopes)
+        .unwrap_or_default();
+    if granted.is_empty() {
+        return Vec::new();
+    }
+
+    required
+        .iter()
+        .filter(|scope| {
+            !granted.iter().any(|g| {
+                g == *scope
+                    || scope
+                        .strip_prefix(g.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+        })
+        .map(ToString::to_string)
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredToken {
     pub client_id: String,
@@ -67,12 +285,17 @@ pub struct StoredToken {
     #[serde(default)]
     pub scope: Option<ScopeField>,
     pub expiration_time_millis: i64,
+    /// User impersonated by a service account token, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct SecretFile {
     installed: Option<SecretSection>,
     web: Option<SecretSection>,
+    #[serde(rename = "type")]
+    key_type: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -91,10 +314,31 @@ struct TokenResponse {
     scope: Option<String>,
 }
 
+/// Response to a device authorization request (RFC 8628 section 3.2).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    #[serde(alias = "verification_uri")]
+    pub verification_url: String,
+    pub expires_in: i64,
+    #[serde(default = "default_device_interval")]
+    pub interval: u64,
+}
+
+fn default_device_interval() -> u64 {
+    5
+}
+
 #[derive(Debug)]
 pub enum TokenState {
     Authorized(StoredToken),
-    AuthorizationRequired { auth_url: String },
+    AuthorizationRequired,
+    /// The stored token works but lacks scopes this command needs;
+    /// `auth login --scope` asks for just those on top of the existing grant.
+    ScopesMissing {
+        missing: Vec<String>,
+    },
 }
 
 pub fn load_oauth_client_config(path: &Path) -> Result<OAuthClientConfig> {
@@ -103,10 +347,15 @@ pub fn load_oauth_client_config(path: &Path) -> Result<OAuthClientConfig> {
     let parsed: SecretFile = serde_json::from_str(&raw)
         .with_context(|| format!("Failed to parse credentials JSON: {}", path.display()))?;
 
-    let section = parsed
-        .installed
-        .or(parsed.web)
-        .ok_or_else(|| anyhow!("Expected 'installed' or 'web' section in client secret JSON"))?;
+    let section = parsed.installed.or(parsed.web).ok_or_else(|| {
+        if parsed.key_type.as_deref() == Some("service_account") {
+            anyhow!(
+                "Credentials file is a service account key; it does not use the OAuth login flow"
+            )
+        } else {
+            anyhow!("Expected 'installed' or 'web' section in client secret JSON")
+        }
+    })?;
 
     Ok(OAuthClientConfig {
         client_id: section.client_id,
@@ -117,73 +366,163 @@ pub fn load_oauth_client_config(path: &Path) -> Result<OAuthClientConfig> {
         token_uri: section
             .token_uri
             .unwrap_or_else(|| DEFAULT_TOKEN_URI.to_string()),
+        device_auth_uri: DEFAULT_DEVICE_AUTH_URI.to_string(),
     })
 }
 
+/// Loads the client secret for `paths` and applies any OAuth endpoint
+/// overrides from the skill config or environment.
+pub fn load_client_config(paths: &AuthPaths) -> Result<OAuthClientConfig> {
+    let mut config = load_oauth_client_config(&paths.credentials_path)?;
+    let endpoints = Endpoints::resolve(&paths.config_path)?;
+    if let Some(auth_uri) = endpoints.oauth_auth {
+        config.auth_uri = auth_uri;
+    }
+    if let Some(token_uri) = endpoints.oauth_token {
+        config.token_uri = token_uri;
+    }
+    if let Some(device_auth_uri) = endpoints.oauth_device {
+        config.device_auth_uri = device_auth_uri;
+    }
+    Ok(config)
+}
+
+/// PKCE verifier/challenge pair (RFC 7636, `S256` method).
+#[derive(Debug, Clone)]
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn generate() -> Self {
+        let verifier = random_url_token(32);
+        let digest = ring::digest::digest(&ring::digest::SHA256, verifier.as_bytes());
+        Self {
+            challenge: URL_SAFE_NO_PAD.encode(digest.as_ref()),
+            verifier,
+        }
+    }
+}
+
+/// Returns `len` random bytes encoded as unpadded base64url.
+pub fn random_url_token(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::random::<u8>()).collect();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Builds the legacy out-of-band authorization URL used by `auth code`.
 pub fn build_auth_url(config: &OAuthClientConfig, scopes: &[&str]) -> Result<String> {
+    build_auth_url_with(config, scopes, OOB_REDIRECT_URI, None, None)
+}
+
+/// Builds an authorization URL for `redirect_uri`, adding the PKCE challenge
+/// and `state` parameter when given.
+pub fn build_auth_url_with(
+    config: &OAuthClientConfig,
+    scopes: &[&str],
+    redirect_uri: &str,
+    pkce: Option<&Pkce>,
+    state: Option<&str>,
+) -> Result<String> {
     let mut url = Url::parse(&config.auth_uri).context("Invalid auth URI")?;
     {
         let mut qp = url.query_pairs_mut();
         qp.append_pair("client_id", &config.client_id);
-        qp.append_pair("redirect_uri", OOB_REDIRECT_URI);
+        qp.append_pair("redirect_uri", redirect_uri);
         qp.append_pair("response_type", "code");
         qp.append_pair("scope", &scopes.join(" "));
         qp.append_pair("access_type", "offline");
+        qp.append_pair("include_granted_scopes", "true");
         qp.append_pair("prompt", "consent");
+        if let Some(pkce) = pkce {
+            qp.append_pair("code_challenge", &pkce.challenge);
+            qp.append_pair("code_challenge_method", "S256");
+        }
+        if let Some(state) = state {
+            qp.append_pair("state", state);
+        }
     }
     Ok(url.to_string())
 }
 
-pub fn load_stored_token(path: &Path) -> Result<StoredToken> {
+/// Loads `profile`'s token. A bare JSON token file (the pre-profile format)
+/// is treated as the `default` profile.
+pub fn load_stored_token(path: &Path, profile: &str) -> Result<StoredToken> {
+    let entry = read_token_entry(path, profile)?.ok_or_else(|| {
+        TokenStoreError::NotFound(format!(
+            "No token stored for profile '{profile}' in {}",
+            path.display()
+        ))
+    })?;
+    if is_encrypted_entry(&entry) {
+        return Err(TokenStoreError::Passphrase(format!(
+            "Token for profile '{profile}' is encrypted; set GOOGLE_SKILL_TOKEN_PASSPHRASE and enable token_store.encrypt"
+        ))
+        .into());
+    }
+
+    serde_json::from_str::<StoredToken>(&entry).with_context(|| {
+        TokenStoreError::Corrupt(format!("Failed parsing {profile} token payload as JSON"))
+    })
+}
+
+/// Returns `profile`'s raw entry from the token file: the token JSON, or an
+/// encrypted envelope written by the encrypting file store.
+pub(crate) fn read_token_entry(path: &Path, profile: &str) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
     let raw = fs::read_to_string(path)
         .with_context(|| format!("Failed to read token file: {}", path.display()))?;
 
-    if let Ok(token) = serde_json::from_str::<StoredToken>(&raw) {
-        return Ok(token);
+    if serde_json::from_str::<StoredToken>(&raw).is_ok() {
+        return Ok((profile == DEFAULT_PROFILE).then(|| raw.trim().to_string()));
     }
 
     let yaml: YamlValue = serde_yaml::from_str(&raw).with_context(|| {
-        format!(
+        TokenStoreError::Corrupt(format!(
             "Failed to parse token file as JSON or YAML: {}",
             path.display()
-        )
+        ))
     })?;
 
-    if let Some(token) = parse_token_from_yaml(&yaml)? {
-        return Ok(token);
-    }
-
-    Err(anyhow!(
-        "Token file format not recognized: {}",
-        path.display()
-    ))
-}
-
-fn parse_token_from_yaml(yaml: &YamlValue) -> Result<Option<StoredToken>> {
-    let Some(mapping) = yaml.as_mapping() else {
-        return Ok(None);
-    };
-
-    let key = YamlValue::String("default".to_string());
-    let Some(value) = mapping.get(&key) else {
+    let Some(value) = yaml
+        .as_mapping()
+        .and_then(|mapping| mapping.get(YamlValue::String(profile.to_string())))
+    else {
         return Ok(None);
     };
 
     match value {
-        YamlValue::String(json_payload) => {
-            let parsed = serde_json::from_str::<StoredToken>(json_payload)
-                .context("Failed parsing YAML default token payload as JSON")?;
-            Ok(Some(parsed))
-        }
+        YamlValue::String(payload) => Ok(Some(payload.clone())),
         other => {
-            let parsed = serde_yaml::from_value::<StoredToken>(other.clone())
-                .context("Failed parsing YAML default token object")?;
-            Ok(Some(parsed))
+            let parsed =
+                serde_yaml::from_value::<StoredToken>(other.clone()).with_context(|| {
+                    TokenStoreError::Corrupt(format!("Failed parsing YAML {profile} token object"))
+                })?;
+            serde_json::to_string(&parsed)
+                .map(Some)
+                .context("Failed serializing token JSON payload")
         }
     }
 }
 
-pub fn save_stored_token(path: &Path, token: &StoredToken) -> Result<()> {
+pub(crate) fn is_encrypted_entry(entry: &str) -> bool {
+    entry.starts_with("enc:")
+}
+
+/// Stores `token` under `profile`, keeping every other profile's entry.
+pub fn save_stored_token(path: &Path, profile: &str, token: &StoredToken) -> Result<()> {
+    let payload = serde_json::to_string(token).context("Failed serializing token JSON payload")?;
+    write_token_entry(path, profile, payload)
+}
+
+/// Writes `entry` under `profile`, keeping every other profile's entry. The
+/// file is created (or tightened) to owner-only permissions. The token lock
+/// is held from reading the file to replacing it, so a concurrent write for
+/// another profile is not lost.
+pub(crate) fn write_token_entry(path: &Path, profile: &str, entry: String) -> Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).with_context(|| {
             format!(
@@ -193,40 +532,153 @@ pub fn save_stored_token(path: &Path, token: &StoredToken) -> Result<()> {
         })?;
     }
 
-    let payload = serde_json::to_string(token).context("Failed serializing token JSON payload")?;
-
-    let mut map = Mapping::new();
+    let _lock = lock_token_file(path)?;
+    let mut map = existing_token_entries(path)?;
     map.insert(
-        YamlValue::String("default".to_string()),
-        YamlValue::String(payload),
+        YamlValue::String(profile.to_string()),
+        YamlValue::String(entry),
     );
 
     let serialized = serde_yaml::to_string(&map).context("Failed serializing token YAML")?;
-    fs::write(path, serialized)
-        .with_context(|| format!("Failed writing token file: {}", path.display()))?;
+    write_private_file(path, serialized.as_bytes())
+        .with_context(|| format!("Failed writing token file: {}", path.display()))
+}
 
-    Ok(())
+/// Removes `profile`'s entry from the token file, deleting the file once no
+/// profiles remain. Returns whether an entry was removed.
+pub fn delete_stored_token(path: &Path, profile: &str) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    let _lock = lock_token_file(path)?;
+    let mut map = existing_token_entries(path)?;
+    if map.remove(YamlValue::String(profile.to_string())).is_none() {
+        return Ok(false);
+    }
+
+    if map.is_empty() {
+        fs::remove_file(path)
+            .with_context(|| format!("Failed deleting token file: {}", path.display()))?;
+    } else {
+        let serialized = serde_yaml::to_string(&map).context("Failed serializing token YAML")?;
+        write_private_file(path, serialized.as_bytes())
+            .with_context(|| format!("Failed writing token file: {}", path.display()))?;
+    }
+
+    Ok(true)
+}
+
+/// Profiles with an entry in the token file.
+pub(crate) fn token_file_profiles(path: &Path) -> Result<Vec<String>> {
+    Ok(existing_token_entries(path)?
+        .keys()
+        .filter_map(|k| k.as_str().map(ToString::to_string))
+        .collect())
+}
+
+/// Reads the current token file as a profile map, converting a legacy bare
+/// JSON token into a `default` entry. A missing file yields an empty map; one
+/// that can't be parsed is an error, since rewriting it would drop every
+/// other profile's token.
+fn existing_token_entries(path: &Path) -> Result<Mapping> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Mapping::new()),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("Failed to read token file: {}", path.display()));
+        }
+    };
+
+    if serde_json::from_str::<StoredToken>(&raw).is_ok() {
+        let mut map = Mapping::new();
+        map.insert(
+            YamlValue::String(DEFAULT_PROFILE.to_string()),
+            YamlValue::String(raw.trim().to_string()),
+        );
+        return Ok(map);
+    }
+
+    match serde_yaml::from_str::<YamlValue>(&raw) {
+        Ok(YamlValue::Mapping(map)) => Ok(map),
+        Ok(YamlValue::Null) => Ok(Mapping::new()),
+        _ => Err(TokenStoreError::Corrupt(format!(
+            "Failed to parse token file as JSON or YAML: {}",
+            path.display()
+        ))
+        .into()),
+    }
+}
+
+/// Replaces `path` with `contents` atomically: the data goes to a temporary
+/// file in the same directory (mode 0600 on Unix) which is then renamed over
+/// the target, so readers never observe a partially written file.
+fn write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let result = options.open(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    let result = result.and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
 }
 
+/// Exchanges an out-of-band authorization code (the `auth code` flow).
 pub fn complete_authorization(
     config: &OAuthClientConfig,
     code: &str,
     existing_refresh_token: Option<String>,
+) -> Result<StoredToken> {
+    exchange_authorization_code(config, code, OOB_REDIRECT_URI, None, existing_refresh_token)
+}
+
+/// Exchanges an authorization code obtained for `redirect_uri`, sending the
+/// PKCE verifier when the authorization request carried a challenge.
+pub fn exchange_authorization_code(
+    config: &OAuthClientConfig,
+    code: &str,
+    redirect_uri: &str,
+    code_verifier: Option<&str>,
+    existing_refresh_token: Option<String>,
 ) -> Result<StoredToken> {
     let client = Client::builder()
         .user_agent("google-docs-skill/1.0")
         .build()
         .context("Failed building HTTP client")?;
 
+    let mut form = vec![
+        ("code", code),
+        ("client_id", config.client_id.as_str()),
+        ("client_secret", config.client_secret.as_str()),
+        ("redirect_uri", redirect_uri),
+        ("grant_type", "authorization_code"),
+    ];
+    if let Some(verifier) = code_verifier {
+        form.push(("code_verifier", verifier));
+    }
+
     let resp = client
         .post(&config.token_uri)
-        .form(&[
-            ("code", code),
-            ("client_id", config.client_id.as_str()),
-            ("client_secret", config.client_secret.as_str()),
-            ("redirect_uri", OOB_REDIRECT_URI),
-            ("grant_type", "authorization_code"),
-        ])
+        .form(&form)
         .send()
         .context("Token exchange request failed")?;
 
@@ -244,6 +696,14 @@ pub fn complete_authorization(
     let payload: TokenResponse = serde_json::from_str(&body)
         .with_context(|| format!("Failed parsing token exchange response JSON. Body: {body}"))?;
 
+    stored_token_from_response(config, payload, existing_refresh_token)
+}
+
+fn stored_token_from_response(
+    config: &OAuthClientConfig,
+    payload: TokenResponse,
+    existing_refresh_token: Option<String>,
+) -> Result<StoredToken> {
     let refresh_token = payload
         .refresh_token
         .or(existing_refresh_token)
@@ -257,30 +717,153 @@ pub fn complete_authorization(
             .scope
             .map(|s| ScopeField::Multiple(s.split_whitespace().map(ToString::to_string).collect())),
         expiration_time_millis: compute_expiration(payload.expires_in),
+        subject: None,
     })
 }
 
+/// Starts the device authorization grant, returning the code the user must
+/// enter at `verification_url`.
+pub fn request_device_code(
+    config: &OAuthClientConfig,
+    scopes: &[&str],
+) -> Result<DeviceAuthorization> {
+    let client = Client::builder()
+        .user_agent("google-docs-skill/1.0")
+        .build()
+        .context("Failed building HTTP client")?;
+
+    let scope = scopes.join(" ");
+    let resp = client
+        .post(&config.device_auth_uri)
+        .form(&[
+            ("client_id", config.client_id.as_str()),
+            ("scope", scope.as_str()),
+        ])
+        .send()
+        .context("Device authorization request failed")?;
+
+    let status = resp.status();
+    let body = resp
+        .text()
+        .context("Failed reading device authorization response body")?;
+
+    if !status.is_success() {
+        let msg = extract_google_error_message(&body)
+            .unwrap_or_else(|| format!("Device authorization failed with status {status}"));
+        return Err(anyhow!("{msg}"));
+    }
+
+    serde_json::from_str(&body)
+        .with_context(|| format!("Failed parsing device authorization response JSON. Body: {body}"))
+}
+
+/// Polls the token endpoint until the user approves or denies `device`, or
+/// the device code expires. Honors `authorization_pending` by waiting the
+/// advertised interval and `slow_down` by extending it by five seconds.
+pub fn poll_device_token(
+    config: &OAuthClientConfig,
+    device: &DeviceAuthorization,
+    existing_refresh_token: Option<String>,
+) -> Result<StoredToken> {
+    let client = Client::builder()
+        .user_agent("google-docs-skill/1.0")
+        .build()
+        .context("Failed building HTTP client")?;
+
+    let deadline = Instant::now() + Duration::from_secs(device.expires_in.max(0) as u64);
+    let mut interval = Duration::from_secs(device.interval);
+
+    loop {
+        std::thread::sleep(interval);
+        if Instant::now() >= deadline {
+            return Err(anyhow!(
+                "Device code expired before authorization was granted"
+            ));
+        }
+
+        let resp = client
+            .post(&config.token_uri)
+            .form(&[
+                ("client_id", config.client_id.as_str()),
+                ("client_secret", config.client_secret.as_str()),
+                ("device_code", device.device_code.as_str()),
+                ("grant_type", DEVICE_CODE_GRANT_TYPE),
+            ])
+            .send()
+            .context("Device token request failed")?;
+
+        let status = resp.status();
+        let body = resp
+            .text()
+            .context("Failed reading device token response body")?;
+
+        if status.is_success() {
+            let payload: TokenResponse = serde_json::from_str(&body).with_context(|| {
+                format!("Failed parsing device token response JSON. Body: {body}")
+            })?;
+            return stored_token_from_response(config, payload, existing_refresh_token);
+        }
+
+        let error = serde_json::from_str::<Value>(&body).ok().and_then(|v| {
+            v.get("error")
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+        });
+        match error.as_deref() {
+            Some("authorization_pending") => {}
+            Some("slow_down") => interval += Duration::from_secs(5),
+            Some("access_denied") => return Err(anyhow!("Authorization denied by the user")),
+            Some("expired_token") => {
+                return Err(anyhow!(
+                    "Device code expired before authorization was granted"
+                ));
+            }
+            _ => {
+                let msg = extract_google_error_message(&body)
+                    .unwrap_or_else(|| format!("Device token request failed with status {status}"));
+                return Err(anyhow!("{msg}"));
+            }
+        }
+    }
+}
+
+/// Loads `paths`' token for `scopes`, refreshing it when it has expired. A
+/// profile without a token needs authorization; any other token store
+/// failure is returned as an error carrying a [`TokenStoreError`].
 pub fn ensure_token(paths: &AuthPaths, scopes: &[&str]) -> Result<TokenState> {
-    let config = load_oauth_client_config(&paths.credentials_path)?;
+    if paths.credentials_path.exists()
+        && let Some(key) = load_service_account_key(&paths.credentials_path)?
+    {
+        return ensure_service_account_token(paths, &key, scopes).map(TokenState::Authorized);
+    }
 
-    let mut token = match load_stored_token(&paths.token_path) {
+    let config = load_client_config(paths)?;
+
+    let mut token = match paths.load_token() {
         Ok(t) => t,
-        Err(_) => {
-            return Ok(TokenState::AuthorizationRequired {
-                auth_url: build_auth_url(&config, scopes)?,
-            });
+        Err(err) if matches!(err.downcast_ref(), Some(TokenStoreError::NotFound(_))) => {
+            return Ok(TokenState::AuthorizationRequired);
         }
+        Err(err) => return Err(err),
     };
 
+    let missing = missing_scopes(&token, scopes);
+    if !missing.is_empty() {
+        return Ok(TokenState::ScopesMissing { missing });
+    }
+
     if token_is_expired(&token) {
         if token.refresh_token.is_none() {
-            return Ok(TokenState::AuthorizationRequired {
-                auth_url: build_auth_url(&config, scopes)?,
-            });
+            return Ok(TokenState::AuthorizationRequired);
         }
 
-        refresh_token(&config, &mut token)?;
-        save_stored_token(&paths.token_path, &token)?;
+        let _lock = paths.lock_tokens()?;
+        // Another process may have refreshed while this one waited.
+        token = paths.load_token()?;
+        if token_is_expired(&token) {
+            refresh_token(&config, &mut token)?;
+            paths.save_token(&token)?;
+        }
     }
 
     Ok(TokenState::Authorized(token))
@@ -364,19 +947,153 @@ pub fn extract_google_error_message(body: &str) -> Option<String> {
         })
 }
 
-pub fn auth_required_payload(auth_url: &str, message: &str, script_hint: &str) -> Value {
+/// Describes the stored token for `paths` without contacting Google.
+pub fn token_status_payload(paths: &AuthPaths) -> Value {
+    let base = json!({
+        "status": "success",
+        "operation": "auth_status",
+        "profile": paths.profile,
+        "token_path": paths.token_path.display().to_string(),
+        "credentials_path": paths.credentials_path.display().to_string(),
+        "token_store": paths.token_store().map(|store| store.name()).ok()
+    });
+
+    let Ok(token) = paths.load_token() else {
+        let mut payload = base;
+        payload["authorized"] = json!(false);
+        return payload;
+    };
+
+    let expires_at = chrono::DateTime::from_timestamp_millis(token.expiration_time_millis)
+        .map(|t| t.to_rfc3339());
+    let remaining = (token.expiration_time_millis - Utc::now().timestamp_millis()) / 1000;
+
+    let mut payload = base;
+    payload["authorized"] = json!(true);
+    payload["client_id"] = json!(token.client_id);
+    payload["scopes"] = json!(
+        token
+            .scope
+            .as_ref()
+            .map(ScopeField::scopes)
+            .unwrap_or_default()
+    );
+    payload["expires_at"] = json!(expires_at);
+    payload["expires_in_seconds"] = json!(remaining.max(0));
+    payload["expired"] = json!(token_is_expired(&token));
+    payload["has_refresh_token"] = json!(token.refresh_token.is_some());
+    if let Some(subject) = token.subject {
+        payload["subject"] = json!(subject);
+    }
+    payload
+}
+
+/// Looks up the signed-in user via the Drive `about` endpoint.
+pub fn whoami(client: &GoogleClient) -> std::result::Result<Value, GoogleApiError> {
+    let about = client.get_json(
+        &client.endpoints().drive_url("about"),
+        &[("fields".to_string(), "user".to_string())],
+    )?;
+    let user = about.get("user").cloned().unwrap_or_else(|| json!({}));
+
+    Ok(json!({
+        "status": "success",
+        "operation": "whoami",
+        "email": user.get("emailAddress").and_then(|v| v.as_str()),
+        "display_name": user.get("displayName").and_then(|v| v.as_str()),
+        "photo_link": user.get("photoLink").and_then(|v| v.as_str())
+    }))
+}
+
+/// Revokes the stored token with Google (refresh token when available, so the
+/// whole grant is dropped) and removes it from the token file. A failed
+/// revocation is reported but does not keep the local token around.
+pub fn logout(paths: &AuthPaths) -> Result<Value> {
+    let token = paths.load_token().ok();
+    let revoke_uri = Endpoints::resolve(&paths.config_path)?
+        .oauth_revoke
+        .unwrap_or_else(|| DEFAULT_REVOKE_URI.to_string());
+
+    let revoke_result = token.as_ref().map(|token| {
+        let credential = token
+            .refresh_token
+            .as_deref()
+            .unwrap_or(&token.access_token);
+        revoke_token(&revoke_uri, credential)
+    });
+    let removed = paths.delete_token()?;
+
+    let mut payload = json!({
+        "status": "success",
+        "operation": "logout",
+        "profile": paths.profile,
+        "revoked": matches!(revoke_result, Some(Ok(()))),
+        "token_removed": removed
+    });
+    if let Some(Err(err)) = revoke_result {
+        payload["revoke_error"] = json!(err.to_string());
+    }
+    Ok(payload)
+}
+
+pub fn revoke_token(revoke_uri: &str, token: &str) -> Result<()> {
+    let client = Client::builder()
+        .user_agent("google-docs-skill/1.0")
+        .build()
+        .context("Failed building HTTP client")?;
+
+    let resp = client
+        .post(revoke_uri)
+        .form(&[("token", token)])
+        .send()
+        .context("Token revocation request failed")?;
+
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let body = resp.text().unwrap_or_default();
+    let msg = extract_google_error_message(&body)
+        .unwrap_or_else(|| format!("Token revocation failed with status {status}"));
+    Err(anyhow!("{msg}"))
+}
+
+/// The error for a token lacking `missing`. Its `login_command` passes each
+/// missing scope to `auth login --scope`, since a command may need scopes
+/// beyond those its binary logs in with.
+pub fn scopes_missing_payload(missing: &[String], script_hint: &str) -> Value {
+    let scope_flags: String = missing
+        .iter()
+        .map(|scope| format!(" --scope {scope}"))
+        .collect();
+    json!({
+      "status": "error",
+      "error_code": "SCOPES_MISSING",
+      "message": "The stored authorization does not cover this command. Grant the additional access and retry.",
+      "missing_scopes": missing,
+      "login_command": format!("{script_hint} auth login{scope_flags}"),
+      "instructions": [
+        format!("1. Run: {script_hint} auth login{scope_flags}"),
+        format!("   If you are using wrappers: scripts/{script_hint} auth login{scope_flags}"),
+        "2. Approve the additional access; previously granted access is kept",
+        "3. Retry the original command"
+      ]
+    })
+}
+
+pub fn auth_required_payload(message: &str, script_hint: &str) -> Value {
     json!({
       "status": "error",
       "error_code": "AUTH_REQUIRED",
       "message": message,
-      "auth_url": auth_url,
+      "login_command": format!("{script_hint} auth login"),
       "instructions": [
-        "1. Visit the authorization URL",
-        "2. Grant access in your browser",
-        "3. Copy the authorization code",
-        format!("4. Run: {script_hint} auth <code>"),
-        format!("   If you are using wrappers: scripts/{script_hint} auth <code>"),
-        "5. Retry the original command"
+        format!("1. Run: {script_hint} auth login"),
+        format!("   If you are using wrappers: scripts/{script_hint} auth login"),
+        "2. Grant access in the browser window that opens (or open the printed URL)",
+        "3. Retry the original command",
+        format!("Without a browser on this machine, run {script_hint} auth device instead")
       ]
     })
 }
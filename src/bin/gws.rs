@@ -0,0 +1,3 @@
+fn main() {
+    std::process::exit(google_docs_rust::cli::run_gws(std::env::args_os()));
+}
@@ -0,0 +1,583 @@
+pub mod auth;
+pub mod docs;
+pub mod drive;
+pub mod sheets;
+
+use crate::auth::{DOCS_SCOPE, DRIVE_SCOPE, SHEETS_SCOPE};
+use crate::docs::DocsError;
+use crate::google_api::{GoogleApiError, GoogleClient, map_api_error};
+use crate::io_helpers::{print_json, success_payload, value_to_i64};
+use anyhow::{Context, Result};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use clap_complete::Shell;
+use serde_json::{Map, Value, json};
+use std::ffi::OsString;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_OPERATION_FAILED: i32 = 1;
+pub const EXIT_AUTH_ERROR: i32 = 2;
+pub const EXIT_API_ERROR: i32 = 3;
+pub const EXIT_INVALID_ARGS: i32 = 4;
+
+/// Scopes `gws auth login` asks for, since one binary covers every service.
+pub const GWS_SCOPES: &[&str] = &[DOCS_SCOPE, DRIVE_SCOPE, SHEETS_SCOPE];
+
+const EXIT_CODES_HELP: &str = "Exit codes:\n  0  Success\n  1  Operation failed\n  2  Authentication error\n  3  API error\n  4  Invalid arguments";
+
+/// A command group (`docs`, `drive`, `sheets`): its operations, the OAuth
+/// scopes they need and the function that runs one of them.
+pub struct Service {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub scopes: &'static [&'static str],
+    /// Scopes particular operations need on top of `scopes`. `auth login`
+    /// only asks for these when given with `--scope`.
+    pub extra_scopes: &'static [ExtraScopes],
+    /// Whether a piped stdin is read as the input when no fields are given.
+    /// Only the services whose scripts always took JSON on stdin do this;
+    /// the others need `--input -`, so an inherited stdin never blocks them.
+    pub piped_input: bool,
+    pub operations: &'static [Operation],
+    pub run: fn(&GoogleClient, &str, &Value) -> i32,
+}
+
+/// Scopes `operation` needs beyond its service's, when its input sets
+/// `field` (or always, without one).
+pub struct ExtraScopes {
+    pub operation: &'static str,
+    pub field: Option<&'static str>,
+    pub scopes: &'static [&'static str],
+}
+
+pub const SERVICES: [&Service; 3] = [&docs::SERVICE, &drive::SERVICE, &sheets::SERVICE];
+
+/// One operation. Its input is a JSON object whose keys are the field names;
+/// each field can also be given as a `--flag` (underscores become dashes).
+pub struct Operation {
+    pub name: &'static str,
+    pub about: &'static str,
+    pub fields: &'static [Field],
+}
+
+pub struct Field {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    pub help: &'static str,
+    pub positional: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    String,
+    Integer,
+    Number,
+    /// `--flag` alone means true; `--flag false` sets false.
+    Boolean,
+    /// Repeatable; collected into a JSON array of strings.
+    List,
+    /// Parsed as JSON, e.g. `--values '[[1, 2]]'`.
+    Json,
+}
+
+impl Field {
+    pub const fn string(name: &'static str, help: &'static str) -> Self {
+        Self::new(name, FieldKind::String, help)
+    }
+
+    pub const fn integer(name: &'static str, help: &'static str) -> Self {
+        Self::new(name, FieldKind::Integer, help)
+    }
+
+    pub const fn number(name: &'static str, help: &'static str) -> Self {
+        Self::new(name, FieldKind::Number, help)
+    }
+
+    pub const fn boolean(name: &'static str, help: &'static str) -> Self {
+        Self::new(name, FieldKind::Boolean, help)
+    }
+
+    pub const fn list(name: &'static str, help: &'static str) -> Self {
+        Self::new(name, FieldKind::List, help)
+    }
+
+    pub const fn json(name: &'static str, help: &'static str) -> Self {
+        Self::new(name, FieldKind::Json, help)
+    }
+
+    /// Takes the field as a positional argument instead of a flag.
+    pub const fn positional(self) -> Self {
+        Self {
+            positional: true,
+            ..self
+        }
+    }
+
+    const fn new(name: &'static str, kind: FieldKind, help: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            help,
+            positional: false,
+        }
+    }
+}
+
+/// The `gws` command tree: `auth`, one group per service and `completions`.
+pub fn gws_command() -> Command {
+    let mut command = root_command("gws")
+        .about("Google Docs, Drive and Sheets from the command line")
+        .subcommand(auth::command());
+    for service in SERVICES {
+        command = command.subcommand(
+            Command::new(service.name)
+                .about(service.about)
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommands(service.operations.iter().map(operation_command)),
+        );
+    }
+    command.subcommand(completions_command())
+}
+
+/// The command tree of a single-service binary (`docs_manager` and friends):
+/// the service's operations at the top level, plus `auth` and `completions`.
+pub fn service_command(service: &Service, program: &str) -> Command {
+    root_command(program)
+        .about(service.about)
+        .subcommand(auth::command())
+        .subcommands(service.operations.iter().map(operation_command))
+        .subcommand(completions_command())
+}
+
+/// Entry point of the `gws` binary; returns the process exit code.
+pub fn run_gws(args: impl IntoIterator<Item = OsString>) -> i32 {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let mut command = gws_command();
+    let matches = match command.try_get_matches_from_mut(&args) {
+        Ok(matches) => matches,
+        Err(err) => return report_usage_error(err, &gws_commands(&args)),
+    };
+    let profile = matches.get_one::<String>("profile").map(String::as_str);
+
+    match matches.subcommand() {
+        Some(("auth", auth_matches)) => auth::run("gws", GWS_SCOPES, profile, auth_matches),
+        Some(("completions", shell_matches)) => print_completions(&mut command, shell_matches),
+        Some((name, service_matches)) => {
+            let Some(service) = SERVICES.iter().find(|service| service.name == name) else {
+                return EXIT_INVALID_ARGS;
+            };
+            let Some((operation, operation_matches)) = service_matches.subcommand() else {
+                return EXIT_INVALID_ARGS;
+            };
+            run_operation("gws", service, operation, operation_matches, profile)
+        }
+        None => EXIT_INVALID_ARGS,
+    }
+}
+
+/// Entry point of the single-service binaries, kept as aliases of
+/// `gws <service>`; returns the process exit code.
+pub fn run_service(service: &Service, args: impl IntoIterator<Item = OsString>) -> i32 {
+    let args: Vec<OsString> = args.into_iter().collect();
+    let program = args
+        .first()
+        .and_then(|arg| Path::new(arg).file_stem())
+        .and_then(|name| name.to_str())
+        .map(ToString::to_string)
+        .unwrap_or_else(|| format!("{}_manager", service.name));
+
+    let mut command = service_command(service, &program);
+    let matches = match command.try_get_matches_from_mut(&args) {
+        Ok(matches) => matches,
+        Err(err) => return report_usage_error(err, &service_commands(service, &args)),
+    };
+    let profile = matches.get_one::<String>("profile").map(String::as_str);
+
+    match matches.subcommand() {
+        Some(("auth", auth_matches)) => auth::run(&program, service.scopes, profile, auth_matches),
+        Some(("completions", shell_matches)) => print_completions(&mut command, shell_matches),
+        Some((operation, operation_matches)) => {
+            run_operation(&program, service, operation, operation_matches, profile)
+        }
+        None => EXIT_INVALID_ARGS,
+    }
+}
+
+fn root_command(name: &str) -> Command {
+    Command::new(name.to_string())
+        .version(env!("CARGO_PKG_VERSION"))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .after_help(EXIT_CODES_HELP)
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .value_name("NAME")
+                .global(true)
+                .help("Auth profile to use (default: the active profile)"),
+        )
+}
+
+fn completions_command() -> Command {
+    Command::new("completions")
+        .about("Print a shell completion script")
+        .arg(
+            Arg::new("shell")
+                .required(true)
+                .value_parser(value_parser!(Shell)),
+        )
+}
+
+fn operation_command(operation: &Operation) -> Command {
+    let mut command = Command::new(operation.name).about(operation.about).arg(
+        Arg::new("input")
+            .long("input")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("Read the JSON input from FILE ('-' for stdin); flags override its fields"),
+    );
+
+    for field in operation.fields {
+        let mut arg = Arg::new(field.name).help(field.help);
+        arg = if field.positional {
+            arg.value_name(field.name.to_uppercase())
+        } else {
+            arg.long(field.name.replace('_', "-"))
+        };
+        arg = match field.kind {
+            FieldKind::String => arg,
+            FieldKind::Integer => arg.value_parser(value_parser!(i64)),
+            FieldKind::Number => arg.value_parser(value_parser!(f64)),
+            FieldKind::Boolean => arg
+                .value_parser(value_parser!(bool))
+                .num_args(0..=1)
+                .default_missing_value("true"),
+            FieldKind::List => arg.action(ArgAction::Append),
+            FieldKind::Json => arg.value_name("JSON").value_parser(parse_json_value),
+        };
+        command = command.arg(arg);
+    }
+
+    command
+}
+
+fn parse_json_value(raw: &str) -> std::result::Result<Value, String> {
+    serde_json::from_str(raw).map_err(|err| format!("invalid JSON: {err}"))
+}
+
+fn run_operation(
+    program: &str,
+    service: &Service,
+    operation: &str,
+    matches: &ArgMatches,
+    profile: Option<&str>,
+) -> i32 {
+    let Some(spec) = service.operations.iter().find(|op| op.name == operation) else {
+        return EXIT_INVALID_ARGS;
+    };
+
+    let input = match operation_input(service, spec, matches) {
+        Ok(input) => input,
+        Err(err) => {
+            print_json(&json!({
+                "status": "error",
+                "error_code": "INVALID_INPUT",
+                "message": format!("{err:#}")
+            }));
+            return EXIT_INVALID_ARGS;
+        }
+    };
+
+    let scopes: Vec<&str> = service
+        .extra_scopes
+        .iter()
+        .filter(|extra| extra.operation == operation)
+        .filter(|extra| {
+            extra
+                .field
+                .is_none_or(|field| input.get(field).is_some_and(|value| !value.is_null()))
+        })
+        .flat_map(|extra| extra.scopes.iter().copied())
+        .fold(service.scopes.to_vec(), |mut scopes, scope| {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+            scopes
+        });
+
+    let client = match auth::initialize_client(program, &scopes, profile) {
+        Ok(client) => client,
+        Err(exit_code) => return exit_code,
+    };
+
+    (service.run)(&client, operation, &input)
+}
+
+/// Builds an operation's input: the JSON object from `--input` (or, for a
+/// service with `piped_input`, from stdin when it is piped and no fields
+/// were given on the command line), overlaid with the fields given as flags.
+fn operation_input(
+    service: &Service,
+    operation: &Operation,
+    matches: &ArgMatches,
+) -> Result<Value> {
+    let flags = operation
+        .fields
+        .iter()
+        .filter_map(|field| Some((field.name.to_string(), flag_value(field, matches)?)))
+        .collect::<Map<_, _>>();
+
+    let base = match matches.get_one::<PathBuf>("input") {
+        Some(path) if path.as_os_str() == "-" => read_stdin()?,
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        None if service.piped_input && flags.is_empty() && !io::stdin().is_terminal() => {
+            read_stdin()?
+        }
+        None => String::new(),
+    };
+
+    let mut input = if base.trim().is_empty() {
+        Map::new()
+    } else {
+        match serde_json::from_str(&base).context("Input is not valid JSON")? {
+            Value::Object(fields) => fields,
+            _ => anyhow::bail!("Input must be a JSON object"),
+        }
+    };
+    input.extend(flags);
+    Ok(Value::Object(input))
+}
+
+fn flag_value(field: &Field, matches: &ArgMatches) -> Option<Value> {
+    if matches.value_source(field.name) != Some(ValueSource::CommandLine) {
+        return None;
+    }
+    match field.kind {
+        FieldKind::String => matches.get_one::<String>(field.name).map(|v| json!(v)),
+        FieldKind::Integer => matches.get_one::<i64>(field.name).map(|v| json!(v)),
+        FieldKind::Number => matches.get_one::<f64>(field.name).map(|v| json!(v)),
+        FieldKind::Boolean => matches.get_one::<bool>(field.name).map(|v| json!(v)),
+        FieldKind::List => matches
+            .get_many::<String>(field.name)
+            .map(|values| json!(values.collect::<Vec<_>>())),
+        FieldKind::Json => matches.get_one::<Value>(field.name).cloned(),
+    }
+}
+
+fn read_stdin() -> Result<String> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read stdin")?;
+    Ok(input)
+}
+
+fn print_completions(command: &mut Command, matches: &ArgMatches) -> i32 {
+    let Some(shell) = matches.get_one::<Shell>("shell").copied() else {
+        return EXIT_INVALID_ARGS;
+    };
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, command, name, &mut io::stdout());
+    EXIT_SUCCESS
+}
+
+/// The commands a single-service binary accepts where parsing of `args`
+/// stopped (its `auth` actions, or the top level), as listed in
+/// `INVALID_COMMAND` errors.
+fn service_commands(service: &Service, args: &[OsString]) -> Vec<&'static str> {
+    if in_auth(args, |arg| {
+        service.operations.iter().any(|op| arg == op.name)
+    }) {
+        return auth::ACTIONS.to_vec();
+    }
+    let operations = service.operations.iter().map(|operation| operation.name);
+    std::iter::once("auth")
+        .chain(operations)
+        .chain(std::iter::once("completions"))
+        .collect()
+}
+
+/// The commands valid where `gws` parsing stopped: the `auth` actions or the
+/// operations of the service group named in `args`, or the top-level
+/// commands otherwise.
+fn gws_commands(args: &[OsString]) -> Vec<&'static str> {
+    if in_auth(args, |arg| {
+        SERVICES.iter().any(|service| arg == service.name)
+    }) {
+        return auth::ACTIONS.to_vec();
+    }
+    let group = args
+        .iter()
+        .skip(1)
+        .find_map(|arg| SERVICES.iter().find(|service| arg == service.name));
+    match group {
+        Some(service) => service.operations.iter().map(|op| op.name).collect(),
+        None => std::iter::once("auth")
+            .chain(SERVICES.iter().map(|service| service.name))
+            .chain(std::iter::once("completions"))
+            .collect(),
+    }
+}
+
+/// Whether `auth` comes before any other command in `args`.
+fn in_auth(args: &[OsString], is_command: impl Fn(&OsString) -> bool) -> bool {
+    args.iter()
+        .skip(1)
+        .find(|arg| *arg == "auth" || is_command(arg))
+        .is_some_and(|arg| arg == "auth")
+}
+
+/// Help and version go to stdout with exit 0; anything else is reported as
+/// a JSON error (with clap's explanation on stderr) and exit 4. An unknown
+/// command also lists `valid_commands`, as the original scripts did.
+fn report_usage_error(err: clap::Error, valid_commands: &[&str]) -> i32 {
+    match err.kind() {
+        ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
+            print!("{err}");
+            EXIT_SUCCESS
+        }
+        ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand => {
+            print!("{err}");
+            EXIT_INVALID_ARGS
+        }
+        kind => {
+            let rendered = err.to_string();
+            eprint!("{}", err.render().ansi());
+            let message = rendered
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim_start_matches("error: ");
+            let payload = if kind == ErrorKind::InvalidSubcommand {
+                json!({
+                    "status": "error",
+                    "error_code": "INVALID_COMMAND",
+                    "message": message,
+                    "valid_commands": valid_commands
+                })
+            } else {
+                json!({
+                    "status": "error",
+                    "error_code": "INVALID_ARGS",
+                    "message": message
+                })
+            };
+            print_json(&payload);
+            EXIT_INVALID_ARGS
+        }
+    }
+}
+
+/// Runs `f` and prints its fields as a success payload for `operation`.
+/// Google API errors exit 3, other [`DocsError`]s exit 1 with their own
+/// `error_code`; any other error is a missing or invalid field.
+pub(crate) fn dispatch<F>(client: &GoogleClient, operation: &str, f: F) -> i32
+where
+    F: FnOnce() -> Result<Value>,
+{
+    match f() {
+        Ok(fields) => {
+            print_json(&client.annotate_retries(success_payload(operation, &fields)));
+            EXIT_SUCCESS
+        }
+        Err(err) => {
+            if let Some(api_err) = err.downcast_ref::<GoogleApiError>() {
+                return report_api_error(client, operation, api_err);
+            }
+            if let Some(docs_err) = err.downcast_ref::<DocsError>() {
+                if let DocsError::Api(api_err) = docs_err {
+                    return report_api_error(client, operation, api_err);
+                }
+                print_json(&json!({
+                    "status": "error",
+                    "error_code": docs_err.error_code(),
+                    "operation": operation,
+                    "message": docs_err.to_string()
+                }));
+                return EXIT_OPERATION_FAILED;
+            }
+
+            print_json(&json!({
+                "status": "error",
+                "error_code": "MISSING_REQUIRED_FIELDS",
+                "message": err.to_string()
+            }));
+            EXIT_INVALID_ARGS
+        }
+    }
+}
+
+pub(crate) fn report_api_error(
+    client: &GoogleClient,
+    operation: &str,
+    err: &GoogleApiError,
+) -> i32 {
+    print_json(&client.annotate_retries(map_api_error(operation, err)));
+    EXIT_API_ERROR
+}
+
+/// Prints an invalid-arguments error with a specific `error_code`.
+pub(crate) fn invalid_args(error_code: &str, message: &str) -> i32 {
+    print_json(&json!({
+        "status": "error",
+        "error_code": error_code,
+        "message": message
+    }));
+    EXIT_INVALID_ARGS
+}
+
+pub(crate) fn required_string(input: &Value, key: &str) -> Result<String> {
+    input
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(ToString::to_string)
+        .ok_or_else(|| anyhow::anyhow!(required_fields_message(&[key])))
+}
+
+pub(crate) fn required_i64(input: &Value, key: &str) -> Result<i64> {
+    input
+        .get(key)
+        .and_then(value_to_i64)
+        .ok_or_else(|| anyhow::anyhow!(required_fields_message(&[key])))
+}
+
+pub(crate) fn required_array<'a>(input: &'a Value, key: &str) -> Result<&'a Vec<Value>> {
+    input
+        .get(key)
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!(required_fields_message(&[key])))
+}
+
+pub(crate) fn optional_str<'a>(input: &'a Value, key: &str) -> Option<&'a str> {
+    input.get(key).and_then(|v| v.as_str())
+}
+
+pub(crate) fn optional_bool(input: &Value, key: &str) -> Option<bool> {
+    input.get(key).and_then(|v| v.as_bool())
+}
+
+pub(crate) fn string_list(input: &Value, key: &str) -> Vec<String> {
+    input
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str().map(ToString::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn required_fields_message(fields: &[&str]) -> String {
+    if fields.len() == 1 {
+        format!("Required field: {}", fields[0])
+    } else {
+        format!("Required fields: {}", fields.join(", "))
+    }
+}
@@ -0,0 +1,321 @@
+use super::{EXIT_API_ERROR, EXIT_AUTH_ERROR, EXIT_SUCCESS, invalid_args};
+use crate::auth::{
+    AuthPaths, TokenState, WHOAMI_SCOPES, auth_required_payload, complete_authorization,
+    ensure_token, list_profiles, load_client_config, logout, scopes_missing_payload,
+    token_status_payload, validate_profile_name, whoami,
+};
+use crate::config::{Endpoints, SkillConfig};
+use crate::google_api::{GoogleClient, map_api_error};
+use crate::io_helpers::{home_dir, print_json};
+use crate::login::{device_login, loopback_login};
+use crate::token_store::TokenStoreError;
+use anyhow::Result;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{Value, json};
+
+const AUTH_MESSAGE: &str = "Authorization required. Please run the auth login flow.";
+
+/// The `auth` subcommands, listed as `valid_commands` when another is given.
+pub(crate) const ACTIONS: [&str; 8] = [
+    "login",
+    "device",
+    "code",
+    "status",
+    "whoami",
+    "logout",
+    "list-profiles",
+    "use",
+];
+
+pub(crate) fn command() -> Command {
+    Command::new("auth")
+        .about("Authorize, inspect or remove the stored Google token")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("scope")
+                .long("scope")
+                .value_name("URL")
+                .action(ArgAction::Append)
+                .global(true)
+                .help(
+                    "Also request this OAuth scope with login, device or code, \
+                     e.g. one listed in a SCOPES_MISSING error",
+                ),
+        )
+        .subcommand(Command::new("login").about("Authorize in the browser"))
+        .subcommand(Command::new("device").about("Authorize by entering a code on another device"))
+        .subcommand(
+            Command::new("code")
+                .about("Complete the legacy out-of-band flow with a pasted authorization code")
+                .arg(Arg::new("code").value_name("CODE")),
+        )
+        .subcommand(Command::new("status").about("Show the stored token's scopes and expiry"))
+        .subcommand(Command::new("whoami").about("Show the authorized account"))
+        .subcommand(Command::new("logout").about("Revoke and delete the stored token"))
+        .subcommand(
+            Command::new("list-profiles").about("List profiles and whether each has a token"),
+        )
+        .subcommand(
+            Command::new("use")
+                .about("Make a profile the active one")
+                .arg(Arg::new("profile").value_name("PROFILE")),
+        )
+}
+
+/// Runs `auth <action>`; `login`, `device` and `code` are granted `scopes`
+/// plus any given with `--scope`.
+pub(crate) fn run(
+    program: &str,
+    scopes: &[&str],
+    profile: Option<&str>,
+    matches: &ArgMatches,
+) -> i32 {
+    let Some((action, action_matches)) = matches.subcommand() else {
+        return super::EXIT_INVALID_ARGS;
+    };
+
+    if action == "whoami" {
+        let client = match initialize_client(program, WHOAMI_SCOPES, profile) {
+            Ok(client) => client,
+            Err(exit_code) => return exit_code,
+        };
+        return match whoami(&client) {
+            Ok(payload) => {
+                print_json(&client.annotate_retries(payload));
+                EXIT_SUCCESS
+            }
+            Err(err) => {
+                print_json(&client.annotate_retries(map_api_error("whoami", &err)));
+                EXIT_API_ERROR
+            }
+        };
+    }
+
+    let mut scopes = scopes.to_vec();
+    for scope in action_matches
+        .get_many::<String>("scope")
+        .into_iter()
+        .flatten()
+    {
+        if !scopes.contains(&scope.as_str()) {
+            scopes.push(scope);
+        }
+    }
+    let scopes = scopes.as_slice();
+
+    let result = match action {
+        "login" => login(profile, scopes),
+        "device" => device_login_command(profile, scopes),
+        "list-profiles" => list_profiles_command(profile),
+        "use" => match action_matches.get_one::<String>("profile") {
+            Some(name) => use_profile_command(name),
+            None => {
+                return invalid_args(
+                    "MISSING_PROFILE",
+                    "Profile name required: auth use <profile>",
+                );
+            }
+        },
+        "status" => status_command(profile),
+        "logout" => logout_command(profile),
+        "code" => match action_matches.get_one::<String>("code") {
+            Some(code) => complete_auth(code, profile, scopes),
+            None => {
+                return invalid_args(
+                    "MISSING_CODE",
+                    "Authorization code required: auth code <code>",
+                );
+            }
+        },
+        _ => return super::EXIT_INVALID_ARGS,
+    };
+
+    match result {
+        Ok(()) => EXIT_SUCCESS,
+        Err(err) => {
+            print_json(&auth_failed_payload("Authorization failed", &err));
+            EXIT_AUTH_ERROR
+        }
+    }
+}
+
+/// Loads (refreshing if needed) a token covering `scopes` and builds a
+/// client for it. On failure the error has been printed and the exit code is
+/// returned.
+pub(crate) fn initialize_client(
+    program: &str,
+    scopes: &[&str],
+    profile: Option<&str>,
+) -> std::result::Result<GoogleClient, i32> {
+    let setup_failed = |err: anyhow::Error| {
+        print_json(&auth_failed_payload("Authorization setup failed", &err));
+        EXIT_AUTH_ERROR
+    };
+
+    let paths = home_dir()
+        .and_then(|home| AuthPaths::for_profile(&home, profile))
+        .map_err(setup_failed)?;
+    let endpoints = Endpoints::resolve(&paths.config_path).map_err(setup_failed)?;
+    paths.token_store().map_err(setup_failed)?;
+
+    match ensure_token(&paths, scopes) {
+        Ok(TokenState::ScopesMissing { missing }) => {
+            print_json(&scopes_missing_payload(&missing, program));
+            Err(EXIT_AUTH_ERROR)
+        }
+        Ok(TokenState::AuthorizationRequired) => {
+            print_json(&auth_required_payload(AUTH_MESSAGE, program));
+            Err(EXIT_AUTH_ERROR)
+        }
+        Ok(TokenState::Authorized(token)) => match GoogleClient::new(token.access_token) {
+            Ok(client) => Ok(client.with_endpoints(endpoints)),
+            Err(err) => {
+                print_json(&json!({
+                    "status": "error",
+                    "error_code": "AUTH_FAILED",
+                    "message": format!("Failed to initialize API client: {err}")
+                }));
+                Err(EXIT_AUTH_ERROR)
+            }
+        },
+        Err(err) => {
+            // Logging in again does not help when the token store is broken.
+            let login_helps = err.downcast_ref::<TokenStoreError>().is_none()
+                && load_client_config(&paths).is_ok();
+
+            if login_helps {
+                print_json(&auth_required_payload(AUTH_MESSAGE, program));
+            } else {
+                print_json(&auth_failed_payload("Authorization failed", &err));
+            }
+            Err(EXIT_AUTH_ERROR)
+        }
+    }
+}
+
+/// An `AUTH_FAILED` error, or the token store's own code when that is what
+/// failed.
+fn auth_failed_payload(context: &str, err: &anyhow::Error) -> Value {
+    let error_code = err
+        .downcast_ref::<TokenStoreError>()
+        .map_or("AUTH_FAILED", TokenStoreError::error_code);
+    json!({
+        "status": "error",
+        "error_code": error_code,
+        "message": format!("{context}: {err}")
+    })
+}
+
+fn list_profiles_command(profile: Option<&str>) -> Result<()> {
+    let home = home_dir()?;
+    let paths = AuthPaths::for_profile(&home, profile)?;
+    let profiles: Vec<Value> = list_profiles(&paths)?
+        .into_iter()
+        .map(|name| {
+            let profile_paths = AuthPaths::for_profile(&home, Some(&name))?;
+            Ok(json!({
+                "name": name,
+                "active": name == paths.profile,
+                "has_token": profile_paths.load_token().is_ok(),
+                "client_secret_path": profile_paths.credentials_path.display().to_string()
+            }))
+        })
+        .collect::<Result<_>>()?;
+
+    print_json(&json!({
+        "status": "success",
+        "operation": "list_profiles",
+        "active_profile": paths.profile,
+        "profiles": profiles
+    }));
+
+    Ok(())
+}
+
+fn status_command(profile: Option<&str>) -> Result<()> {
+    let home = home_dir()?;
+    let paths = AuthPaths::for_profile(&home, profile)?;
+    print_json(&token_status_payload(&paths));
+    Ok(())
+}
+
+fn logout_command(profile: Option<&str>) -> Result<()> {
+    let home = home_dir()?;
+    let paths = AuthPaths::for_profile(&home, profile)?;
+    print_json(&logout(&paths)?);
+    Ok(())
+}
+
+fn use_profile_command(name: &str) -> Result<()> {
+    validate_profile_name(name)?;
+    let home = home_dir()?;
+    let paths = AuthPaths::for_profile(&home, Some(name))?;
+    SkillConfig::set_active_profile(&paths.config_path, name)?;
+
+    print_json(&json!({
+        "status": "success",
+        "operation": "use_profile",
+        "active_profile": name,
+        "config_path": paths.config_path.display().to_string(),
+        "has_token": paths.load_token().is_ok()
+    }));
+
+    Ok(())
+}
+
+fn device_login_command(profile: Option<&str>, scopes: &[&str]) -> Result<()> {
+    let home = home_dir()?;
+    let paths = AuthPaths::for_profile(&home, profile)?;
+    device_login(&paths, scopes, |device| {
+        print_json(&json!({
+            "status": "pending",
+            "operation": "auth_device",
+            "message": format!(
+                "Visit {} and enter code {} to authorize access",
+                device.verification_url, device.user_code
+            ),
+            "verification_url": device.verification_url,
+            "user_code": device.user_code,
+            "expires_in": device.expires_in
+        }));
+    })?;
+
+    print_authorized(&paths, scopes);
+    Ok(())
+}
+
+fn login(profile: Option<&str>, scopes: &[&str]) -> Result<()> {
+    let home = home_dir()?;
+    let paths = AuthPaths::for_profile(&home, profile)?;
+    loopback_login(&paths, scopes, |auth_url| {
+        eprintln!(
+            "Open this URL in your browser to authorize access:\n\n  {auth_url}\n\nWaiting for the browser redirect..."
+        );
+    })?;
+
+    print_authorized(&paths, scopes);
+    Ok(())
+}
+
+fn complete_auth(code: &str, profile: Option<&str>, scopes: &[&str]) -> Result<()> {
+    let home = home_dir()?;
+    let paths = AuthPaths::for_profile(&home, profile)?;
+    let config = load_client_config(&paths)?;
+    let existing_refresh = paths.load_token().ok().and_then(|t| t.refresh_token);
+    let token = complete_authorization(&config, code, existing_refresh)?;
+    paths.save_token(&token)?;
+
+    print_authorized(&paths, scopes);
+    Ok(())
+}
+
+fn print_authorized(paths: &AuthPaths, scopes: &[&str]) {
+    print_json(&json!({
+        "status": "success",
+        "message": "Authorization complete. Token stored successfully.",
+        "token_path": paths.token_path.display().to_string(),
+        "profile": paths.profile,
+        "scopes": scopes
+    }));
+}
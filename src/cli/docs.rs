@@ -0,0 +1,698 @@
+use super::{
+    EXIT_OPERATION_FAILED, ExtraScopes, Field, Operation, Service, dispatch, invalid_args,
+    optional_bool, optional_str, print_json, report_api_error, required_i64, required_string,
+};
+use crate::auth::{DOCS_SCOPE, DRIVE_SCOPE, SHEETS_SCOPE};
+use crate::docs;
+use crate::docs::anchor::Anchor;
+use crate::docs::template;
+use crate::docs::transaction::{self, Edit};
+use crate::google_api::GoogleClient;
+use crate::io_helpers::value_to_i64;
+use crate::models::docs::TextStyle;
+use crate::sheets;
+use serde_json::{Map, Value, json};
+
+const DOCUMENT_ID: Field = Field::string("document_id", "Document ID");
+const REQUIRED_REVISION_ID: Field = Field::string(
+    "required_revision_id",
+    "Fail with REVISION_CONFLICT if the document has changed since this revision",
+);
+const ANCHOR: Field = Field::json(
+    "anchor",
+    "Where to act instead of an index, e.g. {\"after_heading\": \"Risks\"}",
+);
+
+pub const SERVICE: Service = Service {
+    name: "docs",
+    about: "Google Docs document operations",
+    scopes: &[DOCS_SCOPE],
+    extra_scopes: &[
+        ExtraScopes {
+            operation: "render-template",
+            field: None,
+            scopes: &[DRIVE_SCOPE],
+        },
+        ExtraScopes {
+            operation: "render-template",
+            field: Some("spreadsheet_id"),
+            scopes: &[SHEETS_SCOPE],
+        },
+    ],
+    piped_input: true,
+    operations: &[
+        Operation {
+            name: "read",
+            about: "Read document content",
+            fields: &[Field::string("document_id", "Document ID").positional()],
+        },
+        Operation {
+            name: "structure",
+            about: "Get document structure (headings)",
+            fields: &[Field::string("document_id", "Document ID").positional()],
+        },
+        Operation {
+            name: "export-markdown",
+            about: "Export a document as Markdown",
+            fields: &[Field::string("document_id", "Document ID").positional()],
+        },
+        Operation {
+            name: "export-html",
+            about: "Export a document as semantic HTML",
+            fields: &[Field::string("document_id", "Document ID").positional()],
+        },
+        Operation {
+            name: "export-text",
+            about: "Export document text with a map to document indices",
+            fields: &[Field::string("document_id", "Document ID").positional()],
+        },
+        Operation {
+            name: "insert",
+            about: "Insert text at a specific index",
+            fields: &[
+                DOCUMENT_ID,
+                Field::string("text", "Text to insert"),
+                Field::integer("index", "Insertion index (default: 1)"),
+                ANCHOR,
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "append",
+            about: "Append text to the end of the document",
+            fields: &[
+                DOCUMENT_ID,
+                Field::string("text", "Text to append"),
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "replace",
+            about: "Find and replace text",
+            fields: &[
+                DOCUMENT_ID,
+                Field::string("find", "Text to find"),
+                Field::string("replace", "Replacement text"),
+                Field::boolean("match_case", "Match case"),
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "find",
+            about: "Find text and return its index ranges",
+            fields: &[
+                DOCUMENT_ID,
+                Field::string("query", "Text or pattern to find"),
+                Field::boolean("regex", "Treat the query as a regular expression"),
+                Field::boolean("match_case", "Match case"),
+            ],
+        },
+        Operation {
+            name: "format",
+            about: "Format a text range",
+            fields: &[
+                DOCUMENT_ID,
+                Field::integer("start_index", "Start of the range"),
+                Field::integer("end_index", "End of the range (exclusive)"),
+                Field::boolean("bold", "Set or clear bold"),
+                Field::boolean("italic", "Set or clear italic"),
+                Field::boolean("underline", "Set or clear underline"),
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "page-break",
+            about: "Insert a page break",
+            fields: &[
+                DOCUMENT_ID,
+                Field::integer("index", "Insertion index"),
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "create",
+            about: "Create a new document",
+            fields: &[
+                Field::string("title", "Document title"),
+                Field::string("content", "Initial plain-text content"),
+            ],
+        },
+        Operation {
+            name: "create-from-markdown",
+            about: "Create a new document from Markdown",
+            fields: &[
+                Field::string("title", "Document title"),
+                Field::string("markdown", "Markdown content"),
+            ],
+        },
+        Operation {
+            name: "insert-from-markdown",
+            about: "Insert formatted Markdown into an existing document",
+            fields: &[
+                DOCUMENT_ID,
+                Field::string("markdown", "Markdown content"),
+                Field::integer("index", "Insertion index (default: end of document)"),
+                ANCHOR,
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "replace-section",
+            about: "Replace the content under a heading with Markdown",
+            fields: &[
+                DOCUMENT_ID,
+                Field::string("heading", "Heading path, e.g. \"Status > Blockers\""),
+                Field::string("markdown", "Markdown content"),
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "apply",
+            about: "Apply a list of edits as one atomic batch update",
+            fields: &[
+                DOCUMENT_ID,
+                Field::json(
+                    "ops",
+                    "Edits in order, e.g. [{\"op\": \"insert\", \"text\": \"Hi\", \"index\": 1}]",
+                ),
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "render-template",
+            about: "Copy a template document and fill in its {{placeholders}}",
+            fields: &[
+                Field::string("template_id", "Template document ID").positional(),
+                Field::string("title", "Title of each copy; may use placeholders"),
+                Field::json("data", "Values for one copy, as a JSON object"),
+                Field::json(
+                    "records",
+                    "Values for one copy each, as an array of objects",
+                ),
+                Field::string("spreadsheet_id", "Spreadsheet to read records from"),
+                Field::string("range", "Sheet range; the first row names the fields"),
+                Field::string("folder_id", "Folder to put the copies in"),
+            ],
+        },
+        Operation {
+            name: "delete",
+            about: "Delete a content range",
+            fields: &[
+                DOCUMENT_ID,
+                Field::integer("start_index", "Start of the range"),
+                Field::integer("end_index", "End of the range (exclusive)"),
+                ANCHOR,
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "insert-image",
+            about: "Insert an inline image from a URL",
+            fields: &[
+                DOCUMENT_ID,
+                Field::string("image_url", "Publicly reachable image URL"),
+                Field::integer("index", "Insertion index (default: end of document)"),
+                Field::number("width", "Width in points"),
+                Field::number("height", "Height in points"),
+                REQUIRED_REVISION_ID,
+            ],
+        },
+        Operation {
+            name: "insert-table",
+            about: "Insert a table",
+            fields: &[
+                DOCUMENT_ID,
+                Field::integer("rows", "Number of rows"),
+                Field::integer("cols", "Number of columns"),
+                Field::integer("index", "Insertion index (default: end of document)"),
+                Field::json("data", "Cell text as an array of rows"),
+                REQUIRED_REVISION_ID,
+            ],
+        },
+    ],
+    run,
+};
+
+fn run(client: &GoogleClient, operation: &str, input: &Value) -> i32 {
+    match operation {
+        "read" => {
+            let Some(document_id) = optional_str(input, "document_id") else {
+                return invalid_args("MISSING_DOCUMENT_ID", "Document ID required");
+            };
+            dispatch(client, "read", || {
+                Ok(json!(docs::read_document(client, document_id)?))
+            })
+        }
+        "structure" => {
+            let Some(document_id) = optional_str(input, "document_id") else {
+                return invalid_args("MISSING_DOCUMENT_ID", "Document ID required");
+            };
+            dispatch(client, "structure", || {
+                Ok(json!(docs::get_structure(client, document_id)?))
+            })
+        }
+        "export-markdown" => {
+            let Some(document_id) = optional_str(input, "document_id") else {
+                return invalid_args("MISSING_DOCUMENT_ID", "Document ID required");
+            };
+            dispatch(client, "export_markdown", || {
+                Ok(json!(docs::export_markdown(client, document_id)?))
+            })
+        }
+        "export-html" => {
+            let Some(document_id) = optional_str(input, "document_id") else {
+                return invalid_args("MISSING_DOCUMENT_ID", "Document ID required");
+            };
+            dispatch(client, "export_html", || {
+                Ok(json!(docs::export_html(client, document_id)?))
+            })
+        }
+        "export-text" => {
+            let Some(document_id) = optional_str(input, "document_id") else {
+                return invalid_args("MISSING_DOCUMENT_ID", "Document ID required");
+            };
+            dispatch(client, "export_text", || {
+                Ok(json!(docs::export_text(client, document_id)?))
+            })
+        }
+        "insert" => {
+            let anchor = match anchor(input, &["index"]) {
+                Ok(anchor) => anchor,
+                Err(code) => return code,
+            };
+            dispatch(client, "insert", || {
+                let document_id = required_string(input, "document_id")?;
+                let text = required_string(input, "text")?;
+                let revision = optional_str(input, "required_revision_id");
+                let inserted = match &anchor {
+                    Some(anchor) => {
+                        docs::insert_text_at_anchor(client, &document_id, &text, anchor, revision)?
+                    }
+                    None => {
+                        let index = input.get("index").and_then(value_to_i64).unwrap_or(1);
+                        docs::insert_text(client, &document_id, &text, index, revision)?
+                    }
+                };
+                Ok(json!({
+                    "document_id": inserted.document_id,
+                    "inserted_at": inserted.index,
+                    "text_length": inserted.text_length,
+                    "revision_id": inserted.revision_id
+                }))
+            })
+        }
+        "append" => dispatch(client, "append", || {
+            let document_id = required_string(input, "document_id")?;
+            let text = required_string(input, "text")?;
+            let revision = optional_str(input, "required_revision_id");
+            let appended = docs::append_text(client, &document_id, &text, revision)?;
+            Ok(json!({
+                "document_id": appended.document_id,
+                "appended_at": appended.index,
+                "text_length": appended.text_length,
+                "revision_id": appended.revision_id
+            }))
+        }),
+        "replace" => dispatch(client, "replace", || {
+            let document_id = required_string(input, "document_id")?;
+            let find = required_string(input, "find")?;
+            let replace = required_string(input, "replace")?;
+            let match_case = optional_bool(input, "match_case").unwrap_or(false);
+            let revision = optional_str(input, "required_revision_id");
+            let replaced =
+                docs::replace_text(client, &document_id, &find, &replace, match_case, revision)?;
+            Ok(json!({
+                "document_id": replaced.document_id,
+                "find": find,
+                "replace": replace,
+                "occurrences": replaced.occurrences,
+                "revision_id": replaced.revision_id
+            }))
+        }),
+        "find" => {
+            let Some(query) = optional_str(input, "query") else {
+                return invalid_args("MISSING_QUERY", "Search query required: --query <query>");
+            };
+            let regex = optional_bool(input, "regex").unwrap_or(false);
+            let match_case = optional_bool(input, "match_case").unwrap_or(false);
+            let pattern = match docs::text_pattern(query, regex, match_case) {
+                Ok(pattern) => pattern,
+                Err(err) => return invalid_args("INVALID_PATTERN", &err.to_string()),
+            };
+            dispatch(client, "find", || {
+                let document_id = required_string(input, "document_id")?;
+                Ok(json!(docs::find_text(client, &document_id, &pattern)?))
+            })
+        }
+        "format" => dispatch(client, "format", || {
+            let document_id = required_string(input, "document_id")?;
+            let start_index = required_i64(input, "start_index")?;
+            let end_index = required_i64(input, "end_index")?;
+            let style = TextStyle {
+                bold: optional_bool(input, "bold"),
+                italic: optional_bool(input, "italic"),
+                underline: optional_bool(input, "underline"),
+                ..Default::default()
+            };
+            let revision = optional_str(input, "required_revision_id");
+            let written = docs::format_text(
+                client,
+                &document_id,
+                start_index,
+                end_index,
+                style.clone(),
+                revision,
+            )?;
+            Ok(json!({
+                "document_id": written.document_id,
+                "range": {"start": start_index, "end": end_index},
+                "formatting": style,
+                "revision_id": written.revision_id
+            }))
+        }),
+        "page-break" => dispatch(client, "page_break", || {
+            let document_id = required_string(input, "document_id")?;
+            let index = required_i64(input, "index")?;
+            let revision = optional_str(input, "required_revision_id");
+            let inserted = docs::insert_page_break(client, &document_id, index, revision)?;
+            Ok(json!({
+                "document_id": inserted.document_id,
+                "inserted_at": inserted.index,
+                "revision_id": inserted.revision_id
+            }))
+        }),
+        "create" => dispatch(client, "create", || {
+            let title = required_string(input, "title")?;
+            let content = optional_str(input, "content");
+            Ok(json!(docs::create_document(client, &title, content)?))
+        }),
+        "create-from-markdown" => dispatch(client, "create_from_markdown", || {
+            let title = required_string(input, "title")?;
+            let markdown = required_string(input, "markdown")?;
+            Ok(json!(docs::create_from_markdown(
+                client, &title, &markdown
+            )?))
+        }),
+        "insert-from-markdown" => {
+            let anchor = match anchor(input, &["index"]) {
+                Ok(anchor) => anchor,
+                Err(code) => return code,
+            };
+            dispatch(client, "insert_from_markdown", || {
+                let document_id = required_string(input, "document_id")?;
+                let markdown = required_string(input, "markdown")?;
+                let revision = optional_str(input, "required_revision_id");
+                let inserted = match &anchor {
+                    Some(anchor) => docs::insert_markdown_at_anchor(
+                        client,
+                        &document_id,
+                        &markdown,
+                        anchor,
+                        revision,
+                    )?,
+                    None => docs::insert_from_markdown(
+                        client,
+                        &document_id,
+                        &markdown,
+                        input.get("index").and_then(value_to_i64),
+                        revision,
+                    )?,
+                };
+                Ok(json!({
+                    "document_id": inserted.document_id,
+                    "inserted_at": inserted.index,
+                    "text_length": inserted.text_length,
+                    "formats_applied": inserted.formats_applied,
+                    "revision_id": inserted.revision_id
+                }))
+            })
+        }
+        "replace-section" => dispatch(client, "replace_section", || {
+            let document_id = required_string(input, "document_id")?;
+            let heading = required_string(input, "heading")?;
+            let markdown = required_string(input, "markdown")?;
+            let revision = optional_str(input, "required_revision_id");
+            Ok(json!(docs::replace_section(
+                client,
+                &document_id,
+                &heading,
+                &markdown,
+                revision
+            )?))
+        }),
+        "apply" => {
+            let edits = match edits(input) {
+                Ok(edits) => edits,
+                Err(code) => return code,
+            };
+            dispatch(client, "apply", || {
+                let document_id = required_string(input, "document_id")?;
+                let revision = optional_str(input, "required_revision_id");
+                Ok(json!(transaction::apply_edits(
+                    client,
+                    &document_id,
+                    &edits,
+                    revision
+                )?))
+            })
+        }
+        "render-template" => {
+            let Some(template_id) = optional_str(input, "template_id") else {
+                return invalid_args("MISSING_TEMPLATE_ID", "Template document ID required");
+            };
+            let source = match template_source(input) {
+                Ok(source) => source,
+                Err(code) => return code,
+            };
+            let records = match source {
+                TemplateSource::Records(records) => records,
+                TemplateSource::Sheet {
+                    spreadsheet_id,
+                    range,
+                } => match sheets::read_range(client, spreadsheet_id, range) {
+                    Ok(values) => template::records_from_rows(&values.values),
+                    Err(err) => return report_api_error(client, "render_template", &err),
+                },
+            };
+            let title = optional_str(input, "title");
+            let folder_id = optional_str(input, "folder_id");
+            // Every record gets its own copy, so one failing doesn't stop the
+            // rest, and the copies already made are always reported.
+            let mut documents = Vec::new();
+            let mut failed = Vec::new();
+            for (position, record) in records.iter().enumerate() {
+                match template::render_template(client, template_id, title, record, folder_id) {
+                    Ok(document) => documents.push(document),
+                    Err(err) => failed.push(json!({
+                        "record": position,
+                        "error_code": err.error_code(),
+                        "message": err.to_string()
+                    })),
+                }
+            }
+            if failed.is_empty() {
+                return dispatch(client, "render_template", || {
+                    Ok(json!({
+                        "template_id": template_id,
+                        "count": documents.len(),
+                        "documents": documents
+                    }))
+                });
+            }
+            print_json(&client.annotate_retries(json!({
+                "status": "error",
+                "error_code": "RENDER_FAILED",
+                "operation": "render_template",
+                "message": format!("{} of {} records failed to render", failed.len(), records.len()),
+                "template_id": template_id,
+                "count": documents.len(),
+                "documents": documents,
+                "failed": failed
+            })));
+            EXIT_OPERATION_FAILED
+        }
+        "delete" => {
+            let anchor = match anchor(input, &["start_index", "end_index"]) {
+                Ok(anchor) => anchor,
+                Err(code) => return code,
+            };
+            dispatch(client, "delete", || {
+                let document_id = required_string(input, "document_id")?;
+                let revision = optional_str(input, "required_revision_id");
+                let (start_index, end_index, revision) = match &anchor {
+                    Some(anchor) if !anchor.is_range() => {
+                        return Err(docs::DocsError::AnchorNotRange(anchor.to_string()).into());
+                    }
+                    Some(anchor) => {
+                        let (range, revision) =
+                            docs::anchor_range(client, &document_id, anchor, revision)?;
+                        (range.start_index, range.end_index, revision)
+                    }
+                    None => (
+                        required_i64(input, "start_index")?,
+                        required_i64(input, "end_index")?,
+                        revision.map(ToString::to_string),
+                    ),
+                };
+                let written = docs::delete_content(
+                    client,
+                    &document_id,
+                    start_index,
+                    end_index,
+                    revision.as_deref(),
+                )?;
+                Ok(json!({
+                    "document_id": written.document_id,
+                    "deleted_range": {"start": start_index, "end": end_index},
+                    "revision_id": written.revision_id
+                }))
+            })
+        }
+        "insert-image" => dispatch(client, "insert_image", || {
+            let document_id = required_string(input, "document_id")?;
+            let image_url = required_string(input, "image_url")?;
+            let index = input.get("index").and_then(value_to_i64);
+            let width = input.get("width").and_then(|v| v.as_f64());
+            let height = input.get("height").and_then(|v| v.as_f64());
+            let revision = optional_str(input, "required_revision_id");
+            let inserted = docs::insert_image(
+                client,
+                &document_id,
+                &image_url,
+                index,
+                width,
+                height,
+                revision,
+            )?;
+            Ok(json!({
+                "document_id": inserted.document_id,
+                "inserted_at": inserted.index,
+                "image_url": image_url,
+                "revision_id": inserted.revision_id
+            }))
+        }),
+        "insert-table" => dispatch(client, "insert_table", || {
+            let document_id = required_string(input, "document_id")?;
+            let rows = required_i64(input, "rows")?;
+            let cols = required_i64(input, "cols")?;
+            let index = input.get("index").and_then(value_to_i64);
+            let data: Vec<Vec<String>> = input
+                .get("data")
+                .and_then(|v| v.as_array())
+                .map(|rows| {
+                    rows.iter()
+                        .map(|row| {
+                            row.as_array()
+                                .map(|cells| cells.iter().map(value_to_string).collect())
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let revision = optional_str(input, "required_revision_id");
+            let inserted =
+                docs::insert_table(client, &document_id, rows, cols, index, &data, revision)?;
+            Ok(json!({
+                "document_id": inserted.document_id,
+                "rows": inserted.rows,
+                "columns": inserted.columns,
+                "inserted_at": inserted.index,
+                "revision_id": inserted.revision_id
+            }))
+        }),
+        _ => invalid_args("INVALID_COMMAND", &format!("Unknown command: {operation}")),
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// The `anchor` field, or the exit code after reporting it as invalid or as
+/// given together with one of the `indices` it stands in for.
+fn anchor(input: &Value, indices: &[&str]) -> Result<Option<Anchor>, i32> {
+    let anchor = match input.get("anchor") {
+        None | Some(Value::Null) => return Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|err| invalid_args("INVALID_ANCHOR", &format!("Invalid anchor: {err}")))?,
+    };
+    if let Some(index) = indices
+        .iter()
+        .find(|index| input.get(**index).is_some_and(|value| !value.is_null()))
+    {
+        return Err(invalid_args(
+            "INVALID_ARGUMENTS",
+            &format!("Give either anchor or {index}, not both"),
+        ));
+    }
+    Ok(Some(anchor))
+}
+
+/// The `ops` field of `apply`, or the exit code after reporting it as
+/// missing or invalid.
+fn edits(input: &Value) -> Result<Vec<Edit>, i32> {
+    let Some(ops) = input.get("ops").filter(|ops| !ops.is_null()) else {
+        return Err(invalid_args(
+            "MISSING_OPS",
+            "Edits required: --ops '[{\"op\": \"insert\", ...}]'",
+        ));
+    };
+    let edits: Vec<Edit> = serde_json::from_value(ops.clone())
+        .map_err(|err| invalid_args("INVALID_OPS", &format!("Invalid ops: {err}")))?;
+    transaction::validate_edits(&edits).map_err(|err| invalid_args("INVALID_OPS", &err))?;
+    Ok(edits)
+}
+
+/// Where `render-template` gets its records from.
+enum TemplateSource<'a> {
+    Records(Vec<Map<String, Value>>),
+    Sheet {
+        spreadsheet_id: &'a str,
+        range: &'a str,
+    },
+}
+
+/// Reads exactly one of `data`, `records` or `spreadsheet_id` (with `range`)
+/// from the input, reporting the exit code for bad or missing records.
+fn template_source(input: &Value) -> Result<TemplateSource<'_>, i32> {
+    let data = input.get("data").filter(|v| !v.is_null());
+    let records = input.get("records").filter(|v| !v.is_null());
+    let spreadsheet_id = optional_str(input, "spreadsheet_id");
+
+    match (data, records, spreadsheet_id) {
+        (Some(data), None, None) => match data.as_object() {
+            Some(record) => Ok(TemplateSource::Records(vec![record.clone()])),
+            None => Err(invalid_args("INVALID_DATA", "data must be a JSON object")),
+        },
+        (None, Some(records), None) => records
+            .as_array()
+            .and_then(|records| {
+                records
+                    .iter()
+                    .map(|record| record.as_object().cloned())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .map(TemplateSource::Records)
+            .ok_or_else(|| {
+                invalid_args("INVALID_DATA", "records must be an array of JSON objects")
+            }),
+        (None, None, Some(spreadsheet_id)) => match optional_str(input, "range") {
+            Some(range) => Ok(TemplateSource::Sheet {
+                spreadsheet_id,
+                range,
+            }),
+            None => Err(invalid_args(
+                "MISSING_RANGE",
+                "Sheet range required with spreadsheet_id: --range <range>",
+            )),
+        },
+        _ => Err(invalid_args(
+            "MISSING_DATA",
+            "Provide exactly one of data, records, or spreadsheet_id with range",
+        )),
+    }
+}
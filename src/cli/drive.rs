@@ -0,0 +1,287 @@
+use super::{
+    EXIT_OPERATION_FAILED, EXIT_SUCCESS, Field, Operation, Service, invalid_args, optional_bool,
+    optional_str, report_api_error,
+};
+use crate::auth::DRIVE_SCOPE;
+use crate::drive::{self, Download, DriveError};
+use crate::google_api::GoogleClient;
+use crate::io_helpers::{print_json, success_payload, value_to_i64};
+use serde_json::{Value, json};
+use std::path::Path;
+
+const FILE_ID: Field = Field::string("file_id", "Drive file ID");
+const FOLDER_ID: Field = Field::string("folder_id", "Drive folder ID");
+const NAME: Field = Field::string("name", "File or folder name");
+const MAX_RESULTS: Field = Field::integer("max_results", "Max results to return (default: 100)");
+
+pub const SERVICE: Service = Service {
+    name: "drive",
+    about: "Google Drive file operations",
+    scopes: &[DRIVE_SCOPE],
+    extra_scopes: &[],
+    piped_input: false,
+    operations: &[
+        Operation {
+            name: "upload",
+            about: "Upload a file to Drive",
+            fields: &[
+                Field::string("file", "Local file path"),
+                FOLDER_ID,
+                NAME,
+                Field::string("mime_type", "Override the detected MIME type"),
+            ],
+        },
+        Operation {
+            name: "download",
+            about: "Download a file, exporting Google Docs/Sheets/Slides",
+            fields: &[FILE_ID, Field::string("output", "Output file path")],
+        },
+        Operation {
+            name: "list",
+            about: "List files in Drive or a folder",
+            fields: &[FOLDER_ID, MAX_RESULTS],
+        },
+        Operation {
+            name: "search",
+            about: "Search files with a Drive query",
+            fields: &[
+                Field::string("query", "Search query (Drive query syntax)"),
+                MAX_RESULTS,
+            ],
+        },
+        Operation {
+            name: "get-metadata",
+            about: "Get file metadata",
+            fields: &[FILE_ID],
+        },
+        Operation {
+            name: "create-folder",
+            about: "Create a new folder",
+            fields: &[
+                NAME,
+                Field::string("parent_id", "Parent folder ID"),
+                FOLDER_ID,
+            ],
+        },
+        Operation {
+            name: "move",
+            about: "Move a file to a folder",
+            fields: &[FILE_ID, FOLDER_ID],
+        },
+        Operation {
+            name: "share",
+            about: "Share a file with a user or make it public",
+            fields: &[
+                FILE_ID,
+                Field::string("email", "Email address to share with"),
+                Field::string("role", "Permission role: reader, writer, commenter"),
+                Field::string("type", "Permission type: user, anyone, domain"),
+            ],
+        },
+        Operation {
+            name: "delete",
+            about: "Delete a file (trash, or permanently)",
+            fields: &[
+                FILE_ID,
+                Field::boolean("permanent", "Delete permanently instead of trashing"),
+            ],
+        },
+        Operation {
+            name: "copy",
+            about: "Copy a file",
+            fields: &[FILE_ID, NAME, FOLDER_ID],
+        },
+        Operation {
+            name: "update",
+            about: "Replace a file's content",
+            fields: &[FILE_ID, Field::string("file", "Local file path"), NAME],
+        },
+    ],
+    run,
+};
+
+fn run(client: &GoogleClient, operation: &str, input: &Value) -> i32 {
+    let option = |key: &str| optional_str(input, key);
+
+    match operation {
+        "upload" => {
+            let Some(file_path) = option("file") else {
+                return invalid_args("MISSING_FILE", "File path required: --file <path>");
+            };
+
+            let result = drive::upload(
+                client,
+                Path::new(file_path),
+                option("folder_id"),
+                option("name"),
+                option("mime_type"),
+            );
+            report(client, "upload", result.map(|file| json!({"file": file})))
+        }
+        "download" => {
+            let (Some(file_id), Some(output)) = (option("file_id"), option("output")) else {
+                return invalid_args(
+                    "MISSING_ARGS",
+                    "File ID and output path required: --file-id <id> --output <path>",
+                );
+            };
+
+            let result = drive::download(client, file_id, Path::new(output));
+            let operation = match result {
+                Ok(Download::Export(_)) => "export",
+                _ => "download",
+            };
+            report(client, operation, result.map(|download| json!(download)))
+        }
+        "list" => {
+            let folder_id = option("folder_id");
+            let result = drive::list_files(client, folder_id, max_results(input), None);
+            report(
+                client,
+                "list",
+                result.map(|list| {
+                    json!({
+                        "folder_id": folder_id,
+                        "count": list.files.len(),
+                        "files": list.files,
+                        "next_page_token": list.next_page_token
+                    })
+                }),
+            )
+        }
+        "search" => {
+            let Some(query) = option("query") else {
+                return invalid_args("MISSING_QUERY", "Search query required: --query <query>");
+            };
+
+            let result = drive::search_files(client, query, max_results(input), None);
+            report(
+                client,
+                "search",
+                result.map(|list| {
+                    json!({
+                        "query": query,
+                        "count": list.files.len(),
+                        "files": list.files,
+                        "next_page_token": list.next_page_token
+                    })
+                }),
+            )
+        }
+        "get-metadata" => {
+            let Some(file_id) = option("file_id") else {
+                return invalid_args("MISSING_FILE_ID", "File ID required: --file-id <id>");
+            };
+
+            let result = drive::get_metadata(client, file_id);
+            report(
+                client,
+                "get_metadata",
+                result.map(|file| json!({"file": file})),
+            )
+        }
+        "create-folder" => {
+            let Some(name) = option("name") else {
+                return invalid_args("MISSING_NAME", "Folder name required: --name <name>");
+            };
+
+            let parent_id = option("parent_id").or_else(|| option("folder_id"));
+            let result = drive::create_folder(client, name, parent_id);
+            report(
+                client,
+                "create_folder",
+                result.map(|folder| json!({"folder": folder})),
+            )
+        }
+        "move" => {
+            let (Some(file_id), Some(folder_id)) = (option("file_id"), option("folder_id")) else {
+                return invalid_args(
+                    "MISSING_ARGS",
+                    "File ID and folder ID required: --file-id <id> --folder-id <id>",
+                );
+            };
+
+            let result = drive::move_file(client, file_id, folder_id);
+            report(client, "move", result.map(|file| json!({"file": file})))
+        }
+        "share" => {
+            let Some(file_id) = option("file_id") else {
+                return invalid_args("MISSING_FILE_ID", "File ID required: --file-id <id>");
+            };
+
+            let result = drive::share_file(
+                client,
+                file_id,
+                option("email"),
+                option("role").unwrap_or("reader"),
+                option("type"),
+            );
+            report(client, "share", result.map(|share| json!(share)))
+        }
+        "delete" => {
+            let Some(file_id) = option("file_id") else {
+                return invalid_args("MISSING_FILE_ID", "File ID required: --file-id <id>");
+            };
+
+            let permanent = optional_bool(input, "permanent").unwrap_or(false);
+            let result = drive::delete_file(client, file_id, permanent);
+            report(
+                client,
+                "delete",
+                result.map(|()| json!({"file_id": file_id, "permanent": permanent})),
+            )
+        }
+        "copy" => {
+            let Some(file_id) = option("file_id") else {
+                return invalid_args("MISSING_FILE_ID", "File ID required: --file-id <id>");
+            };
+
+            let result = drive::copy_file(client, file_id, option("name"), option("folder_id"));
+            report(client, "copy", result.map(|file| json!({"file": file})))
+        }
+        "update" => {
+            let (Some(file_id), Some(file_path)) = (option("file_id"), option("file")) else {
+                return invalid_args(
+                    "MISSING_ARGS",
+                    "File ID and file path required: --file-id <id> --file <path>",
+                );
+            };
+
+            let result = drive::update_file(client, file_id, Path::new(file_path), option("name"));
+            report(client, "update", result.map(|file| json!({"file": file})))
+        }
+        _ => invalid_args("INVALID_COMMAND", &format!("Unknown command: {operation}")),
+    }
+}
+
+/// Prints the outcome of a library call: `fields` merged into a success
+/// payload, or the error with its exit code.
+fn report<E>(client: &GoogleClient, operation: &str, result: Result<Value, E>) -> i32
+where
+    E: Into<DriveError>,
+{
+    match result.map_err(Into::into) {
+        Ok(fields) => {
+            print_json(&client.annotate_retries(success_payload(operation, &fields)));
+            EXIT_SUCCESS
+        }
+        Err(DriveError::Api(err)) => report_api_error(client, operation, &err),
+        Err(err) => {
+            print_json(&json!({
+                "status": "error",
+                "error_code": err.error_code(),
+                "operation": operation,
+                "message": err.to_string()
+            }));
+            EXIT_OPERATION_FAILED
+        }
+    }
+}
+
+/// `max_results` from a flag or JSON, where it may be a number or a string.
+fn max_results(input: &Value) -> i64 {
+    input
+        .get("max_results")
+        .and_then(|v| value_to_i64(v).or_else(|| v.as_str().and_then(|s| s.trim().parse().ok())))
+        .unwrap_or(100)
+}
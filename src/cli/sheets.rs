@@ -0,0 +1,680 @@
+use super::{
+    Field, Operation, Service, dispatch, invalid_args, optional_bool, optional_str, required_array,
+    required_i64, required_string, string_list,
+};
+use crate::auth::SHEETS_SCOPE;
+use crate::google_api::GoogleClient;
+use crate::io_helpers::value_to_i64;
+use crate::sheets::{self, FindReplaceOptions, RangeValues};
+use anyhow::Context;
+use serde_json::{Map, Value, json};
+
+const SPREADSHEET_ID: Field = Field::string("spreadsheet_id", "Spreadsheet ID");
+const SHEET_ID: Field = Field::integer("sheet_id", "Numeric sheet (tab) ID");
+const RANGE: Field = Field::string("range", "A1 range, e.g. Sheet1!A1:C10");
+const START_COL: Field = Field::integer("start_col", "First column (zero-based)");
+const END_COL: Field = Field::integer("end_col", "End column (zero-based, exclusive)");
+
+pub const SERVICE: Service = Service {
+    name: "sheets",
+    about: "Google Sheets spreadsheet operations",
+    scopes: &[SHEETS_SCOPE],
+    extra_scopes: &[],
+    piped_input: true,
+    operations: &[
+        Operation {
+            name: "create",
+            about: "Create a new spreadsheet",
+            fields: &[
+                Field::string("title", "Spreadsheet title"),
+                Field::list("sheets", "Tab name (repeatable)"),
+                Field::json("data", "Rows to write to the first tab from A1"),
+            ],
+        },
+        Operation {
+            name: "read",
+            about: "Read values from a range",
+            fields: &[SPREADSHEET_ID, RANGE],
+        },
+        Operation {
+            name: "write",
+            about: "Write values to a range",
+            fields: &[
+                SPREADSHEET_ID,
+                RANGE,
+                Field::json("values", "Rows of cell values"),
+            ],
+        },
+        Operation {
+            name: "append",
+            about: "Append rows after a table",
+            fields: &[
+                SPREADSHEET_ID,
+                RANGE,
+                Field::json("values", "Rows of cell values"),
+            ],
+        },
+        Operation {
+            name: "clear",
+            about: "Clear values in a range",
+            fields: &[SPREADSHEET_ID, RANGE],
+        },
+        Operation {
+            name: "batch-read",
+            about: "Read several ranges",
+            fields: &[
+                SPREADSHEET_ID,
+                Field::list("ranges", "A1 range (repeatable)"),
+            ],
+        },
+        Operation {
+            name: "batch-write",
+            about: "Write several ranges",
+            fields: &[
+                SPREADSHEET_ID,
+                Field::json("data", "Array of {\"range\", \"values\"} objects"),
+            ],
+        },
+        Operation {
+            name: "get-metadata",
+            about: "Get spreadsheet and tab properties",
+            fields: &[SPREADSHEET_ID],
+        },
+        Operation {
+            name: "add-sheet",
+            about: "Add a tab",
+            fields: &[SPREADSHEET_ID, Field::string("title", "Tab title")],
+        },
+        Operation {
+            name: "delete-sheet",
+            about: "Delete a tab",
+            fields: &[SPREADSHEET_ID, SHEET_ID],
+        },
+        Operation {
+            name: "rename-sheet",
+            about: "Rename a tab",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                Field::string("title", "New title"),
+            ],
+        },
+        Operation {
+            name: "copy-sheet",
+            about: "Copy a tab, optionally to another spreadsheet",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                Field::string(
+                    "destination_spreadsheet_id",
+                    "Target spreadsheet (default: the same one)",
+                ),
+            ],
+        },
+        Operation {
+            name: "format",
+            about: "Format cells",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                RANGE,
+                Field::boolean("bold", "Bold text"),
+                Field::boolean("italic", "Italic text"),
+                Field::boolean("underline", "Underlined text"),
+                Field::number("font_size", "Font size in points"),
+                Field::string("font_family", "Font family"),
+                Field::json(
+                    "foreground_color",
+                    "Text color as {\"red\", \"green\", \"blue\"}",
+                ),
+                Field::json(
+                    "background_color",
+                    "Fill color as {\"red\", \"green\", \"blue\"}",
+                ),
+                Field::string("horizontal_alignment", "LEFT, CENTER or RIGHT"),
+                Field::string("vertical_alignment", "TOP, MIDDLE or BOTTOM"),
+                Field::json("number_format", "{\"type\", \"pattern\"}"),
+                Field::string("wrap_strategy", "OVERFLOW_CELL, CLIP or WRAP"),
+                Field::integer("text_rotation", "Rotation angle in degrees"),
+                Field::json(
+                    "borders",
+                    "Border style per side (top, bottom, left, right)",
+                ),
+            ],
+        },
+        Operation {
+            name: "merge-cells",
+            about: "Merge cells",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                RANGE,
+                Field::string("merge_type", "MERGE_ALL, MERGE_COLUMNS or MERGE_ROWS"),
+            ],
+        },
+        Operation {
+            name: "unmerge-cells",
+            about: "Unmerge cells",
+            fields: &[SPREADSHEET_ID, SHEET_ID, RANGE],
+        },
+        Operation {
+            name: "freeze",
+            about: "Freeze rows and/or columns",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                Field::integer("rows", "Rows to freeze"),
+                Field::integer("cols", "Columns to freeze"),
+            ],
+        },
+        Operation {
+            name: "auto-resize",
+            about: "Fit column widths to their content",
+            fields: &[SPREADSHEET_ID, SHEET_ID, START_COL, END_COL],
+        },
+        Operation {
+            name: "sort",
+            about: "Sort a range by one column",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                RANGE,
+                Field::integer("sort_column", "Column to sort by (zero-based)"),
+                Field::boolean("ascending", "Sort ascending (default: true)"),
+            ],
+        },
+        Operation {
+            name: "find-replace",
+            about: "Find and replace cell values",
+            fields: &[
+                SPREADSHEET_ID,
+                Field::string("find", "Text to find"),
+                Field::string("replace", "Replacement text"),
+                Field::integer("sheet_id", "Limit to one tab (default: all tabs)"),
+                Field::boolean("match_case", "Match case"),
+                Field::boolean("match_entire_cell", "Only match whole cell values"),
+            ],
+        },
+        Operation {
+            name: "set-column-width",
+            about: "Set column widths",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                START_COL,
+                END_COL,
+                Field::integer("width", "Width in pixels"),
+            ],
+        },
+        Operation {
+            name: "set-row-height",
+            about: "Set row heights",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                Field::integer("start_row", "First row (zero-based)"),
+                Field::integer("end_row", "End row (zero-based, exclusive)"),
+                Field::integer("height", "Height in pixels"),
+            ],
+        },
+        Operation {
+            name: "add-filter",
+            about: "Set the basic filter",
+            fields: &[SPREADSHEET_ID, SHEET_ID, RANGE],
+        },
+        Operation {
+            name: "add-chart",
+            about: "Add a basic chart",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                RANGE,
+                Field::string("chart_type", "BAR, LINE, COLUMN, AREA, ..."),
+                Field::string("title", "Chart title"),
+            ],
+        },
+        Operation {
+            name: "protect-range",
+            about: "Protect a range",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                RANGE,
+                Field::string("description", "Description shown in Sheets"),
+                Field::list("editors", "Email allowed to edit (repeatable)"),
+            ],
+        },
+        Operation {
+            name: "add-conditional-format",
+            about: "Add a conditional format rule",
+            fields: &[
+                SPREADSHEET_ID,
+                SHEET_ID,
+                RANGE,
+                Field::string("rule_type", "boolean or gradient"),
+                Field::string("condition_type", "Boolean condition, e.g. NUMBER_GREATER"),
+                Field::json("condition_values", "Condition operands"),
+                Field::json(
+                    "format_background_color",
+                    "Fill color when the condition holds",
+                ),
+                Field::json(
+                    "format_foreground_color",
+                    "Text color when the condition holds",
+                ),
+                Field::boolean("format_bold", "Bold when the condition holds"),
+                Field::json("min_color", "Gradient minimum color"),
+                Field::string("min_type", "Gradient minimum type (default: MIN)"),
+                Field::json("max_color", "Gradient maximum color"),
+                Field::string("max_type", "Gradient maximum type (default: MAX)"),
+                Field::json("mid_color", "Gradient midpoint color"),
+                Field::string("mid_type", "Gradient midpoint type (default: PERCENTILE)"),
+                Field::string("mid_value", "Gradient midpoint value (default: 50)"),
+            ],
+        },
+    ],
+    run,
+};
+
+fn run(client: &GoogleClient, operation: &str, input: &Value) -> i32 {
+    match operation {
+        "create" => dispatch(client, "create", || {
+            let title = required_string(input, "title")?;
+            let sheets = string_list(input, "sheets");
+            let data = input
+                .get("data")
+                .and_then(|v| v.as_array())
+                .map(|rows| value_rows(rows));
+            Ok(json!(sheets::create_spreadsheet(
+                client,
+                &title,
+                &sheets,
+                data.as_deref()
+            )?))
+        }),
+        "read" => dispatch(client, "read", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let range = required_string(input, "range")?;
+            let read = sheets::read_range(client, &spreadsheet_id, &range)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "range": read.range,
+                "values": read.values,
+                "rows": read.rows(),
+                "columns": read.columns()
+            }))
+        }),
+        "write" => dispatch(client, "write", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let range = required_string(input, "range")?;
+            let values = value_rows(required_array(input, "values")?);
+            let updated = sheets::write_range(client, &spreadsheet_id, &range, &values)?;
+            Ok(with_spreadsheet_id(&spreadsheet_id, json!(updated)))
+        }),
+        "append" => dispatch(client, "append", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let range = required_string(input, "range")?;
+            let values = value_rows(required_array(input, "values")?);
+            let updated = sheets::append_rows(client, &spreadsheet_id, &range, &values)?;
+            Ok(with_spreadsheet_id(&spreadsheet_id, json!(updated)))
+        }),
+        "clear" => dispatch(client, "clear", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let range = required_string(input, "range")?;
+            sheets::clear_range(client, &spreadsheet_id, &range)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "cleared_range": range
+            }))
+        }),
+        "batch-read" => dispatch(client, "batch-read", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let ranges = required_array(input, "ranges")?
+                .iter()
+                .filter_map(|v| v.as_str().map(ToString::to_string))
+                .collect::<Vec<_>>();
+            if ranges.is_empty() {
+                anyhow::bail!("Required fields: spreadsheet_id, ranges");
+            }
+            let ranges = sheets::batch_read(client, &spreadsheet_id, &ranges)?
+                .into_iter()
+                .map(|read| {
+                    json!({
+                        "range": read.range,
+                        "rows": read.rows(),
+                        "columns": read.columns(),
+                        "values": read.values
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "ranges": ranges
+            }))
+        }),
+        "batch-write" => dispatch(client, "batch-write", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let data: Vec<RangeValues> =
+                serde_json::from_value(Value::Array(required_array(input, "data")?.clone()))
+                    .context("Each data entry needs a range and values")?;
+            let summary = sheets::batch_write(client, &spreadsheet_id, &data)?;
+            Ok(with_spreadsheet_id(&spreadsheet_id, json!(summary)))
+        }),
+        "get-metadata" => dispatch(client, "get-metadata", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            Ok(json!(sheets::get_metadata(client, &spreadsheet_id)?))
+        }),
+        "add-sheet" => dispatch(client, "add-sheet", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let title = required_string(input, "title")?;
+            let added = sheets::add_sheet(client, &spreadsheet_id, &title)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": added.sheet_id,
+                "title": added.title,
+                "index": added.index
+            }))
+        }),
+        "delete-sheet" => dispatch(client, "delete-sheet", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            sheets::delete_sheet(client, &spreadsheet_id, sheet_id)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "deleted_sheet_id": sheet_id
+            }))
+        }),
+        "rename-sheet" => dispatch(client, "rename-sheet", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let title = required_string(input, "title")?;
+            sheets::rename_sheet(client, &spreadsheet_id, sheet_id, &title)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "new_title": title
+            }))
+        }),
+        "copy-sheet" => dispatch(client, "copy-sheet", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let destination = optional_str(input, "destination_spreadsheet_id");
+            let copied = sheets::copy_sheet(client, &spreadsheet_id, sheet_id, destination)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "source_sheet_id": sheet_id,
+                "destination_spreadsheet_id": copied.destination_spreadsheet_id,
+                "new_sheet_id": copied.new_sheet_id,
+                "new_title": copied.new_title
+            }))
+        }),
+        "format" => dispatch(client, "format", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let range = required_string(input, "range")?;
+
+            let mut options = Map::new();
+            for key in [
+                "bold",
+                "italic",
+                "underline",
+                "font_size",
+                "font_family",
+                "foreground_color",
+                "background_color",
+                "horizontal_alignment",
+                "vertical_alignment",
+                "number_format",
+                "wrap_strategy",
+                "text_rotation",
+                "borders",
+            ] {
+                if let Some(value) = input.get(key) {
+                    options.insert(key.to_string(), value.clone());
+                }
+            }
+
+            sheets::format_cells(client, &spreadsheet_id, sheet_id, &range, &options)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "range": range,
+                "format_applied": options
+            }))
+        }),
+        "merge-cells" => dispatch(client, "merge-cells", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let range = required_string(input, "range")?;
+            let merge_type = optional_str(input, "merge_type").unwrap_or("MERGE_ALL");
+            sheets::merge_cells(client, &spreadsheet_id, sheet_id, &range, merge_type)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "range": range,
+                "merge_type": merge_type
+            }))
+        }),
+        "unmerge-cells" => dispatch(client, "unmerge-cells", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let range = required_string(input, "range")?;
+            sheets::unmerge_cells(client, &spreadsheet_id, sheet_id, &range)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "range": range
+            }))
+        }),
+        "freeze" => dispatch(client, "freeze", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let rows = input.get("rows").and_then(value_to_i64);
+            let cols = input.get("cols").and_then(value_to_i64);
+            sheets::freeze(client, &spreadsheet_id, sheet_id, rows, cols)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "frozen_rows": rows,
+                "frozen_cols": cols
+            }))
+        }),
+        "auto-resize" => dispatch(client, "auto-resize", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let start_col = required_i64(input, "start_col")?;
+            let end_col = required_i64(input, "end_col")?;
+            sheets::auto_resize(client, &spreadsheet_id, sheet_id, start_col, end_col)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "start_col": start_col,
+                "end_col": end_col
+            }))
+        }),
+        "sort" => dispatch(client, "sort", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let range = required_string(input, "range")?;
+            let sort_column = required_i64(input, "sort_column")?;
+            let ascending = optional_bool(input, "ascending").unwrap_or(true);
+            sheets::sort_range(
+                client,
+                &spreadsheet_id,
+                sheet_id,
+                &range,
+                sort_column,
+                ascending,
+            )?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "range": range,
+                "sort_column": sort_column,
+                "ascending": ascending
+            }))
+        }),
+        "find-replace" => dispatch(client, "find-replace", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let find = required_string(input, "find")?;
+            let replace = required_string(input, "replace")?;
+            let options = FindReplaceOptions {
+                sheet_id: input.get("sheet_id").and_then(value_to_i64),
+                match_case: optional_bool(input, "match_case").unwrap_or(false),
+                match_entire_cell: optional_bool(input, "match_entire_cell").unwrap_or(false),
+            };
+
+            let counts = sheets::find_replace(client, &spreadsheet_id, &find, &replace, options)?;
+            let mut fields = json!({
+                "spreadsheet_id": spreadsheet_id,
+                "find": find,
+                "replace": replace
+            });
+            if let (Some(target), Value::Object(counts)) = (fields.as_object_mut(), json!(counts)) {
+                target.extend(counts);
+            }
+            Ok(fields)
+        }),
+        "set-column-width" => dispatch(client, "set-column-width", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let start_col = required_i64(input, "start_col")?;
+            let end_col = required_i64(input, "end_col")?;
+            let width = required_i64(input, "width")?;
+            sheets::set_column_width(client, &spreadsheet_id, sheet_id, start_col, end_col, width)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "start_col": start_col,
+                "end_col": end_col,
+                "width": width
+            }))
+        }),
+        "set-row-height" => dispatch(client, "set-row-height", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let start_row = required_i64(input, "start_row")?;
+            let end_row = required_i64(input, "end_row")?;
+            let height = required_i64(input, "height")?;
+            sheets::set_row_height(
+                client,
+                &spreadsheet_id,
+                sheet_id,
+                start_row,
+                end_row,
+                height,
+            )?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "start_row": start_row,
+                "end_row": end_row,
+                "height": height
+            }))
+        }),
+        "add-filter" => dispatch(client, "add-filter", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let range = required_string(input, "range")?;
+            sheets::add_filter(client, &spreadsheet_id, sheet_id, &range)?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "range": range
+            }))
+        }),
+        "add-chart" => dispatch(client, "add-chart", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let range = required_string(input, "range")?;
+            let chart_type = required_string(input, "chart_type")?;
+            let title = required_string(input, "title")?;
+            let chart_id = sheets::add_chart(
+                client,
+                &spreadsheet_id,
+                sheet_id,
+                &range,
+                &chart_type,
+                &title,
+            )?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "chart_id": chart_id,
+                "title": title,
+                "chart_type": chart_type
+            }))
+        }),
+        "protect-range" => dispatch(client, "protect-range", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let range = required_string(input, "range")?;
+            let description = optional_str(input, "description");
+            let editors = string_list(input, "editors");
+            let protected = sheets::protect_range(
+                client,
+                &spreadsheet_id,
+                sheet_id,
+                &range,
+                description,
+                &editors,
+            )?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "range": range,
+                "protected_range_id": protected.protected_range_id,
+                "description": protected.description
+            }))
+        }),
+        "add-conditional-format" => dispatch(client, "add-conditional-format", || {
+            let spreadsheet_id = required_string(input, "spreadsheet_id")?;
+            let sheet_id = required_i64(input, "sheet_id")?;
+            let range = required_string(input, "range")?;
+            let rule_type = required_string(input, "rule_type")?;
+
+            let mut rule_params = Map::new();
+            if let Some(obj) = input.as_object() {
+                for (k, v) in obj {
+                    if ["spreadsheet_id", "sheet_id", "range", "rule_type"].contains(&k.as_str()) {
+                        continue;
+                    }
+                    rule_params.insert(k.clone(), v.clone());
+                }
+            }
+
+            sheets::add_conditional_format(
+                client,
+                &spreadsheet_id,
+                sheet_id,
+                &range,
+                &rule_type,
+                &rule_params,
+            )?;
+            Ok(json!({
+                "spreadsheet_id": spreadsheet_id,
+                "sheet_id": sheet_id,
+                "range": range,
+                "rule_type": rule_type
+            }))
+        }),
+        _ => invalid_args("INVALID_COMMAND", &format!("Unknown command: {operation}")),
+    }
+}
+
+fn value_rows(rows: &[Value]) -> Vec<Vec<Value>> {
+    rows.iter()
+        .map(|row| match row {
+            Value::Array(cells) => cells.clone(),
+            other => vec![other.clone()],
+        })
+        .collect()
+}
+
+fn with_spreadsheet_id(spreadsheet_id: &str, fields: Value) -> Value {
+    let mut payload = json!({ "spreadsheet_id": spreadsheet_id });
+    if let (Some(target), Value::Object(fields)) = (payload.as_object_mut(), fields) {
+        target.extend(fields);
+    }
+    payload
+}
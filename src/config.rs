@@ -0,0 +1,283 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_DOCS_BASE: &str = "https://docs.googleapis.com/v1";
+pub const DEFAULT_DRIVE_BASE: &str = "https://www.googleapis.com/drive/v3";
+pub const DEFAULT_DRIVE_UPLOAD_BASE: &str = "https://www.googleapis.com/upload/drive/v3";
+pub const DEFAULT_SHEETS_BASE: &str = "https://sheets.googleapis.com/v4";
+
+/// Optional settings read from `~/.claude/.google/config.json`. Every field may
+/// be omitted; a missing file is equivalent to `{}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillConfig {
+    /// Profile used when neither `--profile` nor `GOOGLE_SKILL_PROFILE` is given.
+    pub active_profile: Option<String>,
+    pub endpoints: EndpointOverrides,
+    pub service_account: ServiceAccountSettings,
+    pub token_store: TokenStoreSettings,
+}
+
+/// Where OAuth tokens are kept. Selected by `token_store` in the config file
+/// or `GOOGLE_SKILL_TOKEN_STORE`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenBackend {
+    /// `token.json`, readable only by the owner.
+    #[default]
+    File,
+    /// The desktop keyring via the Secret Service D-Bus API (Linux only).
+    SecretService,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenStoreSettings {
+    pub backend: TokenBackend,
+    /// Encrypt file-backed tokens with a key derived from
+    /// `GOOGLE_SKILL_TOKEN_PASSPHRASE`.
+    pub encrypt: bool,
+}
+
+/// Settings that only apply when the credentials file is a service account key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceAccountSettings {
+    /// User to impersonate through domain-wide delegation (the JWT `sub`).
+    pub subject: Option<String>,
+}
+
+/// Endpoint overrides as written in the config file. Unset fields fall back to
+/// the public Google endpoints (or, for OAuth, to the client secret file).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointOverrides {
+    /// Single root for every API, laid out like Google's hosts
+    /// (`<base>/v1/documents`, `<base>/drive/v3/files`, `<base>/token`, ...).
+    pub base: Option<String>,
+    pub docs: Option<String>,
+    pub drive: Option<String>,
+    pub drive_upload: Option<String>,
+    pub sheets: Option<String>,
+    pub oauth_auth: Option<String>,
+    pub oauth_token: Option<String>,
+    pub oauth_device: Option<String>,
+    pub oauth_revoke: Option<String>,
+}
+
+/// Resolved base URLs for every Google service the binaries talk to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    pub docs: String,
+    pub drive: String,
+    pub drive_upload: String,
+    pub sheets: String,
+    /// Replaces the `auth_uri` from the client secret file when set.
+    pub oauth_auth: Option<String>,
+    /// Replaces the `token_uri` from the client secret file when set.
+    pub oauth_token: Option<String>,
+    /// Replaces the device authorization endpoint when set.
+    pub oauth_device: Option<String>,
+    /// Replaces the token revocation endpoint when set.
+    pub oauth_revoke: Option<String>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            docs: DEFAULT_DOCS_BASE.to_string(),
+            drive: DEFAULT_DRIVE_BASE.to_string(),
+            drive_upload: DEFAULT_DRIVE_UPLOAD_BASE.to_string(),
+            sheets: DEFAULT_SHEETS_BASE.to_string(),
+            oauth_auth: None,
+            oauth_token: None,
+            oauth_device: None,
+            oauth_revoke: None,
+        }
+    }
+}
+
+impl SkillConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+
+        serde_json::from_str(&raw)
+            .with_context(|| format!("Failed to parse config JSON: {}", path.display()))
+    }
+
+    /// Sets `active_profile` in the config file at `path`, preserving any
+    /// other keys already present.
+    pub fn set_active_profile(path: &Path, profile: &str) -> Result<()> {
+        let mut value = if path.exists() {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+            if raw.trim().is_empty() {
+                serde_json::json!({})
+            } else {
+                serde_json::from_str(&raw)
+                    .with_context(|| format!("Failed to parse config JSON: {}", path.display()))?
+            }
+        } else {
+            serde_json::json!({})
+        };
+
+        let object = value.as_object_mut().ok_or_else(|| {
+            anyhow::anyhow!("Config file is not a JSON object: {}", path.display())
+        })?;
+        object.insert("active_profile".to_string(), serde_json::json!(profile));
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create config directory: {}", parent.display())
+            })?;
+        }
+        let rendered =
+            serde_json::to_string_pretty(&value).context("Failed serializing config JSON")?;
+        fs::write(path, rendered + "\n")
+            .with_context(|| format!("Failed writing config file: {}", path.display()))
+    }
+}
+
+impl TokenStoreSettings {
+    /// Resolves settings from the config file at `config_path`, letting
+    /// `GOOGLE_SKILL_TOKEN_STORE` (`file` or `secret-service`) pick the backend.
+    pub fn resolve(config_path: &Path) -> Result<Self> {
+        let mut settings = SkillConfig::load(config_path)?.token_store;
+        if let Some(backend) = std::env::var("GOOGLE_SKILL_TOKEN_STORE")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+        {
+            settings.backend = serde_json::from_value(serde_json::json!(backend)).map_err(|_| {
+                anyhow::anyhow!(
+                    "Unknown GOOGLE_SKILL_TOKEN_STORE '{backend}'; expected 'file' or 'secret-service'"
+                )
+            })?;
+        }
+        Ok(settings)
+    }
+}
+
+impl ServiceAccountSettings {
+    /// Resolves settings from the config file at `config_path`, letting
+    /// `GOOGLE_SKILL_IMPERSONATE` override the subject.
+    pub fn resolve(config_path: &Path) -> Result<Self> {
+        let mut settings = SkillConfig::load(config_path)?.service_account;
+        if let Some(subject) = std::env::var("GOOGLE_SKILL_IMPERSONATE")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+        {
+            settings.subject = Some(subject);
+        }
+        Ok(settings)
+    }
+}
+
+impl EndpointOverrides {
+    /// Layers `GOOGLE_SKILL_*_URL` environment variables over the file values.
+    fn with_env(mut self) -> Self {
+        let env = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        for (key, slot) in [
+            ("GOOGLE_SKILL_API_BASE", &mut self.base),
+            ("GOOGLE_SKILL_DOCS_URL", &mut self.docs),
+            ("GOOGLE_SKILL_DRIVE_URL", &mut self.drive),
+            ("GOOGLE_SKILL_DRIVE_UPLOAD_URL", &mut self.drive_upload),
+            ("GOOGLE_SKILL_SHEETS_URL", &mut self.sheets),
+            ("GOOGLE_SKILL_OAUTH_AUTH_URL", &mut self.oauth_auth),
+            ("GOOGLE_SKILL_OAUTH_TOKEN_URL", &mut self.oauth_token),
+            ("GOOGLE_SKILL_OAUTH_DEVICE_URL", &mut self.oauth_device),
+            ("GOOGLE_SKILL_OAUTH_REVOKE_URL", &mut self.oauth_revoke),
+        ] {
+            if let Some(value) = env(key) {
+                *slot = Some(value);
+            }
+        }
+
+        self
+    }
+}
+
+impl Endpoints {
+    /// Resolves endpoints from the config file at `config_path` and the
+    /// environment. Environment variables take precedence over the file, and
+    /// a specific endpoint takes precedence over `base`.
+    pub fn resolve(config_path: &Path) -> Result<Self> {
+        let config = SkillConfig::load(config_path)?;
+        Ok(Self::from_overrides(config.endpoints.with_env()))
+    }
+
+    pub fn from_overrides(overrides: EndpointOverrides) -> Self {
+        let base = overrides
+            .base
+            .as_deref()
+            .map(|b| b.trim_end_matches('/').to_string());
+        let derived = |suffix: &str| base.as_ref().map(|b| format!("{b}{suffix}"));
+        let defaults = Self::default();
+
+        Self {
+            docs: overrides
+                .docs
+                .or_else(|| derived("/v1"))
+                .unwrap_or(defaults.docs),
+            drive: overrides
+                .drive
+                .or_else(|| derived("/drive/v3"))
+                .unwrap_or(defaults.drive),
+            drive_upload: overrides
+                .drive_upload
+                .or_else(|| derived("/upload/drive/v3"))
+                .unwrap_or(defaults.drive_upload),
+            sheets: overrides
+                .sheets
+                .or_else(|| derived("/v4"))
+                .unwrap_or(defaults.sheets),
+            oauth_auth: overrides.oauth_auth.or_else(|| derived("/o/oauth2/auth")),
+            oauth_token: overrides.oauth_token.or_else(|| derived("/token")),
+            oauth_device: overrides.oauth_device.or_else(|| derived("/device/code")),
+            oauth_revoke: overrides.oauth_revoke.or_else(|| derived("/revoke")),
+        }
+    }
+
+    pub fn docs_url(&self, path: &str) -> String {
+        join_url(&self.docs, path)
+    }
+
+    pub fn drive_url(&self, path: &str) -> String {
+        join_url(&self.drive, path)
+    }
+
+    pub fn drive_upload_url(&self, path: &str) -> String {
+        join_url(&self.drive_upload, path)
+    }
+
+    pub fn sheets_url(&self, path: &str) -> String {
+        join_url(&self.sheets, path)
+    }
+}
+
+fn join_url(base: &str, path: &str) -> String {
+    let base = base.trim_end_matches('/');
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        base.to_string()
+    } else {
+        format!("{base}/{path}")
+    }
+}
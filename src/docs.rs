@@ -0,0 +1,1214 @@
+pub mod anchor;
+pub mod export;
+pub mod markdown;
+pub mod template;
+pub mod transaction;
+
+use crate::google_api::{GoogleApiError, GoogleClient};
+use crate::models::docs::{
+    BatchUpdateDocumentRequest, BatchUpdateDocumentResponse, Dimension, Document,
+    InsertInlineImageRequest, InsertPageBreakRequest, InsertTableRequest, Location, NamedStyleType,
+    Paragraph, ParagraphStyle, Range, ReplaceAllTextRequest, Request, Response, Size,
+    StructuralElement, SubstringMatchCriteria, Table, TextStyle, WriteControl,
+};
+use anchor::{Anchor, resolve_anchor};
+use markdown::{ParsedMarkdown, build_style_requests, parse_markdown, utf16_len};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DocsError {
+    #[error(transparent)]
+    Api(#[from] GoogleApiError),
+    #[error("Anchor not found: {0}")]
+    AnchorNotFound(String),
+    #[error("Anchor {0} marks a position, not a range")]
+    AnchorNotRange(String),
+    #[error("Document has changed since revision {0}")]
+    RevisionConflict(String),
+    #[error("{0}")]
+    InvalidEdit(String),
+}
+
+impl DocsError {
+    /// The `error_code` reported by the binaries for non-API failures.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::Api(_) => "API_ERROR",
+            Self::AnchorNotFound(_) => "ANCHOR_NOT_FOUND",
+            Self::AnchorNotRange(_) => "ANCHOR_NOT_RANGE",
+            Self::RevisionConflict(_) => "REVISION_CONFLICT",
+            Self::InvalidEdit(_) => "INVALID_OPS",
+        }
+    }
+}
+
+/// A section rewritten by [`replace_section`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionReplacement {
+    pub document_id: String,
+    pub heading: String,
+    /// Range of the new content.
+    pub start_index: i64,
+    pub end_index: i64,
+    /// In UTF-16 code units, like [`Insertion::text_length`].
+    pub removed_length: i64,
+    pub formats_applied: usize,
+    pub revision_id: Option<String>,
+}
+
+/// Plain-text rendering of a document, as returned by [`read_document`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentText {
+    pub document_id: Option<String>,
+    pub title: Option<String>,
+    /// Paragraph text, with table cells joined by ` | ` and rows by newlines.
+    pub content: String,
+    pub revision_id: Option<String>,
+}
+
+/// A document rendered as Markdown, as returned by [`export_markdown`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkdownExport {
+    pub document_id: String,
+    pub title: String,
+    pub markdown: String,
+    pub revision_id: Option<String>,
+}
+
+/// A document rendered as an HTML fragment, as returned by [`export_html`].
+#[derive(Debug, Clone, Serialize)]
+pub struct HtmlExport {
+    pub document_id: String,
+    pub title: String,
+    pub html: String,
+    pub revision_id: Option<String>,
+}
+
+/// The [`read_document`] text of a document with a map back to its indices,
+/// as returned by [`export_text`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TextExport {
+    pub document_id: String,
+    pub title: String,
+    pub text: String,
+    /// Covers every character taken from the document; the separators added
+    /// between paragraphs and table cells are left out.
+    pub index_map: Vec<IndexSpan>,
+    pub revision_id: Option<String>,
+}
+
+impl TextExport {
+    /// The document index of the character at `offset` (in UTF-16 code units)
+    /// in [`text`](Self::text), if it came from the document.
+    pub fn document_index(&self, offset: usize) -> Option<i64> {
+        span_index(&self.index_map, offset)
+    }
+}
+
+/// `length` UTF-16 code units of exported text starting at `offset`, which sit
+/// at `index..index + length` in the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct IndexSpan {
+    pub offset: usize,
+    pub index: i64,
+    pub length: usize,
+}
+
+/// Occurrences of a pattern in a document, as returned by [`find_text`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TextMatches {
+    pub document_id: String,
+    pub matches: Vec<TextMatch>,
+    pub revision_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TextMatch {
+    pub text: String,
+    pub start_index: i64,
+    /// Exclusive, like the `end_index` of a range.
+    pub end_index: i64,
+    /// The paragraph the match is in.
+    pub paragraph: ParagraphInfo,
+    /// The closest heading at or before the match, if any.
+    pub heading: Option<HeadingInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParagraphInfo {
+    /// Without the trailing newline.
+    pub text: String,
+    pub start_index: Option<i64>,
+    pub end_index: Option<i64>,
+}
+
+/// The headings of a document, as returned by [`get_structure`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentOutline {
+    pub document_id: Option<String>,
+    pub title: Option<String>,
+    pub structure: Vec<HeadingInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HeadingInfo {
+    /// 1 for `HEADING_1`, 2 for `HEADING_2`, and so on.
+    pub level: i64,
+    pub text: String,
+    pub start_index: Option<i64>,
+    pub end_index: Option<i64>,
+}
+
+/// Outcome of a `documents.batchUpdate` call.
+#[derive(Debug, Clone, Serialize)]
+pub struct WriteResult {
+    pub document_id: String,
+    /// Revision the document is at after the update, when Google reports it.
+    pub revision_id: Option<String>,
+}
+
+/// Something inserted at `index`: text, a page break or an inline image.
+/// Where content placed at an [`Anchor`] goes, from [`anchor_insertion`].
+#[derive(Debug, Clone)]
+pub struct AnchorInsertion {
+    /// Index the content is inserted at.
+    pub index: i64,
+    /// Deletes what the anchor covers (a section or text). It goes in the
+    /// same batch as the insert, ahead of it, so the two apply together.
+    pub delete: Option<Request>,
+    /// The anchor follows a heading that ends the body, so `index` is inside
+    /// that heading and the content has to open a paragraph of its own.
+    pub new_paragraph: bool,
+    pub revision_id: Option<String>,
+}
+
+impl AnchorInsertion {
+    fn at(index: i64, revision_id: Option<String>) -> Self {
+        Self {
+            index,
+            delete: None,
+            new_paragraph: false,
+            revision_id,
+        }
+    }
+
+    /// `text` as inserted at [`Self::index`], and the index it then starts
+    /// at. A new paragraph swaps the text's final newline for a leading one,
+    /// so the heading keeps its own and the text ends on the body's.
+    fn place(&self, text: &str) -> (String, i64) {
+        if self.new_paragraph {
+            let body = text.strip_suffix('\n').unwrap_or(text);
+            (format!("\n{body}"), self.index + 1)
+        } else {
+            (text.to_string(), self.index)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Insertion {
+    pub document_id: String,
+    pub index: i64,
+    /// In UTF-16 code units, so `index + text_length` is the end index.
+    pub text_length: usize,
+    pub revision_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Replacement {
+    pub document_id: String,
+    pub occurrences: i64,
+    pub revision_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedDocument {
+    pub document_id: String,
+    pub title: Option<String>,
+    pub revision_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableInsertion {
+    pub document_id: String,
+    pub rows: i64,
+    pub columns: i64,
+    pub index: i64,
+    pub revision_id: Option<String>,
+}
+
+/// A document created by [`create_from_markdown`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkdownDocument {
+    pub document_id: String,
+    pub title: String,
+    pub revision_id: Option<String>,
+    pub tables_inserted: usize,
+}
+
+/// Markdown added to an existing document by [`insert_from_markdown`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkdownInsertion {
+    pub document_id: String,
+    pub index: i64,
+    /// In UTF-16 code units, like [`Insertion::text_length`].
+    pub text_length: usize,
+    pub formats_applied: usize,
+    pub revision_id: Option<String>,
+}
+
+/// Reads the text of a document, tables included.
+pub fn read_document(
+    client: &GoogleClient,
+    document_id: &str,
+) -> Result<DocumentText, GoogleApiError> {
+    let document = get_document(client, document_id)?;
+    let content = extract_text_content(&document.body.content);
+
+    Ok(DocumentText {
+        document_id: Some(document.document_id),
+        title: Some(document.title),
+        content,
+        revision_id: document.revision_id,
+    })
+}
+
+/// Renders a document as Markdown that [`create_from_markdown`] reads back
+/// into the same headings, emphasis, links, lists, quotes, code and tables.
+pub fn export_markdown(
+    client: &GoogleClient,
+    document_id: &str,
+) -> Result<MarkdownExport, GoogleApiError> {
+    let document = get_document(client, document_id)?;
+    let markdown = export::to_markdown(&document);
+
+    Ok(MarkdownExport {
+        document_id: document.document_id,
+        title: document.title,
+        markdown,
+        revision_id: document.revision_id,
+    })
+}
+
+/// Renders a document as semantic HTML, without Docs' inline styles.
+pub fn export_html(client: &GoogleClient, document_id: &str) -> Result<HtmlExport, GoogleApiError> {
+    let document = get_document(client, document_id)?;
+    let html = export::to_html(&document);
+
+    Ok(HtmlExport {
+        document_id: document.document_id,
+        title: document.title,
+        html,
+        revision_id: document.revision_id,
+    })
+}
+
+/// Reads the text of a document like [`read_document`], along with the
+/// document index of every character in it.
+pub fn export_text(client: &GoogleClient, document_id: &str) -> Result<TextExport, GoogleApiError> {
+    let document = get_document(client, document_id)?;
+    let mut text = TextExtractor::default();
+    text.content(&document.body.content);
+
+    Ok(TextExport {
+        document_id: document.document_id,
+        title: document.title,
+        text: text.text,
+        index_map: text.spans,
+        revision_id: document.revision_id,
+    })
+}
+
+/// Compiles `query` for [`find_text`]: a regular expression when `regex` is
+/// set, otherwise literal text.
+pub fn text_pattern(query: &str, regex: bool, match_case: bool) -> Result<Regex, regex::Error> {
+    let pattern = if regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!match_case)
+        .build()
+}
+
+/// Finds every non-empty match of `pattern` in the document's paragraphs,
+/// table cells included. Matches do not span paragraphs.
+pub fn find_text(
+    client: &GoogleClient,
+    document_id: &str,
+    pattern: &Regex,
+) -> Result<TextMatches, GoogleApiError> {
+    let document = get_document(client, document_id)?;
+    let matches = document_matches(&document, pattern);
+
+    Ok(TextMatches {
+        document_id: document.document_id,
+        matches,
+        revision_id: document.revision_id,
+    })
+}
+
+fn document_matches(document: &Document, pattern: &Regex) -> Vec<TextMatch> {
+    let mut matches = Vec::new();
+    let mut heading = None;
+    collect_matches(&document.body.content, pattern, &mut heading, &mut matches);
+    matches
+}
+
+fn collect_matches(
+    content: &[StructuralElement],
+    pattern: &Regex,
+    heading: &mut Option<HeadingInfo>,
+    matches: &mut Vec<TextMatch>,
+) {
+    for element in content {
+        if let Some(table) = &element.table {
+            for cell in table.table_rows.iter().flat_map(|row| &row.table_cells) {
+                collect_matches(&cell.content, pattern, heading, matches);
+            }
+            continue;
+        }
+        let Some(paragraph) = &element.paragraph else {
+            continue;
+        };
+
+        let mut text = TextExtractor::default();
+        text.paragraph(paragraph);
+        if let Some(level) = paragraph
+            .paragraph_style
+            .named_style_type
+            .and_then(|style| style.heading_level())
+        {
+            *heading = Some(HeadingInfo {
+                level,
+                text: text.text.clone(),
+                start_index: element.start_index,
+                end_index: element.end_index,
+            });
+        }
+
+        for found in pattern.find_iter(&text.text) {
+            if found.is_empty() {
+                continue;
+            }
+            let start = utf16_len(&text.text[..found.start()]) as usize;
+            let end = utf16_len(&text.text[..found.end()]) as usize;
+            let (Some(start_index), Some(last_index)) = (
+                span_index(&text.spans, start),
+                span_index(&text.spans, end - 1),
+            ) else {
+                continue;
+            };
+            matches.push(TextMatch {
+                text: found.as_str().to_string(),
+                start_index,
+                end_index: last_index + 1,
+                paragraph: ParagraphInfo {
+                    text: text.text.trim_end_matches('\n').to_string(),
+                    start_index: element.start_index,
+                    end_index: element.end_index,
+                },
+                heading: heading.clone(),
+            });
+        }
+    }
+}
+
+/// Lists the headings of a document with their index ranges.
+pub fn get_structure(
+    client: &GoogleClient,
+    document_id: &str,
+) -> Result<DocumentOutline, GoogleApiError> {
+    let document = get_document(client, document_id)?;
+    let structure = headings(&document);
+
+    Ok(DocumentOutline {
+        document_id: Some(document.document_id),
+        title: Some(document.title),
+        structure,
+    })
+}
+
+/// The body's headings, in document order.
+fn headings(document: &Document) -> Vec<HeadingInfo> {
+    document
+        .body
+        .content
+        .iter()
+        .filter_map(|element| {
+            let paragraph = element.paragraph.as_ref()?;
+            let level = paragraph
+                .paragraph_style
+                .named_style_type?
+                .heading_level()?;
+            Some(HeadingInfo {
+                level,
+                text: paragraph.text(),
+                start_index: element.start_index,
+                end_index: element.end_index,
+            })
+        })
+        .collect()
+}
+
+/// The range `anchor` covers in the document as it is now, with the
+/// revision it was resolved against.
+pub fn anchor_range(
+    client: &GoogleClient,
+    document_id: &str,
+    anchor: &Anchor,
+    required_revision_id: Option<&str>,
+) -> Result<(Range, Option<String>), DocsError> {
+    let document = document_at(client, document_id, required_revision_id)?;
+    let range = resolve_anchor(&document, anchor)?;
+    Ok((range, document.revision_id))
+}
+
+/// Where content placed at `anchor` goes in the document as it is now.
+/// Ranges (sections and text) come with the request deleting them, so the
+/// new content replaces them.
+pub fn anchor_insertion(
+    client: &GoogleClient,
+    document_id: &str,
+    anchor: &Anchor,
+    required_revision_id: Option<&str>,
+) -> Result<AnchorInsertion, DocsError> {
+    let document = document_at(client, document_id, required_revision_id)?;
+    place_at_anchor(&document, anchor)
+}
+
+fn place_at_anchor(document: &Document, anchor: &Anchor) -> Result<AnchorInsertion, DocsError> {
+    let range = resolve_anchor(document, anchor)?;
+    let index = range.start_index;
+    // Heading anchors land on a paragraph start unless the heading is the
+    // body's last paragraph.
+    let new_paragraph = matches!(anchor, Anchor::AfterHeading(_) | Anchor::Section(_))
+        && !document
+            .body
+            .content
+            .iter()
+            .any(|element| element.paragraph.is_some() && element.start_index == Some(index));
+    Ok(AnchorInsertion {
+        index,
+        delete: (index < range.end_index)
+            .then(|| Request::delete_content_range(index, range.end_index)),
+        new_paragraph,
+        revision_id: document.revision_id.clone(),
+    })
+}
+
+/// Inserts `text` at `index` (1 is the start of the body).
+pub fn insert_text(
+    client: &GoogleClient,
+    document_id: &str,
+    text: &str,
+    index: i64,
+    required_revision_id: Option<&str>,
+) -> Result<Insertion, DocsError> {
+    let at = AnchorInsertion::at(index, required_revision_id.map(ToString::to_string));
+    insert_text_at(client, document_id, text, at, false)
+}
+
+/// Inserts `text` at `anchor`, replacing the section or text it covers in
+/// the same batch. Text replacing a section is made normal text.
+pub fn insert_text_at_anchor(
+    client: &GoogleClient,
+    document_id: &str,
+    text: &str,
+    anchor: &Anchor,
+    required_revision_id: Option<&str>,
+) -> Result<Insertion, DocsError> {
+    let at = anchor_insertion(client, document_id, anchor, required_revision_id)?;
+    let reset_style = matches!(anchor, Anchor::Section(_));
+    insert_text_at(client, document_id, text, at, reset_style)
+}
+
+fn insert_text_at(
+    client: &GoogleClient,
+    document_id: &str,
+    text: &str,
+    at: AnchorInsertion,
+    reset_style: bool,
+) -> Result<Insertion, DocsError> {
+    let (placed, index) = at.place(text);
+    let text_length = text.encode_utf16().count();
+    let mut requests: Vec<Request> = at.delete.into_iter().collect();
+    requests.push(Request::insert_text(placed, at.index));
+    if reset_style || at.new_paragraph {
+        requests.push(normal_text(index, text_length as i64));
+    }
+    let result = batch_update(client, document_id, requests, at.revision_id.as_deref())?;
+
+    Ok(Insertion {
+        document_id: document_id.to_string(),
+        index,
+        text_length,
+        revision_id: result.revision_id,
+    })
+}
+
+/// Resets the paragraphs in `index..index + length` to normal text. New
+/// paragraphs otherwise take the style of the one they are inserted into,
+/// which after a heading is that heading's.
+fn normal_text(index: i64, length: i64) -> Request {
+    Request::update_paragraph_style(
+        Range::new(index, index + length.max(1)),
+        ParagraphStyle {
+            named_style_type: Some(NamedStyleType::NormalText),
+            ..Default::default()
+        },
+        &["namedStyleType"],
+    )
+}
+
+/// Inserts `text` just before the final newline of the body.
+pub fn append_text(
+    client: &GoogleClient,
+    document_id: &str,
+    text: &str,
+    required_revision_id: Option<&str>,
+) -> Result<Insertion, DocsError> {
+    let document = document_at(client, document_id, required_revision_id)?;
+    let end_index = document.end_index().unwrap_or(1) - 1;
+    insert_text(
+        client,
+        document_id,
+        text,
+        end_index,
+        document.revision_id.as_deref(),
+    )
+}
+
+/// Replaces every occurrence of `find` with `replace`.
+pub fn replace_text(
+    client: &GoogleClient,
+    document_id: &str,
+    find: &str,
+    replace: &str,
+    match_case: bool,
+    required_revision_id: Option<&str>,
+) -> Result<Replacement, DocsError> {
+    let requests = vec![Request::ReplaceAllText(ReplaceAllTextRequest {
+        contains_text: SubstringMatchCriteria {
+            text: find.to_string(),
+            match_case,
+        },
+        replace_text: replace.to_string(),
+    })];
+
+    let result = batch_update(client, document_id, requests, required_revision_id)?;
+    let occurrences = result
+        .replies
+        .first()
+        .and_then(|reply| reply.replace_all_text.as_ref())
+        .map(|replaced| replaced.occurrences_changed)
+        .unwrap_or(0);
+
+    Ok(Replacement {
+        document_id: document_id.to_string(),
+        occurrences,
+        revision_id: result.revision_id,
+    })
+}
+
+/// Applies the fields set in `style` to the characters in
+/// `start_index..end_index`; unset fields are left untouched.
+pub fn format_text(
+    client: &GoogleClient,
+    document_id: &str,
+    start_index: i64,
+    end_index: i64,
+    style: TextStyle,
+    required_revision_id: Option<&str>,
+) -> Result<WriteResult, DocsError> {
+    let set_fields = match serde_json::to_value(&style) {
+        Ok(Value::Object(fields)) => fields.keys().cloned().collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+    let fields = set_fields.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let requests = vec![Request::update_text_style(
+        Range::new(start_index, end_index),
+        style,
+        &fields,
+    )];
+
+    batch_update(client, document_id, requests, required_revision_id)
+        .map(BatchUpdate::into_write_result)
+}
+
+pub fn insert_page_break(
+    client: &GoogleClient,
+    document_id: &str,
+    index: i64,
+    required_revision_id: Option<&str>,
+) -> Result<Insertion, DocsError> {
+    let requests = vec![Request::InsertPageBreak(InsertPageBreakRequest {
+        location: Location::at(index),
+    })];
+
+    let result = batch_update(client, document_id, requests, required_revision_id)?;
+
+    Ok(Insertion {
+        document_id: document_id.to_string(),
+        index,
+        text_length: 1,
+        revision_id: result.revision_id,
+    })
+}
+
+/// Inserts the image at `image_url` at `index`, or at the end of the body when
+/// `index` is `None`. `width` and `height` are in points.
+pub fn insert_image(
+    client: &GoogleClient,
+    document_id: &str,
+    image_url: &str,
+    index: Option<i64>,
+    width: Option<f64>,
+    height: Option<f64>,
+    required_revision_id: Option<&str>,
+) -> Result<Insertion, DocsError> {
+    let (insertion_index, revision_id) =
+        insertion_point(client, document_id, index, required_revision_id)?;
+
+    let object_size = (width.is_some() || height.is_some()).then(|| Size {
+        width: width.map(Dimension::points),
+        height: height.map(Dimension::points),
+    });
+
+    let requests = vec![Request::InsertInlineImage(InsertInlineImageRequest {
+        uri: image_url.to_string(),
+        location: Location::at(insertion_index),
+        object_size,
+    })];
+
+    let result = batch_update(client, document_id, requests, revision_id.as_deref())?;
+
+    Ok(Insertion {
+        document_id: document_id.to_string(),
+        index: insertion_index,
+        text_length: 1,
+        revision_id: result.revision_id,
+    })
+}
+
+/// Creates an empty document, optionally filled with plain `content`.
+pub fn create_document(
+    client: &GoogleClient,
+    title: &str,
+    content: Option<&str>,
+) -> Result<CreatedDocument, DocsError> {
+    let created = post_new_document(client, title)?;
+
+    if let Some(content) = content {
+        insert_text(client, &created.document_id, content, 1, None)?;
+    }
+
+    Ok(CreatedDocument {
+        document_id: created.document_id,
+        title: Some(created.title),
+        revision_id: created.revision_id,
+    })
+}
+
+/// Deletes the content in `start_index..end_index`.
+pub fn delete_content(
+    client: &GoogleClient,
+    document_id: &str,
+    start_index: i64,
+    end_index: i64,
+    required_revision_id: Option<&str>,
+) -> Result<WriteResult, DocsError> {
+    let requests = vec![Request::delete_content_range(start_index, end_index)];
+
+    batch_update(client, document_id, requests, required_revision_id)
+        .map(BatchUpdate::into_write_result)
+}
+
+/// Inserts a `rows` x `cols` table at `index`, or at the end of the body when
+/// `index` is `None`, and fills it from `data` (row-major; extra cells are
+/// ignored).
+pub fn insert_table(
+    client: &GoogleClient,
+    document_id: &str,
+    rows: i64,
+    cols: i64,
+    index: Option<i64>,
+    data: &[Vec<String>],
+    required_revision_id: Option<&str>,
+) -> Result<TableInsertion, DocsError> {
+    let (insertion_index, revision_id) =
+        insertion_point(client, document_id, index, required_revision_id)?;
+
+    let revision_id = insert_table_internal(
+        client,
+        document_id,
+        rows,
+        cols,
+        insertion_index,
+        data,
+        revision_id.as_deref(),
+    )?;
+
+    Ok(TableInsertion {
+        document_id: document_id.to_string(),
+        rows,
+        columns: cols,
+        index: insertion_index,
+        revision_id,
+    })
+}
+
+/// Inserts and fills a table, returning the revision the document ends at.
+fn insert_table_internal(
+    client: &GoogleClient,
+    document_id: &str,
+    rows: i64,
+    cols: i64,
+    index: i64,
+    data: &[Vec<String>],
+    required_revision_id: Option<&str>,
+) -> Result<Option<String>, DocsError> {
+    let insert_requests = vec![Request::InsertTable(InsertTableRequest {
+        rows,
+        columns: cols,
+        location: Location::at(index),
+    })];
+
+    let inserted = batch_update(client, document_id, insert_requests, required_revision_id)?;
+
+    if data.is_empty() {
+        return Ok(inserted.revision_id);
+    }
+
+    // Cell indices are read at the revision the table went in at, so an edit
+    // in between is still a conflict when the caller asked for one.
+    let document = document_at(
+        client,
+        document_id,
+        required_revision_id.and(inserted.revision_id.as_deref()),
+    )?;
+    let revision_id = document.revision_id;
+    let Some(table) = document
+        .body
+        .content
+        .into_iter()
+        .filter(|element| element.start_index.is_some_and(|start| start >= index))
+        .find_map(|element| element.table)
+    else {
+        return Ok(revision_id);
+    };
+
+    let mut cell_requests = Vec::new();
+
+    for (row_idx, row_data) in data.iter().enumerate().rev() {
+        if row_idx as i64 >= rows {
+            continue;
+        }
+
+        for (col_idx, text) in row_data.iter().enumerate().rev() {
+            if col_idx as i64 >= cols {
+                continue;
+            }
+
+            let cell_start = table
+                .table_rows
+                .get(row_idx)
+                .and_then(|row| row.table_cells.get(col_idx))
+                .and_then(|cell| cell.content.first())
+                .and_then(|first| first.start_index);
+
+            let Some(cell_start) = cell_start else {
+                continue;
+            };
+
+            cell_requests.push(Request::insert_text(text.as_str(), cell_start));
+        }
+    }
+
+    if cell_requests.is_empty() {
+        return Ok(revision_id);
+    }
+    let filled = batch_update(client, document_id, cell_requests, revision_id.as_deref())?;
+
+    Ok(filled.revision_id)
+}
+
+/// Creates a document titled `title` and fills it from `markdown`, with
+/// headings, emphasis, links, code, quotes, lists, images and tables styled.
+pub fn create_from_markdown(
+    client: &GoogleClient,
+    title: &str,
+    markdown: &str,
+) -> Result<MarkdownDocument, DocsError> {
+    let created = post_new_document(client, title)?;
+    let document_id = created.document_id;
+
+    let parsed = parse_markdown(markdown);
+
+    if !parsed.text.is_empty() {
+        insert_text(client, &document_id, &parsed.text, 1, None)?;
+    }
+
+    let format_requests = build_style_requests(&parsed, 1);
+
+    if !format_requests.is_empty() {
+        batch_update(client, &document_id, format_requests, None)?;
+    }
+
+    for table in parsed.tables.iter().rev() {
+        insert_table_internal(
+            client,
+            &document_id,
+            table.num_rows,
+            table.num_cols,
+            table.insert_index,
+            &table.rows,
+            None,
+        )?;
+    }
+
+    Ok(MarkdownDocument {
+        document_id,
+        title: title.to_string(),
+        revision_id: created.revision_id,
+        tables_inserted: parsed.tables.len(),
+    })
+}
+
+/// Inserts formatted `markdown` at `index`, or at the end of the body when
+/// `index` is `None`. Tables are not supported here.
+pub fn insert_from_markdown(
+    client: &GoogleClient,
+    document_id: &str,
+    markdown: &str,
+    index: Option<i64>,
+    required_revision_id: Option<&str>,
+) -> Result<MarkdownInsertion, DocsError> {
+    let (insertion_index, revision_id) =
+        insertion_point(client, document_id, index, required_revision_id)?;
+
+    insert_parsed_markdown(
+        client,
+        document_id,
+        &parse_markdown(markdown),
+        AnchorInsertion::at(insertion_index, revision_id),
+        false,
+    )
+}
+
+/// Inserts formatted `markdown` at `anchor`, replacing the section or text
+/// it covers in the same batch. Paragraphs replacing a section start out as
+/// normal text, like [`replace_section`].
+pub fn insert_markdown_at_anchor(
+    client: &GoogleClient,
+    document_id: &str,
+    markdown: &str,
+    anchor: &Anchor,
+    required_revision_id: Option<&str>,
+) -> Result<MarkdownInsertion, DocsError> {
+    let at = anchor_insertion(client, document_id, anchor, required_revision_id)?;
+    let reset_style = matches!(anchor, Anchor::Section(_));
+    insert_parsed_markdown(
+        client,
+        document_id,
+        &parse_markdown(markdown),
+        at,
+        reset_style,
+    )
+}
+
+/// Deletes what `at` replaces, inserts `parsed` and styles it in a single
+/// batch, so the document never holds a half-applied edit. With
+/// `reset_style`, the new paragraphs are made normal text before the
+/// Markdown's own styles apply.
+fn insert_parsed_markdown(
+    client: &GoogleClient,
+    document_id: &str,
+    parsed: &ParsedMarkdown,
+    at: AnchorInsertion,
+    reset_style: bool,
+) -> Result<MarkdownInsertion, DocsError> {
+    let (requests, index) = markdown_requests(parsed, &at, reset_style);
+    let mut revision_id = at.revision_id;
+    if !requests.is_empty() {
+        let updated = batch_update(client, document_id, requests, revision_id.as_deref())?;
+        revision_id = updated.revision_id;
+    }
+
+    Ok(MarkdownInsertion {
+        document_id: document_id.to_string(),
+        index,
+        text_length: parsed.final_len() as usize,
+        formats_applied: parsed.formats.len(),
+        revision_id,
+    })
+}
+
+/// The requests [`insert_parsed_markdown`] sends, and the index the
+/// Markdown's text starts at.
+fn markdown_requests(
+    parsed: &ParsedMarkdown,
+    at: &AnchorInsertion,
+    reset_style: bool,
+) -> (Vec<Request>, i64) {
+    let (text, index) = at.place(&parsed.text);
+    let length = utf16_len(&parsed.text);
+    let mut requests: Vec<Request> = at.delete.iter().cloned().collect();
+    if length > 0 {
+        requests.push(Request::insert_text(text, at.index));
+        if reset_style || at.new_paragraph {
+            requests.push(normal_text(index, length));
+        }
+        requests.extend(build_style_requests(parsed, index));
+    }
+    (requests, index)
+}
+
+/// Rewrites the section under the heading at `heading_path` (e.g.
+/// `"Status > Blockers"`): everything up to the next heading of the same or a
+/// higher level is replaced by `markdown` as normal text paragraphs, styled
+/// like [`insert_from_markdown`], in one batch.
+pub fn replace_section(
+    client: &GoogleClient,
+    document_id: &str,
+    heading_path: &str,
+    markdown: &str,
+    required_revision_id: Option<&str>,
+) -> Result<SectionReplacement, DocsError> {
+    let document = document_at(client, document_id, required_revision_id)?;
+    let at = place_at_anchor(&document, &Anchor::Section(heading_path.to_string()))?;
+    let removed_length = match &at.delete {
+        Some(Request::DeleteContentRange(delete)) => {
+            delete.range.end_index - delete.range.start_index
+        }
+        _ => 0,
+    };
+
+    let inserted =
+        insert_parsed_markdown(client, document_id, &parse_markdown(markdown), at, true)?;
+
+    Ok(SectionReplacement {
+        document_id: inserted.document_id,
+        heading: heading_path.to_string(),
+        start_index: inserted.index,
+        end_index: inserted.index + inserted.text_length as i64,
+        removed_length,
+        formats_applied: inserted.formats_applied,
+        revision_id: inserted.revision_id,
+    })
+}
+
+/// Fetches the `documents.get` resource.
+pub fn get_document(client: &GoogleClient, document_id: &str) -> Result<Document, GoogleApiError> {
+    let url = client
+        .endpoints()
+        .docs_url(&format!("documents/{document_id}"));
+    client.get(&url, &[])
+}
+
+/// Response of [`batch_update`].
+#[derive(Debug, Clone)]
+pub struct BatchUpdate {
+    pub document_id: String,
+    pub revision_id: Option<String>,
+    /// One reply per request, in request order.
+    pub replies: Vec<Response>,
+}
+
+impl BatchUpdate {
+    fn into_write_result(self) -> WriteResult {
+        WriteResult {
+            document_id: self.document_id,
+            revision_id: self.revision_id,
+        }
+    }
+}
+
+/// Sends `requests` as a single `documents.batchUpdate`. With
+/// `required_revision_id`, Google rejects the update if the document has been
+/// changed since that revision, which is reported as
+/// [`DocsError::RevisionConflict`].
+pub fn batch_update(
+    client: &GoogleClient,
+    document_id: &str,
+    requests: Vec<Request>,
+    required_revision_id: Option<&str>,
+) -> Result<BatchUpdate, DocsError> {
+    let url = client
+        .endpoints()
+        .docs_url(&format!("documents/{document_id}:batchUpdate"));
+    let payload = BatchUpdateDocumentRequest {
+        requests,
+        write_control: required_revision_id.map(|revision_id| WriteControl {
+            required_revision_id: Some(revision_id.to_string()),
+            target_revision_id: None,
+        }),
+    };
+    let response: BatchUpdateDocumentResponse =
+        client
+            .post(&url, &[], &payload)
+            .map_err(|err| match required_revision_id {
+                // Google refuses a stale `requiredRevisionId` as a failed
+                // precondition; any other 400 is a bad request.
+                Some(revision_id) if err.api_status().as_deref() == Some("FAILED_PRECONDITION") => {
+                    DocsError::RevisionConflict(revision_id.to_string())
+                }
+                _ => DocsError::Api(err),
+            })?;
+
+    Ok(BatchUpdate {
+        document_id: document_id.to_string(),
+        revision_id: response
+            .write_control
+            .and_then(|control| control.required_revision_id),
+        replies: response.replies,
+    })
+}
+
+/// Fetches the document to compute indices from, failing with
+/// [`DocsError::RevisionConflict`] if it is no longer at
+/// `required_revision_id`.
+fn document_at(
+    client: &GoogleClient,
+    document_id: &str,
+    required_revision_id: Option<&str>,
+) -> Result<Document, DocsError> {
+    let document = get_document(client, document_id)?;
+    if let Some(required) = required_revision_id
+        && document.revision_id.as_deref() != Some(required)
+    {
+        return Err(DocsError::RevisionConflict(required.to_string()));
+    }
+    Ok(document)
+}
+
+/// `index`, or the end of the body when `None`, with the revision to write
+/// at: the one the end of the body was read from in that case.
+fn insertion_point(
+    client: &GoogleClient,
+    document_id: &str,
+    index: Option<i64>,
+    required_revision_id: Option<&str>,
+) -> Result<(i64, Option<String>), DocsError> {
+    match index {
+        Some(index) => Ok((index, required_revision_id.map(ToString::to_string))),
+        None => {
+            let document = document_at(client, document_id, required_revision_id)?;
+            Ok((document.end_index().unwrap_or(1) - 1, document.revision_id))
+        }
+    }
+}
+
+/// Index just before the body's trailing newline, where appended content goes.
+pub fn end_of_body(client: &GoogleClient, document_id: &str) -> Result<i64, GoogleApiError> {
+    let document = get_document(client, document_id)?;
+    Ok(document.end_index().unwrap_or(1) - 1)
+}
+
+fn post_new_document(client: &GoogleClient, title: &str) -> Result<Document, GoogleApiError> {
+    let created: Document = client.post(
+        &client.endpoints().docs_url("documents"),
+        &[],
+        &json!({"title": title}),
+    )?;
+    if created.document_id.is_empty() {
+        return Err(GoogleApiError::Parse(
+            "Failed to parse documentId from create response".to_string(),
+        ));
+    }
+    Ok(created)
+}
+
+/// Flattens structural elements to text: paragraphs as-is, tables with cells
+/// joined by ` | ` and rows by newlines.
+pub fn extract_text_content(content_elements: &[StructuralElement]) -> String {
+    let mut text = TextExtractor::default();
+    text.content(content_elements);
+    text.text
+}
+
+/// Builds the [`extract_text_content`] text while recording where each text
+/// run came from.
+#[derive(Default)]
+struct TextExtractor {
+    text: String,
+    /// Length of `text` in UTF-16 code units.
+    len: usize,
+    spans: Vec<IndexSpan>,
+}
+
+impl TextExtractor {
+    fn content(&mut self, content_elements: &[StructuralElement]) {
+        let blocks = content_elements
+            .iter()
+            .filter(|element| element.paragraph.is_some() || element.table.is_some());
+        for (i, element) in blocks.enumerate() {
+            if i > 0 {
+                self.push("\n", None);
+            }
+            if let Some(paragraph) = &element.paragraph {
+                self.paragraph(paragraph);
+            } else if let Some(table) = &element.table {
+                self.table(table);
+            }
+        }
+    }
+
+    fn paragraph(&mut self, paragraph: &Paragraph) {
+        for element in &paragraph.elements {
+            if let Some(text_run) = &element.text_run {
+                self.push(&text_run.content, element.start_index);
+            }
+        }
+    }
+
+    fn table(&mut self, table: &Table) {
+        for (r, row) in table.table_rows.iter().enumerate() {
+            if r > 0 {
+                self.push("\n", None);
+            }
+            for (c, cell) in row.table_cells.iter().enumerate() {
+                if c > 0 {
+                    self.push(" | ", None);
+                }
+                self.content(&cell.content);
+            }
+        }
+    }
+
+    /// Appends `text`, mapped to the document from `index` on when it has one.
+    fn push(&mut self, text: &str, index: Option<i64>) {
+        let length = utf16_len(text) as usize;
+        if let Some(index) = index
+            && length > 0
+        {
+            match self.spans.last_mut() {
+                Some(last)
+                    if last.offset + last.length == self.len
+                        && last.index + last.length as i64 == index =>
+                {
+                    last.length += length;
+                }
+                _ => self.spans.push(IndexSpan {
+                    offset: self.len,
+                    index,
+                    length,
+                }),
+            }
+        }
+        self.text.push_str(text);
+        self.len += length;
+    }
+}
+
+/// The document index of the text at `offset` according to `spans`.
+fn span_index(spans: &[IndexSpan], offset: usize) -> Option<i64> {
+    spans
+        .iter()
+        .find(|span| (span.offset..span.offset + span.length).contains(&offset))
+        .map(|span| span.index + (offset - span.offset) as i64)
+}
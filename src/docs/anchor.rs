@@ -0,0 +1,278 @@
+use super::{DocsError, HeadingInfo, document_matches, headings, text_pattern};
+use crate::models::docs::{Document, Range};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A place in a document named by its content instead of an index, written
+/// in JSON as `{"after_heading": "Risks"}`, `{"after_table": 1}` and so on.
+/// Headings match on their text without surrounding whitespace, and a path
+/// such as `"Status > Blockers"` picks a heading nested in an earlier one's
+/// section. Text anchors match the first occurrence, case-sensitively.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    /// The start of the heading's paragraph.
+    BeforeHeading(String),
+    /// The start of the paragraph after the heading, or the end of its text
+    /// when it is the body's last paragraph.
+    AfterHeading(String),
+    /// Everything under the heading up to the next heading of the same or a
+    /// higher level, or the end of the body.
+    Section(String),
+    /// The start of the text.
+    BeforeText(String),
+    /// Just past the end of the text.
+    AfterText(String),
+    /// The text itself.
+    Text(String),
+    /// The end of the Nth table (counting from 1), where the paragraph after
+    /// it starts.
+    AfterTable(usize),
+}
+
+impl Anchor {
+    /// Whether the anchor covers content rather than marking a position.
+    pub fn is_range(&self) -> bool {
+        matches!(self, Self::Section(_) | Self::Text(_))
+    }
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BeforeHeading(heading) => write!(f, "before heading {heading:?}"),
+            Self::AfterHeading(heading) => write!(f, "after heading {heading:?}"),
+            Self::Section(heading) => write!(f, "section {heading:?}"),
+            Self::BeforeText(text) => write!(f, "before text {text:?}"),
+            Self::AfterText(text) => write!(f, "after text {text:?}"),
+            Self::Text(text) => write!(f, "text {text:?}"),
+            Self::AfterTable(n) => write!(f, "after table {n}"),
+        }
+    }
+}
+
+/// The range `anchor` covers in `document`; positions come back as empty
+/// ranges.
+pub fn resolve_anchor(document: &Document, anchor: &Anchor) -> Result<Range, DocsError> {
+    let not_found = || DocsError::AnchorNotFound(anchor.to_string());
+    let point = |index: i64| Range::new(index, index);
+
+    match anchor {
+        Anchor::BeforeHeading(title) | Anchor::AfterHeading(title) | Anchor::Section(title) => {
+            let headings = headings(document);
+            let position = heading_position(&headings, title).ok_or_else(not_found)?;
+            let heading = &headings[position];
+            let (Some(start), Some(end)) = (heading.start_index, heading.end_index) else {
+                return Err(not_found());
+            };
+            // Nothing can be inserted at or deleted past the body's final
+            // newline, so a heading that ends the body ends just before it.
+            let body_end = document.end_index().map(|index| index - 1);
+            let after = body_end.map_or(end, |body_end| end.min(body_end));
+            match anchor {
+                Anchor::BeforeHeading(_) => Ok(point(start)),
+                Anchor::AfterHeading(_) => Ok(point(after)),
+                _ => {
+                    let section_end = headings[position + 1..]
+                        .iter()
+                        .find(|next| next.level <= heading.level)
+                        .and_then(|next| next.start_index)
+                        .or(body_end)
+                        .unwrap_or(after);
+                    Ok(Range::new(after, section_end.max(after)))
+                }
+            }
+        }
+        Anchor::BeforeText(text) | Anchor::AfterText(text) | Anchor::Text(text) => {
+            let pattern = text_pattern(text, false, true).map_err(|_| not_found())?;
+            let found = document_matches(document, &pattern)
+                .into_iter()
+                .next()
+                .ok_or_else(not_found)?;
+            match anchor {
+                Anchor::BeforeText(_) => Ok(point(found.start_index)),
+                Anchor::AfterText(_) => Ok(point(found.end_index)),
+                _ => Ok(Range::new(found.start_index, found.end_index)),
+            }
+        }
+        Anchor::AfterTable(n) => document
+            .body
+            .content
+            .iter()
+            .filter(|element| element.table.is_some())
+            .nth(n.checked_sub(1).ok_or_else(not_found)?)
+            .and_then(|element| element.end_index)
+            .map(point)
+            .ok_or_else(not_found),
+    }
+}
+
+/// The position in `headings` of the first heading matching `path`, a list
+/// of titles separated by `>` where each is looked for inside the section of
+/// the one before.
+fn heading_position(headings: &[HeadingInfo], path: &str) -> Option<usize> {
+    let mut within = 0..headings.len();
+    let mut found = None;
+    for title in path.split('>').map(str::trim) {
+        let position = within.clone().find(|&i| headings[i].text.trim() == title)?;
+        let level = headings[position].level;
+        let section_end = headings[position + 1..]
+            .iter()
+            .position(|next| next.level <= level)
+            .map_or(headings.len(), |offset| position + 1 + offset);
+        within = position + 1..section_end;
+        found = Some(position);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    /// A document with one paragraph per `(text, named style)` pair, laid out
+    /// from index 1 like the real API. `"TABLE"` stands for a five-index table.
+    fn document(blocks: &[(&str, &str)]) -> Document {
+        let mut index = 1;
+        let content: Vec<Value> = blocks
+            .iter()
+            .map(|&(text, style)| {
+                let start = index;
+                if text == "TABLE" {
+                    index += 5;
+                    return json!({
+                        "startIndex": start,
+                        "endIndex": index,
+                        "table": {"rows": 1, "columns": 1}
+                    });
+                }
+                let content = format!("{text}\n");
+                index += content.encode_utf16().count() as i64;
+                json!({
+                    "startIndex": start,
+                    "endIndex": index,
+                    "paragraph": {
+                        "elements": [{
+                            "startIndex": start,
+                            "endIndex": index,
+                            "textRun": {"content": content}
+                        }],
+                        "paragraphStyle": {"namedStyleType": style}
+                    }
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({"documentId": "doc", "body": {"content": content}}))
+            .expect("valid document")
+    }
+
+    fn plan() -> Document {
+        document(&[
+            ("Plan", "HEADING_1"),
+            ("Intro", "NORMAL_TEXT"),
+            ("TABLE", ""),
+            ("Risks", "HEADING_2"),
+            ("Old risk", "NORMAL_TEXT"),
+            ("Detail", "HEADING_3"),
+            ("Next", "HEADING_2"),
+            ("Status", "HEADING_1"),
+            ("Risks", "HEADING_2"),
+        ])
+    }
+
+    fn resolve(document: &Document, anchor: Anchor) -> (i64, i64) {
+        let range = resolve_anchor(document, &anchor).expect("anchor resolves");
+        (range.start_index, range.end_index)
+    }
+
+    #[test]
+    fn headings_resolve_to_their_paragraph_and_section() {
+        let document = plan();
+        assert_eq!(
+            resolve(&document, Anchor::BeforeHeading("Risks".into())),
+            (17, 17)
+        );
+        assert_eq!(
+            resolve(&document, Anchor::AfterHeading("Risks".into())),
+            (23, 23)
+        );
+        // Deeper headings belong to the section; the next level 2 ends it.
+        assert_eq!(
+            resolve(&document, Anchor::Section("Risks".into())),
+            (23, 39)
+        );
+        assert_eq!(resolve(&document, Anchor::Section("Next".into())), (44, 44));
+        // The last section stops short of the body's final newline.
+        assert_eq!(
+            resolve(&document, Anchor::Section("Status".into())),
+            (51, 56)
+        );
+    }
+
+    #[test]
+    fn heading_paths_pick_a_nested_heading() {
+        let document = plan();
+        assert_eq!(
+            resolve(&document, Anchor::BeforeHeading(" Status > Risks ".into())),
+            (51, 51)
+        );
+        assert!(matches!(
+            resolve_anchor(&document, &Anchor::Section("Next > Risks".into())),
+            Err(DocsError::AnchorNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn a_heading_that_ends_the_body_is_clamped_before_its_newline() {
+        let document = plan();
+        assert_eq!(
+            resolve(&document, Anchor::AfterHeading("Status > Risks".into())),
+            (56, 56)
+        );
+        assert_eq!(
+            resolve(&document, Anchor::Section("Status > Risks".into())),
+            (56, 56)
+        );
+    }
+
+    #[test]
+    fn text_and_table_anchors() {
+        let document = plan();
+        assert_eq!(
+            resolve(&document, Anchor::BeforeText("risk".into())),
+            (27, 27)
+        );
+        assert_eq!(
+            resolve(&document, Anchor::AfterText("risk".into())),
+            (31, 31)
+        );
+        assert_eq!(
+            resolve(&document, Anchor::Text("Old risk".into())),
+            (23, 31)
+        );
+        assert_eq!(resolve(&document, Anchor::AfterTable(1)), (17, 17));
+        for missing in [
+            Anchor::Text("Budget".into()),
+            Anchor::AfterTable(0),
+            Anchor::AfterTable(2),
+        ] {
+            assert!(matches!(
+                resolve_anchor(&document, &missing),
+                Err(DocsError::AnchorNotFound(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn only_sections_and_text_are_ranges() {
+        assert!(Anchor::Section("Risks".into()).is_range());
+        assert!(Anchor::Text("risk".into()).is_range());
+        assert!(!Anchor::AfterHeading("Risks".into()).is_range());
+        assert!(!Anchor::AfterTable(1).is_range());
+        assert_eq!(
+            serde_json::from_value::<Anchor>(json!({"after_heading": "Risks"})).unwrap(),
+            Anchor::AfterHeading("Risks".into())
+        );
+    }
+}
@@ -0,0 +1,787 @@
+use crate::models::docs::{Document, Paragraph, Table, TextStyle};
+use std::collections::HashMap;
+
+/// Fonts treated as code when exporting.
+const MONOSPACE_FONTS: &[&str] = &[
+    "Courier New",
+    "Consolas",
+    "Roboto Mono",
+    "Source Code Pro",
+    "Inconsolata",
+];
+
+/// Renders the body of `document` as Markdown that
+/// [`parse_markdown`](super::markdown::parse_markdown) turns back into the
+/// same paragraphs: named styles become headings, text styles emphasis, code
+/// and links, bullets lists, indented paragraphs quotes and tables pipe
+/// tables. Empty paragraphs become blank lines.
+pub fn to_markdown(document: &Document) -> String {
+    let blocks = blocks(document);
+    let mut out = String::new();
+    let mut previous: Option<&Block> = None;
+    let mut empty_paragraphs = 0;
+    let mut numbering: HashMap<(&str, usize), usize> = HashMap::new();
+
+    for block in &blocks {
+        if matches!(block, Block::Empty) {
+            empty_paragraphs += 1;
+            continue;
+        }
+
+        match previous {
+            Some(Block::Quote(_)) if empty_paragraphs == 0 && matches!(block, Block::Quote(_)) => {
+                out.push_str(">\n");
+            }
+            Some(previous) => {
+                let blank_lines = if empty_paragraphs > 0 {
+                    empty_paragraphs
+                } else {
+                    usize::from(needs_blank_line(previous, block))
+                };
+                out.push_str(&"\n".repeat(blank_lines));
+            }
+            None => out.push_str(&"\n".repeat(empty_paragraphs)),
+        }
+
+        let inline = |paragraph| inline_markdown(document, paragraph, false);
+        match block {
+            Block::Heading(level, paragraph) => {
+                out.push_str(&format!("{} {}\n", "#".repeat(*level), inline(paragraph)));
+            }
+            Block::Paragraph(paragraph) => {
+                out.push_str(&format!("{}\n", escape_line_start(&inline(paragraph))));
+            }
+            Block::Quote(paragraph) => {
+                out.push_str(&format!("> {}\n", escape_line_start(&inline(paragraph))));
+            }
+            Block::Code(lines) => {
+                let fence = code_fence(lines);
+                out.push_str(&format!("{fence}\n{}\n{fence}\n", lines.join("\n")));
+            }
+            Block::ListItem {
+                list_id,
+                level,
+                kind,
+                paragraph,
+            } => {
+                numbering.retain(|(id, l), _| id != list_id || l <= level);
+                let marker = match kind {
+                    ListKind::Bullet => "-".to_string(),
+                    ListKind::Checkbox => "- [ ]".to_string(),
+                    ListKind::Ordered => {
+                        let number = numbering.entry((list_id, *level)).or_insert(0);
+                        *number += 1;
+                        format!("{number}.")
+                    }
+                };
+                let indent = "    ".repeat(*level);
+                out.push_str(&format!("{indent}{marker} {}\n", inline(paragraph)));
+            }
+            Block::Table(table) => out.push_str(&pipe_table(&table_cells(table, |paragraph| {
+                inline_markdown(document, paragraph, true)
+            }))),
+            Block::Rule => out.push_str("---\n"),
+            Block::Empty => {}
+        }
+
+        previous = Some(block);
+        empty_paragraphs = 0;
+    }
+
+    out
+}
+
+/// Renders the body of `document` as an HTML fragment made of semantic
+/// elements only (`h1`-`h6`, `p`, `ul`/`ol`, `blockquote`, `pre`, `table`,
+/// `strong`, `em`, `a`, ...), with none of the inline styles Docs exports.
+/// Empty paragraphs are dropped.
+pub fn to_html(document: &Document) -> String {
+    let blocks = blocks(document);
+    let mut out = String::new();
+    let mut lists = HtmlLists::default();
+    let mut in_quote = false;
+
+    for block in &blocks {
+        if matches!(block, Block::Empty) {
+            continue;
+        }
+        if !matches!(block, Block::ListItem { .. }) {
+            lists.close_all(&mut out);
+        }
+        if in_quote && !matches!(block, Block::Quote(_)) {
+            out.push_str("</blockquote>\n");
+            in_quote = false;
+        }
+
+        let inline = |paragraph| inline_html(document, paragraph);
+        match block {
+            Block::Heading(level, paragraph) => {
+                out.push_str(&format!("<h{level}>{}</h{level}>\n", inline(paragraph)));
+            }
+            Block::Paragraph(paragraph) => {
+                out.push_str(&format!("<p>{}</p>\n", inline(paragraph)));
+            }
+            Block::Quote(paragraph) => {
+                if !in_quote {
+                    out.push_str("<blockquote>\n");
+                    in_quote = true;
+                }
+                out.push_str(&format!("<p>{}</p>\n", inline(paragraph)));
+            }
+            Block::Code(lines) => {
+                out.push_str(&format!(
+                    "<pre><code>{}</code></pre>\n",
+                    escape_html(&lines.join("\n"))
+                ));
+            }
+            Block::ListItem {
+                list_id,
+                level,
+                kind,
+                paragraph,
+            } => lists.item(&mut out, list_id, *level, *kind, &inline(paragraph)),
+            Block::Table(table) => {
+                out.push_str(&html_table(&table_cells(table, |paragraph| {
+                    inline_html(document, paragraph)
+                })));
+            }
+            Block::Rule => out.push_str("<hr>\n"),
+            Block::Empty => {}
+        }
+    }
+
+    lists.close_all(&mut out);
+    if in_quote {
+        out.push_str("</blockquote>\n");
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListKind {
+    Bullet,
+    Ordered,
+    Checkbox,
+}
+
+/// A paragraph or table, classified by what it reads as.
+#[derive(Debug)]
+enum Block<'a> {
+    Heading(usize, &'a Paragraph),
+    Paragraph(&'a Paragraph),
+    Quote(&'a Paragraph),
+    /// Consecutive monospace paragraphs, one line each.
+    Code(Vec<String>),
+    ListItem {
+        list_id: &'a str,
+        level: usize,
+        kind: ListKind,
+        paragraph: &'a Paragraph,
+    },
+    Table(&'a Table),
+    Rule,
+    Empty,
+}
+
+fn blocks(document: &Document) -> Vec<Block<'_>> {
+    let mut blocks: Vec<Block> = Vec::new();
+
+    for element in &document.body.content {
+        if let Some(table) = &element.table {
+            // insertTable adds an empty paragraph before the table, and Docs
+            // keeps one after it; both are implied by the table itself.
+            if matches!(blocks.last(), Some(Block::Empty)) {
+                blocks.pop();
+            }
+            blocks.push(Block::Table(table));
+            continue;
+        }
+        let Some(paragraph) = &element.paragraph else {
+            continue;
+        };
+        if matches!(blocks.last(), Some(Block::Table(_))) && is_empty(paragraph) {
+            // The paragraph Docs keeps after a table.
+            continue;
+        }
+
+        let block = paragraph_block(document, paragraph);
+        match (blocks.last_mut(), block) {
+            (Some(Block::Code(lines)), Block::Code(more)) => lines.extend(more),
+            (_, block) => blocks.push(block),
+        }
+    }
+
+    blocks
+}
+
+fn paragraph_block<'a>(document: &'a Document, paragraph: &'a Paragraph) -> Block<'a> {
+    if is_rule(paragraph) {
+        return Block::Rule;
+    }
+    if is_code(paragraph) {
+        return Block::Code(vec![paragraph.text().trim_end_matches('\n').to_string()]);
+    }
+
+    if let Some(bullet) = &paragraph.bullet {
+        let list_id = bullet.list_id.as_deref().unwrap_or_default();
+        let level = bullet.nesting_level.unwrap_or(0).max(0) as usize;
+        let nesting_level = document
+            .lists
+            .get(list_id)
+            .and_then(|list| list.list_properties.nesting_levels.get(level));
+        let kind = match nesting_level {
+            Some(glyph) if glyph.is_ordered() => ListKind::Ordered,
+            Some(glyph) if glyph.glyph_symbol.is_none() => ListKind::Checkbox,
+            _ => ListKind::Bullet,
+        };
+        return Block::ListItem {
+            list_id,
+            level,
+            kind,
+            paragraph,
+        };
+    }
+    if is_empty(paragraph) {
+        return Block::Empty;
+    }
+
+    let style = &paragraph.paragraph_style;
+    if let Some(level) = style.named_style_type.and_then(|s| s.heading_level()) {
+        return Block::Heading(level as usize, paragraph);
+    }
+    let indented = style
+        .indent_start
+        .as_ref()
+        .is_some_and(|indent| indent.magnitude > 0.0);
+    if indented {
+        Block::Quote(paragraph)
+    } else {
+        Block::Paragraph(paragraph)
+    }
+}
+
+fn is_empty(paragraph: &Paragraph) -> bool {
+    paragraph.bullet.is_none()
+        && paragraph.elements.iter().all(|element| {
+            element.inline_object_element.is_none()
+                && element.horizontal_rule.is_none()
+                && element
+                    .text_run
+                    .as_ref()
+                    .is_none_or(|run| run.content.trim_end_matches('\n').is_empty())
+        })
+}
+
+/// A native horizontal rule, or a line of em dashes as `create-from-markdown`
+/// writes for `---`.
+fn is_rule(paragraph: &Paragraph) -> bool {
+    if paragraph
+        .elements
+        .iter()
+        .any(|element| element.horizontal_rule.is_some())
+    {
+        return true;
+    }
+    let text = paragraph.text();
+    let text = text.trim_end_matches('\n');
+    text.chars().count() >= 3 && text.chars().all(|c| c == '—')
+}
+
+/// A paragraph set entirely in a monospace font without a background, which
+/// is how code blocks are written (inline code has a background).
+fn is_code(paragraph: &Paragraph) -> bool {
+    if paragraph.bullet.is_some()
+        || paragraph
+            .paragraph_style
+            .named_style_type
+            .and_then(|s| s.heading_level())
+            .is_some()
+    {
+        return false;
+    }
+    let runs: Vec<_> = paragraph
+        .elements
+        .iter()
+        .filter_map(|element| element.text_run.as_ref())
+        .filter(|run| !run.content.trim_end_matches('\n').is_empty())
+        .collect();
+    let runs = if runs.is_empty() {
+        // An empty line inside a code block carries the font on its newline.
+        paragraph
+            .elements
+            .iter()
+            .filter_map(|element| element.text_run.as_ref())
+            .collect()
+    } else {
+        runs
+    };
+    !runs.is_empty()
+        && runs
+            .iter()
+            .all(|run| is_monospace(&run.text_style) && run.text_style.background_color.is_none())
+        && paragraph
+            .elements
+            .iter()
+            .all(|element| element.inline_object_element.is_none())
+}
+
+fn is_monospace(style: &TextStyle) -> bool {
+    style
+        .weighted_font_family
+        .as_ref()
+        .is_some_and(|font| MONOSPACE_FONTS.contains(&font.font_family.as_str()))
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Inline {
+    bold: bool,
+    italic: bool,
+    strikethrough: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+impl Inline {
+    fn of(style: &TextStyle) -> Self {
+        Self {
+            bold: style.bold == Some(true),
+            italic: style.italic == Some(true),
+            strikethrough: style.strikethrough == Some(true),
+            code: is_monospace(style),
+            link: style.link.as_ref().and_then(|link| link.url.clone()),
+        }
+    }
+
+    fn markers(&self) -> Vec<&'static str> {
+        let mut markers = Vec::new();
+        if self.bold {
+            markers.push("**");
+        }
+        if self.italic {
+            markers.push("*");
+        }
+        if self.strikethrough {
+            markers.push("~~");
+        }
+        markers
+    }
+}
+
+/// A run of text with one style, or an image.
+enum Segment {
+    Text(String, Inline),
+    Image { url: String, alt: String },
+}
+
+/// The paragraph's text runs, with neighbours of equal style merged and the
+/// trailing newline dropped, and its images.
+fn segments(document: &Document, paragraph: &Paragraph) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    for element in &paragraph.elements {
+        if let Some(run) = &element.text_run {
+            let text = run.content.trim_end_matches('\n');
+            if text.is_empty() {
+                continue;
+            }
+            let style = Inline::of(&run.text_style);
+            match segments.last_mut() {
+                Some(Segment::Text(previous, previous_style)) if *previous_style == style => {
+                    previous.push_str(text);
+                }
+                _ => segments.push(Segment::Text(text.to_string(), style)),
+            }
+        } else if let Some(object) = &element.inline_object_element
+            && let Some(image) = document.inline_objects.get(&object.inline_object_id)
+        {
+            let embedded = &image.inline_object_properties.embedded_object;
+            let Some(properties) = &embedded.image_properties else {
+                continue;
+            };
+            let Some(url) = properties
+                .source_uri
+                .as_ref()
+                .or(properties.content_uri.as_ref())
+            else {
+                continue;
+            };
+            let alt = embedded
+                .description
+                .as_ref()
+                .or(embedded.title.as_ref())
+                .cloned()
+                .unwrap_or_default();
+            segments.push(Segment::Image {
+                url: url.clone(),
+                alt,
+            });
+        }
+    }
+    segments
+}
+
+/// Splits segments into images and stretches of text runs sharing a link, so
+/// each link is rendered once around all of its runs.
+fn link_groups(segments: &[Segment]) -> Vec<&[Segment]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    while start < segments.len() {
+        let mut end = start + 1;
+        if let Segment::Text(_, style) = &segments[start] {
+            while let Some(Segment::Text(_, next)) = segments.get(end)
+                && next.link == style.link
+            {
+                end += 1;
+            }
+        }
+        groups.push(&segments[start..end]);
+        start = end;
+    }
+    groups
+}
+
+fn text_runs(group: &[Segment]) -> Vec<(&str, &Inline)> {
+    group
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Text(text, style) => Some((text.as_str(), style)),
+            Segment::Image { .. } => None,
+        })
+        .collect()
+}
+
+/// The paragraph's content as inline Markdown, without its newline. In table
+/// cells `|` is escaped too.
+fn inline_markdown(document: &Document, paragraph: &Paragraph, in_table: bool) -> String {
+    let segments = segments(document, paragraph);
+    let mut out = String::new();
+    for group in link_groups(&segments) {
+        match group {
+            [Segment::Image { url, alt }] => {
+                let alt = escape_text(alt, in_table);
+                out.push_str(&format!("![{alt}]({})", link_destination(url)));
+            }
+            [Segment::Text(_, style), ..] => {
+                let styled = styled_runs(&text_runs(group), in_table);
+                match &style.link {
+                    Some(url) => out.push_str(&format!("[{styled}]({})", link_destination(url))),
+                    None => out.push_str(&styled),
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// The paragraph's content as inline HTML, without its newline.
+fn inline_html(document: &Document, paragraph: &Paragraph) -> String {
+    let segments = segments(document, paragraph);
+    let mut out = String::new();
+    for group in link_groups(&segments) {
+        match group {
+            [Segment::Image { url, alt }] => out.push_str(&format!(
+                "<img src=\"{}\" alt=\"{}\">",
+                escape_html(url),
+                escape_html(alt)
+            )),
+            [Segment::Text(_, style), ..] => {
+                let mut runs = String::new();
+                for (text, style) in text_runs(group) {
+                    let mut html = escape_html(text).replace('\u{b}', "<br>");
+                    for (on, tag) in [
+                        (style.code, "code"),
+                        (style.strikethrough, "s"),
+                        (style.italic, "em"),
+                        (style.bold, "strong"),
+                    ] {
+                        if on {
+                            html = format!("<{tag}>{html}</{tag}>");
+                        }
+                    }
+                    runs.push_str(&html);
+                }
+                match &style.link {
+                    Some(url) => {
+                        out.push_str(&format!("<a href=\"{}\">{runs}</a>", escape_html(url)))
+                    }
+                    None => out.push_str(&runs),
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Wraps runs in emphasis markers, opening and closing each only where the
+/// style changes and keeping whitespace outside the markers.
+fn styled_runs(runs: &[(&str, &Inline)], in_table: bool) -> String {
+    let mut out = String::new();
+    let mut open: Vec<&'static str> = Vec::new();
+    let mut pending_space = String::new();
+
+    for (text, style) in runs {
+        let wanted = style.markers();
+        while open.iter().any(|marker| !wanted.contains(marker)) {
+            if let Some(marker) = open.pop() {
+                out.push_str(marker);
+            }
+        }
+        out.push_str(&std::mem::take(&mut pending_space));
+
+        let core = text.trim();
+        if core.is_empty() {
+            out.push_str(text);
+            continue;
+        }
+        let leading = &text[..text.len() - text.trim_start().len()];
+        let trailing = &text[text.trim_end().len()..];
+        out.push_str(leading);
+        for marker in wanted {
+            if !open.contains(&marker) {
+                out.push_str(marker);
+                open.push(marker);
+            }
+        }
+        if style.code {
+            out.push_str(&code_span(core, in_table));
+        } else {
+            out.push_str(&escape_text(core, in_table));
+        }
+        pending_space = trailing.to_string();
+    }
+
+    while let Some(marker) = open.pop() {
+        out.push_str(marker);
+    }
+    out.push_str(&pending_space);
+    out
+}
+
+/// Backslash-escapes the characters that would otherwise start inline
+/// Markdown syntax. Line breaks inside a paragraph become hard breaks.
+fn escape_text(text: &str, in_table: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '~' | '&' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '|' if in_table => out.push_str("\\|"),
+            '\u{b}' if in_table => out.push(' '),
+            '\u{b}' => out.push_str("\\\n"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes a paragraph start that would read as a heading, list item, quote
+/// or code block. Indentation is written as character references, which
+/// Markdown neither strips nor reads as a code block.
+fn escape_line_start(text: &str) -> String {
+    let body = text.trim_start_matches([' ', '\t']);
+    let indent: String = text[..text.len() - body.len()]
+        .chars()
+        .map(|c| if c == '\t' { "&#9;" } else { "&#32;" })
+        .collect();
+    let digits = body.chars().take_while(char::is_ascii_digit).count();
+    if body.starts_with(['#', '-', '+', '>', '=']) {
+        format!("{indent}\\{body}")
+    } else if digits > 0 && body[digits..].starts_with(['.', ')']) {
+        format!("{indent}{}\\{}", &body[..digits], &body[digits..])
+    } else {
+        format!("{indent}{body}")
+    }
+}
+
+/// Wraps `text` in a backtick fence longer than any run inside it. In a pipe
+/// table a `|` still ends the cell inside code, so it is escaped there too.
+fn code_span(text: &str, in_table: bool) -> String {
+    let text = if in_table {
+        text.replace('|', "\\|")
+    } else {
+        text.to_string()
+    };
+    let longest = longest_backtick_run(&text);
+    let fence = "`".repeat(longest + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{fence} {text} {fence}")
+    } else {
+        format!("{fence}{text}{fence}")
+    }
+}
+
+fn code_fence(lines: &[String]) -> String {
+    let longest = lines
+        .iter()
+        .map(|line| longest_backtick_run(line))
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+fn link_destination(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{url}>")
+    } else {
+        url.to_string()
+    }
+}
+
+/// Each cell's paragraphs rendered with `render` and joined by spaces.
+fn table_cells(table: &Table, render: impl Fn(&Paragraph) -> String) -> Vec<Vec<String>> {
+    table
+        .table_rows
+        .iter()
+        .map(|row| {
+            row.table_cells
+                .iter()
+                .map(|cell| {
+                    cell.content
+                        .iter()
+                        .filter_map(|element| element.paragraph.as_ref())
+                        .map(&render)
+                        .filter(|text| !text.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn pipe_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    let line = |cells: &[String]| {
+        let mut line = String::from("|");
+        for column in 0..columns {
+            let cell = cells.get(column).map(String::as_str).unwrap_or_default();
+            line.push_str(&format!(" {cell} |"));
+        }
+        line.push('\n');
+        line
+    };
+
+    let mut out = String::new();
+    let header = rows.first().cloned().unwrap_or_default();
+    out.push_str(&line(&header));
+    out.push_str(&line(&vec!["---".to_string(); columns]));
+    for row in rows.iter().skip(1) {
+        out.push_str(&line(row));
+    }
+    out
+}
+
+/// The first row becomes the header, as in the pipe tables
+/// `create-from-markdown` reads.
+fn html_table(rows: &[Vec<String>]) -> String {
+    let row = |cells: &[String], tag: &str| {
+        let cells: String = cells
+            .iter()
+            .map(|cell| format!("<{tag}>{cell}</{tag}>"))
+            .collect();
+        format!("<tr>{cells}</tr>\n")
+    };
+
+    let mut out = String::from("<table>\n");
+    if let Some((header, body)) = rows.split_first() {
+        out.push_str(&format!("<thead>\n{}</thead>\n", row(header, "th")));
+        if !body.is_empty() {
+            let body: String = body.iter().map(|cells| row(cells, "td")).collect();
+            out.push_str(&format!("<tbody>\n{body}</tbody>\n"));
+        }
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// The `ul`/`ol` elements open around the current list item, one per nesting
+/// level, each with its last `li` still open.
+#[derive(Default)]
+struct HtmlLists<'a> {
+    list_id: &'a str,
+    open: Vec<&'static str>,
+}
+
+impl<'a> HtmlLists<'a> {
+    fn item(
+        &mut self,
+        out: &mut String,
+        list_id: &'a str,
+        level: usize,
+        kind: ListKind,
+        text: &str,
+    ) {
+        if list_id != self.list_id {
+            self.close_all(out);
+            self.list_id = list_id;
+        }
+        while self.open.len() > level + 1 {
+            self.close(out);
+        }
+        if self.open.len() == level + 1 {
+            out.push_str("</li>\n");
+        }
+        while self.open.len() < level + 1 {
+            if !self.open.is_empty() {
+                out.push('\n');
+            }
+            let tag = if kind == ListKind::Ordered {
+                "ol"
+            } else {
+                "ul"
+            };
+            out.push_str(&format!("<{tag}>\n"));
+            self.open.push(tag);
+        }
+        if kind == ListKind::Checkbox {
+            out.push_str(&format!("<li><input type=\"checkbox\" disabled> {text}"));
+        } else {
+            out.push_str(&format!("<li>{text}"));
+        }
+    }
+
+    fn close(&mut self, out: &mut String) {
+        if let Some(tag) = self.open.pop() {
+            out.push_str(&format!("</li>\n</{tag}>\n"));
+        }
+    }
+
+    fn close_all(&mut self, out: &mut String) {
+        while !self.open.is_empty() {
+            self.close(out);
+        }
+        self.list_id = "";
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether two adjacent blocks need a blank line between them to stay
+/// separate when parsed back.
+fn needs_blank_line(previous: &Block, next: &Block) -> bool {
+    !matches!(
+        (previous, next),
+        (Block::ListItem { .. }, Block::ListItem { .. })
+            | (Block::Heading(..) | Block::Code(_) | Block::Rule, _)
+            | (_, Block::Heading(..) | Block::Code(_))
+    )
+}
@@ -0,0 +1,655 @@
+use crate::models::docs::{
+    BulletGlyphPreset, Dimension, InsertInlineImageRequest, Link, Location, NamedStyleType,
+    OptionalColor, ParagraphStyle, Range, Request, TextStyle, WeightedFontFamily,
+};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+/// Google Docs lists have nine nesting levels.
+const MAX_NESTING_LEVELS: usize = 9;
+
+const CODE_FONT: &str = "Courier New";
+
+/// How far block quotes are indented, in points.
+const BLOCKQUOTE_INDENT: f64 = 36.0;
+
+const HORIZONTAL_RULE: &str = "———————————————————————————\n";
+
+/// Line break within a paragraph, which Docs represents as a vertical tab.
+const LINE_BREAK: &str = "\u{b}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatType {
+    Heading1,
+    Heading2,
+    Heading3,
+    Heading4,
+    Heading5,
+    Heading6,
+    Bold,
+    Italic,
+    Strikethrough,
+    Code,
+    /// Fenced or indented code: whole paragraphs in a monospace font.
+    CodeBlock,
+    /// Paragraphs inside a `>` quote, indented.
+    Blockquote,
+}
+
+/// A style to apply to `start..end`, in document indices (UTF-16 code units).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatInfo {
+    pub format_type: FormatType,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Text in `start..end` that links to `url`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkInfo {
+    pub url: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// An inline image. `index` is where it goes once the lists are created, so
+/// images are inserted after them, back to front.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageInfo {
+    pub url: String,
+    pub index: i64,
+}
+
+/// A run of list items, turned into a native list with `preset` once the text
+/// is in place. `start..end` covers the items' paragraphs, which carry
+/// `nesting_tabs` leading tabs in total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListInfo {
+    pub preset: BulletGlyphPreset,
+    pub start: i64,
+    pub end: i64,
+    pub nesting_tabs: i64,
+}
+
+/// A Markdown table, inserted at `insert_index` after the text is in place,
+/// the lists are created (which removes their nesting tabs) and the images
+/// are inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    pub rows: Vec<Vec<String>>,
+    pub insert_index: i64,
+    pub num_rows: i64,
+    pub num_cols: i64,
+}
+
+/// Result of [`parse_markdown`]. Indices assume the text is inserted at
+/// index 1; shift them for any other insertion point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedMarkdown {
+    pub text: String,
+    pub formats: Vec<FormatInfo>,
+    pub links: Vec<LinkInfo>,
+    pub images: Vec<ImageInfo>,
+    pub lists: Vec<ListInfo>,
+    pub tables: Vec<TableInfo>,
+}
+
+impl ParsedMarkdown {
+    /// Length of the text once the lists are created and the images inserted,
+    /// in UTF-16 code units.
+    pub fn final_len(&self) -> i64 {
+        utf16_len(&self.text) - self.lists.iter().map(|l| l.nesting_tabs).sum::<i64>()
+            + self.images.len() as i64
+    }
+}
+
+/// Converts CommonMark (with GFM tables, strikethrough and task lists) into
+/// the text to insert at index 1, with the ranges to style afterwards, the
+/// links, images and lists to create and the tables to insert. Blank lines
+/// between blocks are kept as empty paragraphs.
+pub fn parse_markdown(markdown: &str) -> ParsedMarkdown {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut builder = Builder::new(markdown);
+    for (event, range) in Parser::new_ext(markdown, options).into_offset_iter() {
+        builder.event(event, range);
+    }
+    builder.parsed
+}
+
+enum Span {
+    Format(FormatType),
+    Link(String),
+}
+
+struct OpenList {
+    preset: BulletGlyphPreset,
+    start: i64,
+    nesting_tabs: i64,
+    checkbox: bool,
+}
+
+#[derive(Default)]
+struct OpenTable {
+    rows: Vec<Vec<String>>,
+    row: Vec<String>,
+    cell: String,
+}
+
+/// Walks the parser's events, appending paragraphs to `parsed.text` and
+/// recording where each style applies.
+struct Builder<'a> {
+    source: &'a str,
+    parsed: ParsedMarkdown,
+    /// Document index of the next character appended.
+    index: i64,
+    /// Whether the current paragraph has content and still needs its newline.
+    line_open: bool,
+    /// Block-level elements open around the current event.
+    block_depth: usize,
+    /// Source offset just past the content of the last top-level block.
+    last_block_end: usize,
+    /// Start indices of open headings, code blocks and quotes.
+    block_starts: Vec<i64>,
+    spans: Vec<(Span, i64)>,
+    list_depth: usize,
+    list: Option<OpenList>,
+    table: Option<OpenTable>,
+    in_code_block: bool,
+    image_depth: usize,
+    /// Nesting tabs emitted so far, all of which the lists will remove.
+    tabs: i64,
+}
+
+impl<'a> Builder<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            parsed: ParsedMarkdown {
+                text: String::new(),
+                formats: Vec::new(),
+                links: Vec::new(),
+                images: Vec::new(),
+                lists: Vec::new(),
+                tables: Vec::new(),
+            },
+            index: 1,
+            line_open: false,
+            block_depth: 0,
+            last_block_end: 0,
+            block_starts: Vec::new(),
+            spans: Vec::new(),
+            list_depth: 0,
+            list: None,
+            table: None,
+            in_code_block: false,
+            image_depth: 0,
+            tabs: 0,
+        }
+    }
+
+    fn event(&mut self, event: Event<'a>, range: std::ops::Range<usize>) {
+        self.track_blocks(&event, range);
+
+        if let Some(table) = self.table.as_mut() {
+            match event {
+                Event::Text(text) | Event::Code(text) => table.cell.push_str(&text),
+                Event::End(TagEnd::TableCell) => {
+                    let cell = std::mem::take(&mut table.cell);
+                    table.row.push(cell.trim().to_string());
+                }
+                Event::End(TagEnd::TableHead | TagEnd::TableRow) => {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                }
+                Event::End(TagEnd::Table) => self.finish_table(),
+                _ => {}
+            }
+            return;
+        }
+
+        // Alt text is dropped; the image itself is inserted separately.
+        if self.image_depth > 0 && !matches!(event, Event::End(TagEnd::Image)) {
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.in_code_block => {
+                self.push(&text);
+                self.line_open = !text.ends_with('\n');
+            }
+            Event::Text(text) | Event::InlineHtml(text) => self.push_inline(&text),
+            Event::Code(code) => {
+                let start = self.open_line();
+                self.push(&code);
+                self.format(FormatType::Code, start, self.index);
+            }
+            Event::Html(html) => {
+                self.open_line();
+                self.push(&html);
+                self.line_open = !html.ends_with('\n');
+            }
+            Event::SoftBreak => self.push_inline(" "),
+            Event::HardBreak => self.push_inline(LINE_BREAK),
+            Event::Rule => {
+                self.close_line();
+                self.push(HORIZONTAL_RULE);
+            }
+            Event::TaskListMarker(_) => {
+                if self.list_depth == 1
+                    && let Some(list) = self.list.as_mut()
+                {
+                    list.checkbox = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Keeps blank source lines between top-level blocks as empty paragraphs.
+    fn track_blocks(&mut self, event: &Event<'a>, range: std::ops::Range<usize>) {
+        let (starts, ends) = match event {
+            Event::Start(tag) => (!is_inline(tag), false),
+            Event::End(tag) => (false, !is_inline_end(tag)),
+            Event::Rule => (true, true),
+            _ => (false, false),
+        };
+
+        if starts {
+            if self.block_depth == 0 {
+                let gap = &self.source[self.last_block_end..range.start];
+                let at_line_start =
+                    self.last_block_end == 0 || self.source[..self.last_block_end].ends_with('\n');
+                let gap = if at_line_start {
+                    gap
+                } else {
+                    gap.split_once('\n').map_or("", |(_, rest)| rest)
+                };
+                let blank_lines = gap
+                    .split_inclusive('\n')
+                    .filter(|line| line.ends_with('\n') && line.trim().is_empty())
+                    .count();
+                for _ in 0..blank_lines {
+                    self.push("\n");
+                }
+            }
+            self.block_depth += 1;
+        }
+        if ends {
+            self.block_depth -= 1;
+            if self.block_depth == 0 {
+                let content = self.source[range.clone()].trim_end();
+                self.last_block_end = range.start + content.len();
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag<'a>) {
+        match tag {
+            Tag::Paragraph | Tag::Item | Tag::HtmlBlock => self.close_line(),
+            Tag::Heading { .. } => {
+                self.close_line();
+                let start = self.open_line();
+                self.block_starts.push(start);
+            }
+            Tag::BlockQuote(_) => {
+                self.close_line();
+                self.block_starts.push(self.index);
+            }
+            Tag::CodeBlock(_) => {
+                self.close_line();
+                self.in_code_block = true;
+                self.block_starts.push(self.index);
+            }
+            Tag::List(first) => {
+                self.close_line();
+                self.list_depth += 1;
+                if self.list_depth == 1 {
+                    self.list = Some(OpenList {
+                        preset: if first.is_some() {
+                            BulletGlyphPreset::NumberedDecimalAlphaRoman
+                        } else {
+                            BulletGlyphPreset::BulletDiscCircleSquare
+                        },
+                        start: self.index,
+                        nesting_tabs: 0,
+                        checkbox: false,
+                    });
+                }
+            }
+            Tag::Table(_) => {
+                self.close_line();
+                self.table = Some(OpenTable::default());
+            }
+            Tag::Emphasis => self.open_span(Span::Format(FormatType::Italic)),
+            Tag::Strong => self.open_span(Span::Format(FormatType::Bold)),
+            Tag::Strikethrough => self.open_span(Span::Format(FormatType::Strikethrough)),
+            Tag::Link { dest_url, .. } => self.open_span(Span::Link(dest_url.to_string())),
+            Tag::Image { dest_url, .. } => {
+                self.open_line();
+                self.parsed.images.push(ImageInfo {
+                    url: dest_url.to_string(),
+                    index: self.index - self.tabs,
+                });
+                self.image_depth += 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::Item | TagEnd::HtmlBlock => self.close_line(),
+            TagEnd::Heading(level) => {
+                let start = self.block_starts.pop().unwrap_or(self.index);
+                self.format(heading_format(level), start, self.index);
+                self.close_line();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.close_line();
+                let start = self.block_starts.pop().unwrap_or(self.index);
+                self.format(FormatType::Blockquote, start, self.index - 1);
+            }
+            TagEnd::CodeBlock => {
+                self.close_line();
+                self.in_code_block = false;
+                let start = self.block_starts.pop().unwrap_or(self.index);
+                self.format(FormatType::CodeBlock, start, self.index - 1);
+            }
+            TagEnd::List(_) => {
+                self.close_line();
+                self.list_depth -= 1;
+                if self.list_depth == 0
+                    && let Some(list) = self.list.take()
+                    && self.index > list.start
+                {
+                    self.parsed.lists.push(ListInfo {
+                        preset: if list.checkbox {
+                            BulletGlyphPreset::BulletCheckbox
+                        } else {
+                            list.preset
+                        },
+                        start: list.start,
+                        end: self.index - 1,
+                        nesting_tabs: list.nesting_tabs,
+                    });
+                }
+            }
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                self.close_span();
+            }
+            TagEnd::Image => self.image_depth -= 1,
+            _ => {}
+        }
+    }
+
+    fn finish_table(&mut self) {
+        let Some(table) = self.table.take() else {
+            return;
+        };
+        if table.rows.is_empty() {
+            return;
+        }
+
+        let num_rows = table.rows.len() as i64;
+        let num_cols = table.rows.first().map(|r| r.len()).unwrap_or(0) as i64;
+        self.parsed.tables.push(TableInfo {
+            rows: table.rows,
+            insert_index: self.index - self.tabs + self.parsed.images.len() as i64,
+            num_rows,
+            num_cols,
+        });
+        self.push("\n");
+    }
+
+    fn open_span(&mut self, span: Span) {
+        let start = self.open_line();
+        self.spans.push((span, start));
+    }
+
+    fn close_span(&mut self) {
+        let Some((span, start)) = self.spans.pop() else {
+            return;
+        };
+        match span {
+            Span::Format(format_type) => self.format(format_type, start, self.index),
+            Span::Link(url) if self.index > start => self.parsed.links.push(LinkInfo {
+                url,
+                start,
+                end: self.index,
+            }),
+            Span::Link(_) => {}
+        }
+    }
+
+    fn format(&mut self, format_type: FormatType, start: i64, end: i64) {
+        if end > start {
+            self.parsed.formats.push(FormatInfo {
+                format_type,
+                start,
+                end,
+            });
+        }
+    }
+
+    /// Starts a paragraph if none is open, indenting list items with one tab
+    /// per nesting level. Returns the index content will be appended at.
+    fn open_line(&mut self) -> i64 {
+        if !self.line_open {
+            self.line_open = true;
+            if self.list_depth > 0 {
+                let level = (self.list_depth - 1).min(MAX_NESTING_LEVELS - 1);
+                self.push(&"\t".repeat(level));
+                self.tabs += level as i64;
+                if let Some(list) = self.list.as_mut() {
+                    list.nesting_tabs += level as i64;
+                }
+            }
+        }
+        self.index
+    }
+
+    fn close_line(&mut self) {
+        if self.line_open {
+            self.push("\n");
+            self.line_open = false;
+        }
+    }
+
+    fn push_inline(&mut self, text: &str) {
+        self.open_line();
+        self.push(text);
+    }
+
+    fn push(&mut self, text: &str) {
+        self.parsed.text.push_str(text);
+        self.index += utf16_len(text);
+    }
+}
+
+fn is_inline(tag: &Tag) -> bool {
+    matches!(
+        tag,
+        Tag::Emphasis
+            | Tag::Strong
+            | Tag::Strikethrough
+            | Tag::Superscript
+            | Tag::Subscript
+            | Tag::Link { .. }
+            | Tag::Image { .. }
+    )
+}
+
+fn is_inline_end(tag: &TagEnd) -> bool {
+    matches!(
+        tag,
+        TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link
+            | TagEnd::Image
+    )
+}
+
+fn heading_format(level: HeadingLevel) -> FormatType {
+    match level {
+        HeadingLevel::H1 => FormatType::Heading1,
+        HeadingLevel::H2 => FormatType::Heading2,
+        HeadingLevel::H3 => FormatType::Heading3,
+        HeadingLevel::H4 => FormatType::Heading4,
+        HeadingLevel::H5 => FormatType::Heading5,
+        HeadingLevel::H6 => FormatType::Heading6,
+    }
+}
+
+/// Every request that styles `parsed` once its text is in place at `index`:
+/// formats and links first, then the lists and images back to front, since
+/// removing nesting tabs and inserting images shift what follows them.
+/// Tables are inserted separately.
+pub fn build_style_requests(parsed: &ParsedMarkdown, index: i64) -> Vec<Request> {
+    let offset = index - 1;
+    let formats = parsed.formats.iter().rev().map(|fmt| {
+        build_format_request(&FormatInfo {
+            start: fmt.start + offset,
+            end: fmt.end + offset,
+            ..fmt.clone()
+        })
+    });
+    let links = parsed.links.iter().map(|link| {
+        build_link_request(&LinkInfo {
+            start: link.start + offset,
+            end: link.end + offset,
+            ..link.clone()
+        })
+    });
+    let lists = parsed.lists.iter().rev().map(|list| {
+        build_list_request(&ListInfo {
+            start: list.start + offset,
+            end: list.end + offset,
+            ..list.clone()
+        })
+    });
+    let images = parsed.images.iter().rev().map(|image| {
+        build_image_request(&ImageInfo {
+            index: image.index + offset,
+            ..image.clone()
+        })
+    });
+    formats.chain(links).chain(lists).chain(images).collect()
+}
+
+/// The `updateTextStyle` request that sets `link.url` on its range.
+pub fn build_link_request(link: &LinkInfo) -> Request {
+    Request::update_text_style(
+        Range::new(link.start, link.end),
+        TextStyle {
+            link: Some(Link {
+                url: Some(link.url.clone()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        &["link"],
+    )
+}
+
+/// The `insertInlineImage` request for `image`.
+pub fn build_image_request(image: &ImageInfo) -> Request {
+    Request::InsertInlineImage(InsertInlineImageRequest {
+        uri: image.url.clone(),
+        location: Location::at(image.index),
+        object_size: None,
+    })
+}
+
+/// The `createParagraphBullets` request for `list`.
+pub fn build_list_request(list: &ListInfo) -> Request {
+    Request::create_paragraph_bullets(Range::new(list.start, list.end), list.preset)
+}
+
+/// The `updateParagraphStyle` or `updateTextStyle` request for `fmt`.
+pub fn build_format_request(fmt: &FormatInfo) -> Request {
+    let range = Range::new(fmt.start, fmt.end);
+    let heading = |level| {
+        Request::update_paragraph_style(
+            range.clone(),
+            ParagraphStyle {
+                named_style_type: Some(NamedStyleType::heading(level)),
+                ..Default::default()
+            },
+            &["namedStyleType"],
+        )
+    };
+    let monospace = || WeightedFontFamily {
+        font_family: CODE_FONT.to_string(),
+        weight: None,
+    };
+
+    match fmt.format_type {
+        FormatType::Heading1 => heading(1),
+        FormatType::Heading2 => heading(2),
+        FormatType::Heading3 => heading(3),
+        FormatType::Heading4 => heading(4),
+        FormatType::Heading5 => heading(5),
+        FormatType::Heading6 => heading(6),
+        FormatType::Bold => Request::update_text_style(
+            range,
+            TextStyle {
+                bold: Some(true),
+                ..Default::default()
+            },
+            &["bold"],
+        ),
+        FormatType::Italic => Request::update_text_style(
+            range,
+            TextStyle {
+                italic: Some(true),
+                ..Default::default()
+            },
+            &["italic"],
+        ),
+        FormatType::Strikethrough => Request::update_text_style(
+            range,
+            TextStyle {
+                strikethrough: Some(true),
+                ..Default::default()
+            },
+            &["strikethrough"],
+        ),
+        FormatType::Code => Request::update_text_style(
+            range,
+            TextStyle {
+                weighted_font_family: Some(monospace()),
+                background_color: Some(OptionalColor::rgb(0.95, 0.95, 0.95)),
+                ..Default::default()
+            },
+            &["weightedFontFamily", "backgroundColor"],
+        ),
+        FormatType::CodeBlock => Request::update_text_style(
+            range,
+            TextStyle {
+                weighted_font_family: Some(monospace()),
+                ..Default::default()
+            },
+            &["weightedFontFamily"],
+        ),
+        FormatType::Blockquote => Request::update_paragraph_style(
+            range,
+            ParagraphStyle {
+                indent_start: Some(Dimension::points(BLOCKQUOTE_INDENT)),
+                indent_first_line: Some(Dimension::points(BLOCKQUOTE_INDENT)),
+                ..Default::default()
+            },
+            &["indentStart", "indentFirstLine"],
+        ),
+    }
+}
+
+/// Length of `text` in UTF-16 code units, the unit Docs API indices count
+/// in: characters outside the Basic Multilingual Plane (most emoji) take two.
+pub fn utf16_len(text: &str) -> i64 {
+    text.encode_utf16().count() as i64
+}
@@ -0,0 +1,454 @@
+use super::markdown::parse_markdown;
+use super::{
+    AnchorInsertion, DocsError, batch_update, document_matches, extract_text_content, get_document,
+    markdown_requests,
+};
+use crate::drive;
+use crate::google_api::{GoogleApiError, GoogleClient};
+use crate::models::docs::{
+    Document, ReplaceAllTextRequest, Request, SubstringMatchCriteria, TableCell,
+};
+use regex::{Captures, Regex};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::LazyLock;
+
+/// `{{name}}`, `{{ customer.name }}` and so on. Tags starting with `#` or `/`
+/// are block tags, not placeholders.
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*([^{}#/\s][^{}]*?)\s*\}\}").expect("placeholder pattern is valid")
+});
+static EACH_OPEN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*#each\s+([^{}]+?)\s*\}\}").expect("each pattern is valid")
+});
+static EACH_CLOSE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*/each\s*\}\}").expect("each pattern is valid"));
+
+/// A copy of a template filled in by [`render_template`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedDocument {
+    pub document_id: String,
+    pub title: Option<String>,
+    pub web_view_link: Option<String>,
+    /// Placeholder occurrences replaced outside `{{#each}}` rows, Markdown
+    /// fields included.
+    pub replacements: i64,
+    /// Table rows written from `{{#each}}` items.
+    pub rows_rendered: usize,
+    /// Placeholders left in the document because the record has no text or
+    /// Markdown value for them.
+    pub unresolved: Vec<String>,
+}
+
+/// What a placeholder is replaced with.
+enum Filling<'a> {
+    Text(String),
+    Markdown(&'a str),
+}
+
+/// Copies the document `template_id` (into `folder_id` when given) and fills
+/// in the copy from `record`:
+///
+/// - `{{key}}` takes the value of `key`, or of a nested field for a dotted
+///   path like `{{customer.name}}`. Strings go in as they are, numbers and
+///   booleans as JSON writes them and `null` as nothing.
+/// - A value written `{"markdown": "..."}` is inserted as formatted Markdown,
+///   like `insert-from-markdown` (tables are not supported).
+/// - A table row with `{{#each key}}` in one of its cells is repeated for
+///   every item of the array `key`, with `{{field}}` taken from the item
+///   (`{{this}}` for the item itself) and falling back to the record. The
+///   row is removed when the array is empty or missing. `{{/each}}` may
+///   close the block and is dropped.
+///
+/// `title`, if given, names the copy and may use placeholders too; Drive's
+/// default "Copy of ..." name is kept otherwise.
+pub fn render_template(
+    client: &GoogleClient,
+    template_id: &str,
+    title: Option<&str>,
+    record: &Map<String, Value>,
+    folder_id: Option<&str>,
+) -> Result<RenderedDocument, DocsError> {
+    let title = title.map(|title| fill(title, |key| lookup(record, key)));
+    let copy = drive::copy_file(client, template_id, title.as_deref(), folder_id)?;
+    let document_id = copy.id.ok_or_else(|| {
+        GoogleApiError::Parse("Failed to parse id from copy response".to_string())
+    })?;
+
+    let rows_rendered = expand_each_rows(client, &document_id, record)?;
+
+    let document = get_document(client, &document_id)?;
+    let mut texts: Vec<(String, String)> = Vec::new();
+    let mut markdown = Vec::new();
+    let mut unresolved = Vec::new();
+    for found in document_matches(&document, &PLACEHOLDER) {
+        let key = PLACEHOLDER
+            .captures(&found.text)
+            .map(|caps| caps[1].to_string())
+            .unwrap_or_default();
+        match lookup(record, &key).and_then(filling) {
+            Some(Filling::Text(text)) => {
+                if !texts
+                    .iter()
+                    .any(|(placeholder, _)| *placeholder == found.text)
+                {
+                    texts.push((found.text, text));
+                }
+            }
+            Some(Filling::Markdown(source)) => {
+                markdown.push((found.start_index, found.end_index, source));
+            }
+            None => {
+                if !unresolved.contains(&found.text) {
+                    unresolved.push(found.text);
+                }
+            }
+        }
+    }
+
+    // Everything goes in one batch, so a failure leaves no placeholder half
+    // replaced. Markdown fields go back to front, so the positions found above
+    // stay valid for each one in turn.
+    let mut requests = Vec::new();
+    for &(start, end, source) in markdown.iter().rev() {
+        let mut parsed = parse_markdown(source);
+        // The placeholder's paragraph already ends in a newline.
+        if parsed.text.ends_with('\n') {
+            parsed.text.pop();
+        }
+        let at = AnchorInsertion {
+            index: start,
+            delete: Some(Request::delete_content_range(start, end)),
+            new_paragraph: false,
+            revision_id: None,
+        };
+        requests.extend(markdown_requests(&parsed, &at, false).0);
+    }
+    requests.extend(texts.into_iter().map(|(placeholder, text)| {
+        Request::ReplaceAllText(ReplaceAllTextRequest {
+            contains_text: SubstringMatchCriteria {
+                text: placeholder,
+                match_case: true,
+            },
+            replace_text: text,
+        })
+    }));
+
+    let mut replacements = markdown.len() as i64;
+    if !requests.is_empty() {
+        let result = batch_update(
+            client,
+            &document_id,
+            requests,
+            document.revision_id.as_deref(),
+        )?;
+        replacements += result
+            .replies
+            .iter()
+            .filter_map(|reply| reply.replace_all_text.as_ref())
+            .map(|replaced| replaced.occurrences_changed)
+            .sum::<i64>();
+    }
+
+    Ok(RenderedDocument {
+        document_id,
+        title: copy.name,
+        web_view_link: copy.web_view_link,
+        replacements,
+        rows_rendered,
+        unresolved,
+    })
+}
+
+/// Turns sheet rows into records: the first row names the fields and every
+/// later row that isn't blank becomes one record. Columns without a header
+/// are skipped and missing cells are empty strings.
+pub fn records_from_rows(rows: &[Vec<Value>]) -> Vec<Map<String, Value>> {
+    let Some((header, rows)) = rows.split_first() else {
+        return Vec::new();
+    };
+    let names: Vec<String> = header.iter().map(cell_string).collect();
+
+    rows.iter()
+        .filter(|row| row.iter().any(|cell| !cell_string(cell).is_empty()))
+        .map(|row| {
+            names
+                .iter()
+                .enumerate()
+                .filter(|(_, name)| !name.is_empty())
+                .map(|(column, name)| {
+                    let value = row
+                        .get(column)
+                        .filter(|cell| !cell.is_null())
+                        .cloned()
+                        .unwrap_or_else(|| Value::String(String::new()));
+                    (name.clone(), value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn cell_string(cell: &Value) -> String {
+    match cell {
+        Value::String(text) => text.trim().to_string(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// A table row holding an `{{#each key}}` tag.
+struct EachRow {
+    /// Position of the table in the body's content.
+    element: usize,
+    table_start: i64,
+    row_index: usize,
+    key: String,
+}
+
+/// Repeats the `{{#each}}` rows of the document for their items and fills
+/// them in, returning the number of rows written. Items' fields are
+/// substituted here; other placeholders are left for the record.
+fn expand_each_rows(
+    client: &GoogleClient,
+    document_id: &str,
+    record: &Map<String, Value>,
+) -> Result<usize, DocsError> {
+    let items = |key: &str| -> &[Value] {
+        lookup(record, key)
+            .and_then(Value::as_array)
+            .map_or(&[], Vec::as_slice)
+    };
+
+    let document = get_document(client, document_id)?;
+    let rows = each_rows(&document);
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    // Later rows first, so the tables and rows before them keep their
+    // indices. New rows are empty and filled in below.
+    let structure: Vec<Request> = rows
+        .iter()
+        .rev()
+        .flat_map(|row| {
+            let table_start = row.table_start;
+            let row_index = row.row_index as i64;
+            match items(&row.key).len() {
+                0 => vec![Request::delete_table_row(table_start, row_index)],
+                count => (1..count)
+                    .map(|_| Request::insert_table_row_below(table_start, row_index))
+                    .collect(),
+            }
+        })
+        .collect();
+    if !structure.is_empty() {
+        batch_update(
+            client,
+            document_id,
+            structure,
+            document.revision_id.as_deref(),
+        )?;
+    }
+
+    let document = get_document(client, document_id)?;
+    let mut requests = Vec::new();
+    let mut written = 0;
+    for row in each_rows(&document).iter().rev() {
+        let Some(table) = &document.body.content[row.element].table else {
+            continue;
+        };
+        let template: Vec<String> = table.table_rows[row.row_index]
+            .table_cells
+            .iter()
+            .map(|cell| {
+                let text = extract_text_content(&cell.content);
+                let text = EACH_OPEN.replace_all(&text, "");
+                EACH_CLOSE.replace_all(&text, "").into_owned()
+            })
+            .collect();
+
+        for (offset, item) in items(&row.key).iter().enumerate().rev() {
+            let Some(target) = table.table_rows.get(row.row_index + offset) else {
+                continue;
+            };
+            for (text, cell) in template.iter().zip(&target.table_cells).rev() {
+                let Some((start, end)) = cell_range(cell) else {
+                    continue;
+                };
+                // The cell's last newline stays.
+                if start < end - 1 {
+                    requests.push(Request::delete_content_range(start, end - 1));
+                }
+                let text = fill(text, |key| item_value(item, key));
+                let text = text.trim_end_matches('\n');
+                if !text.is_empty() {
+                    requests.push(Request::insert_text(text, start));
+                }
+            }
+            written += 1;
+        }
+    }
+    if !requests.is_empty() {
+        batch_update(
+            client,
+            document_id,
+            requests,
+            document.revision_id.as_deref(),
+        )?;
+    }
+
+    Ok(written)
+}
+
+/// The `{{#each}}` rows of the body's tables, in document order.
+fn each_rows(document: &Document) -> Vec<EachRow> {
+    let mut rows = Vec::new();
+    for (element, structural) in document.body.content.iter().enumerate() {
+        let (Some(table), Some(table_start)) = (&structural.table, structural.start_index) else {
+            continue;
+        };
+        for (row_index, row) in table.table_rows.iter().enumerate() {
+            let key = row.table_cells.iter().find_map(|cell| {
+                let text = extract_text_content(&cell.content);
+                EACH_OPEN.captures(&text).map(|caps| caps[1].to_string())
+            });
+            if let Some(key) = key {
+                rows.push(EachRow {
+                    element,
+                    table_start,
+                    row_index,
+                    key,
+                });
+            }
+        }
+    }
+    rows
+}
+
+/// Start and end index of a cell's content.
+fn cell_range(cell: &TableCell) -> Option<(i64, i64)> {
+    Some((
+        cell.content.first()?.start_index?,
+        cell.content.last()?.end_index?,
+    ))
+}
+
+/// Substitutes the placeholders in `text` that `value` has a text or Markdown
+/// value for (Markdown as its source) and leaves the others.
+fn fill<'a>(text: &str, value: impl Fn(&str) -> Option<&'a Value>) -> String {
+    PLACEHOLDER
+        .replace_all(text, |caps: &Captures| {
+            match value(&caps[1]).and_then(filling) {
+                Some(Filling::Text(text)) => text,
+                Some(Filling::Markdown(source)) => source.to_string(),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+fn filling(value: &Value) -> Option<Filling<'_>> {
+    match value {
+        Value::String(text) => Some(Filling::Text(text.clone())),
+        Value::Number(number) => Some(Filling::Text(number.to_string())),
+        Value::Bool(flag) => Some(Filling::Text(flag.to_string())),
+        Value::Null => Some(Filling::Text(String::new())),
+        Value::Object(object) => object
+            .get("markdown")
+            .and_then(Value::as_str)
+            .map(Filling::Markdown),
+        Value::Array(_) => None,
+    }
+}
+
+/// The value at `key`, trying it as a field name first and then as a dotted
+/// path.
+fn lookup<'a>(record: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    record.get(key).or_else(|| {
+        let mut path = key.split('.');
+        let first = record.get(path.next()?)?;
+        path.try_fold(first, |value, field| value.get(field))
+    })
+}
+
+/// The value of `key` inside an `{{#each}}` item.
+fn item_value<'a>(item: &'a Value, key: &str) -> Option<&'a Value> {
+    match item {
+        _ if key == "this" => Some(item),
+        Value::Object(fields) => lookup(fields, key),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().expect("record is an object")
+    }
+
+    #[test]
+    fn lookup_prefers_the_field_name_over_a_path() {
+        let record = record(json!({
+            "customer": {"name": "Ada", "address": {"city": "London"}},
+            "customer.name": "Flat key"
+        }));
+        assert_eq!(lookup(&record, "customer.name"), Some(&json!("Flat key")));
+        assert_eq!(
+            lookup(&record, "customer.address.city"),
+            Some(&json!("London"))
+        );
+        assert_eq!(lookup(&record, "customer.phone"), None);
+        assert_eq!(lookup(&record, "missing"), None);
+    }
+
+    #[test]
+    fn fill_writes_scalars_and_keeps_unknown_placeholders() {
+        let record = record(json!({
+            "name": "Ada",
+            "due": 30,
+            "paid": false,
+            "note": null,
+            "items": [1, 2],
+            "body": {"markdown": "**Hi**"}
+        }));
+        let filled = fill(
+            "{{name}}/{{ due }}/{{paid}}/{{note}}/{{items}}/{{body}}/{{other}}/{{#each items}}",
+            |key| lookup(&record, key),
+        );
+        assert_eq!(
+            filled,
+            "Ada/30/false//{{items}}/**Hi**/{{other}}/{{#each items}}"
+        );
+    }
+
+    #[test]
+    fn each_items_read_their_own_fields_or_themselves() {
+        let item = json!({"name": "Widget", "price": {"net": 5}});
+        assert_eq!(item_value(&item, "price.net"), Some(&json!(5)));
+        assert_eq!(item_value(&item, "this"), Some(&item));
+        assert_eq!(item_value(&json!("plain"), "name"), None);
+    }
+
+    #[test]
+    fn rows_become_records_named_by_the_header() {
+        let rows = vec![
+            vec![json!("name"), json!(""), json!(" due ")],
+            vec![json!("Ada"), json!("skipped"), json!(30)],
+            vec![json!(""), json!(null)],
+            vec![json!("Grace")],
+        ];
+        assert_eq!(
+            records_from_rows(&rows),
+            [
+                record(json!({"name": "Ada", "due": 30})),
+                record(json!({"name": "Grace", "due": ""})),
+            ]
+        );
+        assert!(records_from_rows(&[]).is_empty());
+        assert!(records_from_rows(&rows[..1]).is_empty());
+    }
+}
@@ -0,0 +1,480 @@
+use super::markdown::utf16_len;
+use super::{DocsError, batch_update, document_at};
+use crate::google_api::GoogleClient;
+use crate::models::docs::{
+    InsertTableRequest, Location, Range, ReplaceAllTextRequest, Request, SubstringMatchCriteria,
+    TextStyle,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One step of an [`apply_edits`] transaction, written in JSON as
+/// `{"op": "insert", "text": "Hi", "index": 1}` and so on. Indices refer to
+/// the document as it was before the transaction; each is moved past the
+/// text that earlier steps insert or delete.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Edit {
+    /// Inserts `text` at `index`, or at the end of the body.
+    Insert {
+        text: String,
+        index: Option<i64>,
+    },
+    /// Sets or clears bold, italic and underline on a range.
+    Format {
+        start_index: i64,
+        end_index: i64,
+        bold: Option<bool>,
+        italic: Option<bool>,
+        underline: Option<bool>,
+    },
+    Delete {
+        start_index: i64,
+        end_index: i64,
+    },
+    /// Inserts a table at `index`, or at the end of the body, filled from
+    /// `data` (row-major; extra cells are ignored).
+    InsertTable {
+        rows: i64,
+        cols: i64,
+        index: Option<i64>,
+        #[serde(default)]
+        data: Vec<Vec<String>>,
+    },
+    /// Replaces every occurrence of `find`. Text moves by an amount only
+    /// Google knows, so no step with indices may follow.
+    Replace {
+        find: String,
+        replace: String,
+        #[serde(default)]
+        match_case: bool,
+    },
+}
+
+impl Edit {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Insert { .. } => "insert",
+            Self::Format { .. } => "format",
+            Self::Delete { .. } => "delete",
+            Self::InsertTable { .. } => "insert_table",
+            Self::Replace { .. } => "replace",
+        }
+    }
+
+    fn uses_indices(&self) -> bool {
+        !matches!(self, Self::Replace { .. })
+    }
+
+    fn appends(&self) -> bool {
+        matches!(
+            self,
+            Self::Insert { index: None, .. } | Self::InsertTable { index: None, .. }
+        )
+    }
+}
+
+/// Outcome of [`apply_edits`].
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedEdits {
+    pub document_id: String,
+    /// Number of `batchUpdate` requests the edits were sent as.
+    pub requests: usize,
+    /// One entry per edit, in order.
+    pub edits: Vec<AppliedEdit>,
+    pub revision_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedEdit {
+    pub op: &'static str,
+    /// Where the edit's content is in the updated document: what was
+    /// inserted or formatted, or the empty range left by a delete. Not set
+    /// for `replace`, and not moved for the text replaces change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_index: Option<i64>,
+    /// For `replace`, how many occurrences were changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occurrences: Option<i64>,
+}
+
+/// How an edit moved the text after it.
+#[derive(Debug, Clone, Copy)]
+enum Shift {
+    Inserted { index: i64, length: i64 },
+    Deleted { start: i64, end: i64 },
+}
+
+impl Shift {
+    /// Where `index` is after the shift. Text inserted exactly at `index`
+    /// goes in front of it, unless `index` is the (exclusive) end of a range.
+    fn map(self, index: i64, range_end: bool) -> i64 {
+        match self {
+            Self::Inserted { index: at, length } if index > at || (index == at && !range_end) => {
+                index + length
+            }
+            Self::Deleted { start, end } if index >= end => index - (end - start),
+            Self::Deleted { start, .. } if index > start => start,
+            _ => index,
+        }
+    }
+}
+
+/// Where an edit went, once its own requests have run.
+struct Placed {
+    /// The range it covers; `None` for replace.
+    covered: Option<(i64, i64)>,
+    /// How many shifts there were by then.
+    shifted: usize,
+    /// Index of its reply, for replace.
+    reply: Option<usize>,
+}
+
+fn rebase(shifts: &[Shift], index: i64, range_end: bool) -> i64 {
+    shifts
+        .iter()
+        .fold(index, |index, shift| shift.map(index, range_end))
+}
+
+/// Checks that `edits` can be rebased: there is at least one, ranges are
+/// not empty or reversed, tables have cells and no step with indices comes
+/// after a `replace`.
+pub fn validate_edits(edits: &[Edit]) -> Result<(), String> {
+    if edits.is_empty() {
+        return Err("At least one op is required".to_string());
+    }
+    let mut replaced = false;
+    for (i, edit) in edits.iter().enumerate() {
+        if replaced && edit.uses_indices() {
+            return Err(format!(
+                "ops[{i}] ({}) comes after a replace, which moves text by an unknown amount; put replace ops last",
+                edit.name()
+            ));
+        }
+        match *edit {
+            Edit::Insert {
+                index: Some(index), ..
+            }
+            | Edit::InsertTable {
+                index: Some(index), ..
+            } if index < 1 => {
+                return Err(format!("ops[{i}]: index must be at least 1"));
+            }
+            Edit::Format {
+                start_index,
+                end_index,
+                ..
+            }
+            | Edit::Delete {
+                start_index,
+                end_index,
+            } if start_index < 1 || end_index <= start_index => {
+                return Err(format!(
+                    "ops[{i}]: invalid range {start_index}..{end_index}"
+                ));
+            }
+            Edit::InsertTable { rows, cols, .. } if rows < 1 || cols < 1 => {
+                return Err(format!(
+                    "ops[{i}]: a table needs at least one row and column"
+                ));
+            }
+            Edit::Replace { ref find, .. } if find.is_empty() => {
+                return Err(format!("ops[{i}]: find must not be empty"));
+            }
+            Edit::Replace { .. } => replaced = true,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Applies `edits` to the document as a single `batchUpdate`, so either all
+/// of them land or none do. Each edit's indices are rebased past the edits
+/// before it, as they would be if it ran on its own right after them. With
+/// `required_revision_id`, the document is read to check it is still at that
+/// revision, even when no edit ends up sending a request, and the batch is
+/// written at it. An edit without an index makes the end of the body be read
+/// too.
+pub fn apply_edits(
+    client: &GoogleClient,
+    document_id: &str,
+    edits: &[Edit],
+    required_revision_id: Option<&str>,
+) -> Result<AppliedEdits, DocsError> {
+    validate_edits(edits).map_err(DocsError::InvalidEdit)?;
+
+    let (end_of_body, revision_id) =
+        if required_revision_id.is_some() || edits.iter().any(Edit::appends) {
+            let document = document_at(client, document_id, required_revision_id)?;
+            (document.end_index().unwrap_or(1) - 1, document.revision_id)
+        } else {
+            (1, required_revision_id.map(ToString::to_string))
+        };
+
+    let mut requests = Vec::new();
+    let mut shifts = Vec::new();
+    let mut placed = Vec::new();
+
+    for edit in edits {
+        let position = |index: Option<i64>| rebase(&shifts, index.unwrap_or(end_of_body), false);
+        let range =
+            |start: i64, end: i64| (rebase(&shifts, start, false), rebase(&shifts, end, true));
+
+        let (covered, reply) = match edit {
+            Edit::Insert { text, index } => {
+                let index = position(*index);
+                let length = utf16_len(text);
+                if length > 0 {
+                    requests.push(Request::insert_text(text.as_str(), index));
+                    shifts.push(Shift::Inserted { index, length });
+                }
+                (Some((index, index + length)), None)
+            }
+            Edit::Format {
+                start_index,
+                end_index,
+                bold,
+                italic,
+                underline,
+            } => {
+                let (start, end) = range(*start_index, *end_index);
+                let style = TextStyle {
+                    bold: *bold,
+                    italic: *italic,
+                    underline: *underline,
+                    ..Default::default()
+                };
+                let fields: Vec<String> = match serde_json::to_value(&style) {
+                    Ok(Value::Object(fields)) => fields.keys().cloned().collect(),
+                    _ => Vec::new(),
+                };
+                if start < end && !fields.is_empty() {
+                    let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+                    requests.push(Request::update_text_style(
+                        Range::new(start, end),
+                        style,
+                        &fields,
+                    ));
+                }
+                (Some((start, end.max(start))), None)
+            }
+            Edit::Delete {
+                start_index,
+                end_index,
+            } => {
+                let (start, end) = range(*start_index, *end_index);
+                // Nothing is left if an earlier delete already covered it.
+                if start < end {
+                    requests.push(Request::delete_content_range(start, end));
+                    shifts.push(Shift::Deleted { start, end });
+                }
+                (Some((start, start)), None)
+            }
+            Edit::InsertTable {
+                rows,
+                cols,
+                index,
+                data,
+            } => {
+                let index = position(*index);
+                requests.push(Request::InsertTable(InsertTableRequest {
+                    rows: *rows,
+                    columns: *cols,
+                    location: Location::at(index),
+                }));
+                // A newline goes in before the table, then one index each
+                // for the table, every row and every cell, whose content
+                // starts as an empty paragraph.
+                let row_length = 1 + 2 * cols;
+                let mut length = 3 + rows * row_length;
+                let cells = data
+                    .iter()
+                    .take(*rows as usize)
+                    .enumerate()
+                    .flat_map(|(row, cells)| {
+                        cells
+                            .iter()
+                            .take(*cols as usize)
+                            .enumerate()
+                            .map(move |(col, text)| (row as i64, col as i64, text))
+                    })
+                    .filter(|(_, _, text)| !text.is_empty())
+                    .collect::<Vec<_>>();
+                // Last cell first, so the ones before keep their indices.
+                for &(row, col, text) in cells.iter().rev() {
+                    let cell_start = index + 4 + row * row_length + 2 * col;
+                    requests.push(Request::insert_text(text.as_str(), cell_start));
+                    length += utf16_len(text);
+                }
+                shifts.push(Shift::Inserted { index, length });
+                (Some((index, index + length)), None)
+            }
+            Edit::Replace {
+                find,
+                replace,
+                match_case,
+            } => {
+                requests.push(Request::ReplaceAllText(ReplaceAllTextRequest {
+                    contains_text: SubstringMatchCriteria {
+                        text: find.clone(),
+                        match_case: *match_case,
+                    },
+                    replace_text: replace.clone(),
+                }));
+                (None, Some(requests.len() - 1))
+            }
+        };
+        placed.push(Placed {
+            covered,
+            shifted: shifts.len(),
+            reply,
+        });
+    }
+
+    let request_count = requests.len();
+    // Empty inserts and fully overlapped deletes can leave nothing to send.
+    let (replies, revision_id) = if requests.is_empty() {
+        (Vec::new(), revision_id)
+    } else {
+        let result = batch_update(client, document_id, requests, revision_id.as_deref())?;
+        (result.replies, result.revision_id)
+    };
+
+    let applied = edits
+        .iter()
+        .zip(placed)
+        .map(|(edit, placed)| {
+            // Later edits may have moved this one's content too.
+            let later = &shifts[placed.shifted..];
+            let (start_index, end_index) = match placed.covered {
+                Some((start, end)) => (
+                    Some(rebase(later, start, false)),
+                    Some(rebase(later, end, true)),
+                ),
+                None => (None, None),
+            };
+            AppliedEdit {
+                op: edit.name(),
+                start_index,
+                end_index,
+                occurrences: placed.reply.and_then(|reply| {
+                    replies
+                        .get(reply)
+                        .and_then(|reply| reply.replace_all_text.as_ref())
+                        .map(|replaced| replaced.occurrences_changed)
+                }),
+            }
+        })
+        .collect();
+
+    Ok(AppliedEdits {
+        document_id: document_id.to_string(),
+        requests: request_count,
+        edits: applied,
+        revision_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edits(ops: Value) -> Vec<Edit> {
+        serde_json::from_value(ops).expect("valid ops")
+    }
+
+    #[test]
+    fn inserts_push_later_text_along() {
+        let shift = Shift::Inserted {
+            index: 5,
+            length: 3,
+        };
+        assert_eq!(shift.map(4, false), 4);
+        assert_eq!(shift.map(6, false), 9);
+        // Text inserted at an index goes in front of what starts there, but
+        // not past a range that ends there.
+        assert_eq!(shift.map(5, false), 8);
+        assert_eq!(shift.map(5, true), 5);
+    }
+
+    #[test]
+    fn deletes_pull_later_text_back() {
+        let shift = Shift::Deleted { start: 5, end: 8 };
+        assert_eq!(shift.map(4, false), 4);
+        assert_eq!(shift.map(5, false), 5);
+        assert_eq!(shift.map(8, true), 5);
+        assert_eq!(shift.map(10, false), 7);
+        // Indices inside the deleted text collapse onto its start.
+        assert_eq!(shift.map(6, false), 5);
+        assert_eq!(shift.map(7, true), 5);
+    }
+
+    #[test]
+    fn rebase_applies_shifts_in_order() {
+        let shifts = [
+            Shift::Inserted {
+                index: 1,
+                length: 4,
+            },
+            Shift::Deleted { start: 10, end: 12 },
+        ];
+        assert_eq!(rebase(&shifts, 3, false), 7);
+        assert_eq!(rebase(&shifts, 8, false), 10);
+        assert_eq!(rebase(&shifts, 20, false), 22);
+        assert_eq!(rebase(&[], 20, true), 20);
+    }
+
+    #[test]
+    fn valid_edits_pass() {
+        let ops = edits(json!([
+            {"op": "insert", "text": "Hi", "index": 1},
+            {"op": "format", "start_index": 1, "end_index": 3, "bold": true},
+            {"op": "insert_table", "rows": 1, "cols": 1},
+            {"op": "replace", "find": "a", "replace": "b"},
+            {"op": "replace", "find": "c", "replace": "d"}
+        ]));
+        assert_eq!(validate_edits(&ops), Ok(()));
+    }
+
+    #[test]
+    fn invalid_edits_name_the_op() {
+        let cases = [
+            (json!([]), "At least one op is required"),
+            (
+                json!([{"op": "insert", "text": "x", "index": 0}]),
+                "ops[0]: index must be at least 1",
+            ),
+            (
+                json!([
+                    {"op": "insert", "text": "x"},
+                    {"op": "delete", "start_index": 4, "end_index": 4}
+                ]),
+                "ops[1]: invalid range 4..4",
+            ),
+            (
+                json!([{"op": "insert_table", "rows": 0, "cols": 2}]),
+                "ops[0]: a table needs at least one row and column",
+            ),
+            (
+                json!([{"op": "replace", "find": "", "replace": "x"}]),
+                "ops[0]: find must not be empty",
+            ),
+        ];
+        for (ops, message) in cases {
+            assert_eq!(validate_edits(&edits(ops)), Err(message.to_string()));
+        }
+
+        let misordered = edits(json!([
+            {"op": "replace", "find": "a", "replace": "b"},
+            {"op": "format", "start_index": 1, "end_index": 2, "italic": true}
+        ]));
+        let err = validate_edits(&misordered).unwrap_err();
+        assert!(
+            err.starts_with("ops[1] (format) comes after a replace"),
+            "{err}"
+        );
+    }
+}
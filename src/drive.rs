@@ -0,0 +1,561 @@
+use crate::google_api::{GoogleApiError, GoogleClient, detect_drive_mime_type};
+use crate::models::drive::{self as model, FOLDER_MIME_TYPE, Permission, User};
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+const FILE_LIST_FIELDS: &str =
+    "nextPageToken,files(id,name,mimeType,webViewLink,parents,createdTime,modifiedTime,size)";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DriveError {
+    #[error(transparent)]
+    Api(#[from] GoogleApiError),
+    #[error("File not found: {}", .0.display())]
+    FileNotFound(PathBuf),
+}
+
+impl DriveError {
+    /// The `error_code` reported by the binaries for non-API failures.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::Api(_) => "API_ERROR",
+            Self::FileNotFound(_) => "FILE_NOT_FOUND",
+        }
+    }
+}
+
+/// Drive file metadata. Only the fields requested by the operation (and
+/// returned by Google) are set.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_view_link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_content_link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parents: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_time: Option<String>,
+    /// Size in bytes, as the decimal string Drive returns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starred: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trashed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owners: Option<Vec<Owner>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Vec<PermissionInfo>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Owner {
+    pub email: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionInfo {
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub permission_type: Option<String>,
+    pub role: Option<String>,
+    pub email: Option<String>,
+}
+
+/// One page of [`list_files`] or [`search_files`] results.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileList {
+    pub files: Vec<FileInfo>,
+    pub next_page_token: Option<String>,
+}
+
+/// Where [`download`] wrote a file. Google Docs, Sheets, Slides and Drawings
+/// cannot be downloaded as-is and are exported instead.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Download {
+    Media(DownloadedFile),
+    Export(ExportedFile),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadedFile {
+    pub file_id: String,
+    pub output_path: String,
+    pub name: Option<String>,
+    pub mime_type: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedFile {
+    pub file_id: String,
+    pub output_path: String,
+    pub export_mime_type: String,
+}
+
+/// Result of [`share_file`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Share {
+    pub permission: PermissionInfo,
+    pub web_view_link: Option<String>,
+    pub web_content_link: Option<String>,
+}
+
+/// Uploads the file at `file_path`, named after it unless `name` is given.
+/// The MIME type is guessed from the extension unless `mime_type` is given.
+pub fn upload(
+    client: &GoogleClient,
+    file_path: &Path,
+    folder_id: Option<&str>,
+    name: Option<&str>,
+    mime_type: Option<&str>,
+) -> Result<FileInfo, DriveError> {
+    require_file(file_path)?;
+
+    let file_name = name
+        .map(ToString::to_string)
+        .or_else(|| {
+            file_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(ToString::to_string)
+        })
+        .unwrap_or_else(|| "upload.bin".to_string());
+
+    let detected_mime = mime_type
+        .map(ToString::to_string)
+        .unwrap_or_else(|| detect_drive_mime_type(file_path).to_string());
+
+    let metadata = model::File {
+        name: Some(file_name.clone()),
+        parents: folder_id.map(|id| vec![id.to_string()]),
+        ..Default::default()
+    };
+
+    let query = vec![
+        ("uploadType".to_string(), "multipart".to_string()),
+        (
+            "fields".to_string(),
+            "id,name,mimeType,webViewLink,webContentLink,parents,createdTime,modifiedTime,size"
+                .to_string(),
+        ),
+    ];
+
+    let result = client.post_multipart(
+        &client.endpoints().drive_upload_url("files"),
+        &query,
+        &json!(metadata),
+        file_path,
+        &detected_mime,
+        &file_name,
+    )?;
+
+    Ok(parse_file(result)?.into())
+}
+
+/// Downloads a file's content to `output_path`, exporting Google-native
+/// formats with their default export type.
+pub fn download(
+    client: &GoogleClient,
+    file_id: &str,
+    output_path: &Path,
+) -> Result<Download, GoogleApiError> {
+    let metadata: model::File = client.get(
+        &client.endpoints().drive_url(&format!("files/{file_id}")),
+        &[("fields".to_string(), "id,name,mimeType".to_string())],
+    )?;
+
+    let mime_type = metadata.mime_type.clone().unwrap_or_default();
+
+    if metadata.is_google_native() {
+        return export_google_doc(client, file_id, output_path, &mime_type, None)
+            .map(Download::Export);
+    }
+
+    client.get_bytes_to_path(
+        &client.endpoints().drive_url(&format!("files/{file_id}")),
+        &[("alt".to_string(), "media".to_string())],
+        output_path,
+    )?;
+
+    Ok(Download::Media(DownloadedFile {
+        file_id: file_id.to_string(),
+        output_path: output_path.display().to_string(),
+        name: metadata.name,
+        mime_type,
+    }))
+}
+
+/// Exports a Google-native file (`source_mime`) to `output_path` as
+/// `export_mime`, defaulting to PDF for documents and slides, CSV for
+/// spreadsheets and PNG for drawings.
+pub fn export_google_doc(
+    client: &GoogleClient,
+    file_id: &str,
+    output_path: &Path,
+    source_mime: &str,
+    export_mime: Option<&str>,
+) -> Result<ExportedFile, GoogleApiError> {
+    let selected_export =
+        export_mime
+            .map(ToString::to_string)
+            .unwrap_or_else(|| match source_mime {
+                "application/vnd.google-apps.document" => "application/pdf".to_string(),
+                "application/vnd.google-apps.spreadsheet" => "text/csv".to_string(),
+                "application/vnd.google-apps.presentation" => "application/pdf".to_string(),
+                "application/vnd.google-apps.drawing" => "image/png".to_string(),
+                _ => "application/pdf".to_string(),
+            });
+
+    client.get_bytes_to_path(
+        &client
+            .endpoints()
+            .drive_url(&format!("files/{file_id}/export")),
+        &[("mimeType".to_string(), selected_export.clone())],
+        output_path,
+    )?;
+
+    Ok(ExportedFile {
+        file_id: file_id.to_string(),
+        output_path: output_path.display().to_string(),
+        export_mime_type: selected_export,
+    })
+}
+
+/// Lists files that are not in the trash, optionally only those in `folder_id`.
+pub fn list_files(
+    client: &GoogleClient,
+    folder_id: Option<&str>,
+    max_results: i64,
+    page_token: Option<&str>,
+) -> Result<FileList, GoogleApiError> {
+    let mut query_parts = vec!["trashed = false".to_string()];
+    if let Some(folder_id) = folder_id {
+        query_parts.push(format!("'{folder_id}' in parents"));
+    }
+
+    query_file_list(client, query_parts.join(" and "), max_results, page_token)
+}
+
+/// Searches with Drive query syntax, excluding trashed files unless the query
+/// mentions `trashed` itself.
+pub fn search_files(
+    client: &GoogleClient,
+    query: &str,
+    max_results: i64,
+    page_token: Option<&str>,
+) -> Result<FileList, GoogleApiError> {
+    let full_query = if query.contains("trashed") {
+        query.to_string()
+    } else {
+        format!("{query} and trashed = false")
+    };
+
+    query_file_list(client, full_query, max_results, page_token)
+}
+
+fn query_file_list(
+    client: &GoogleClient,
+    query: String,
+    max_results: i64,
+    page_token: Option<&str>,
+) -> Result<FileList, GoogleApiError> {
+    let mut params = vec![
+        ("q".to_string(), query),
+        ("pageSize".to_string(), max_results.to_string()),
+        ("fields".to_string(), FILE_LIST_FIELDS.to_string()),
+    ];
+    if let Some(token) = page_token {
+        params.push(("pageToken".to_string(), token.to_string()));
+    }
+
+    let result: model::FileList = client.get(&client.endpoints().drive_url("files"), &params)?;
+
+    Ok(FileList {
+        files: result.files.into_iter().map(FileInfo::from).collect(),
+        next_page_token: result.next_page_token,
+    })
+}
+
+/// Full metadata for one file, including owners and permissions.
+pub fn get_metadata(client: &GoogleClient, file_id: &str) -> Result<FileInfo, GoogleApiError> {
+    let file: model::File = client.get(
+        &client.endpoints().drive_url(&format!("files/{file_id}")),
+        &[ (
+            "fields".to_string(),
+            "id,name,mimeType,webViewLink,webContentLink,parents,createdTime,modifiedTime,size,description,starred,trashed,owners,permissions".to_string(),
+        )],
+    )?;
+
+    let owners = file.owners.clone().unwrap_or_default();
+    let permissions = file.permissions.clone().unwrap_or_default();
+
+    Ok(FileInfo {
+        owners: Some(owners.into_iter().map(Owner::from).collect()),
+        permissions: Some(permissions.into_iter().map(PermissionInfo::from).collect()),
+        ..file.into()
+    })
+}
+
+/// Creates a folder, inside `parent_id` when given.
+pub fn create_folder(
+    client: &GoogleClient,
+    name: &str,
+    parent_id: Option<&str>,
+) -> Result<FileInfo, GoogleApiError> {
+    let metadata = model::File {
+        name: Some(name.to_string()),
+        mime_type: Some(FOLDER_MIME_TYPE.to_string()),
+        parents: parent_id.map(|id| vec![id.to_string()]),
+        ..Default::default()
+    };
+
+    let result: model::File = client.post(
+        &client.endpoints().drive_url("files"),
+        &[(
+            "fields".to_string(),
+            "id,name,mimeType,webViewLink,parents,createdTime".to_string(),
+        )],
+        &metadata,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Moves a file into `folder_id`, removing it from its current parents.
+pub fn move_file(
+    client: &GoogleClient,
+    file_id: &str,
+    folder_id: &str,
+) -> Result<FileInfo, GoogleApiError> {
+    let file: model::File = client.get(
+        &client.endpoints().drive_url(&format!("files/{file_id}")),
+        &[("fields".to_string(), "parents".to_string())],
+    )?;
+
+    let previous_parents = file.parents.unwrap_or_default().join(",");
+
+    let query = vec![
+        ("addParents".to_string(), folder_id.to_string()),
+        ("removeParents".to_string(), previous_parents),
+        (
+            "fields".to_string(),
+            "id,name,parents,webViewLink".to_string(),
+        ),
+    ];
+
+    let result: model::File = client.patch(
+        &client.endpoints().drive_url(&format!("files/{file_id}")),
+        &query,
+        &model::File::default(),
+    )?;
+
+    Ok(result.into())
+}
+
+/// Grants `role` on a file. With an `email` the grant defaults to that user;
+/// without one it defaults to anyone with the link.
+pub fn share_file(
+    client: &GoogleClient,
+    file_id: &str,
+    email: Option<&str>,
+    role: &str,
+    permission_type: Option<&str>,
+) -> Result<Share, GoogleApiError> {
+    let perm_type = permission_type.unwrap_or(if email.is_some() { "user" } else { "anyone" });
+
+    let permission = Permission {
+        permission_type: Some(perm_type.to_string()),
+        role: Some(role.to_string()),
+        email_address: email
+            .filter(|_| perm_type == "user")
+            .map(ToString::to_string),
+        ..Default::default()
+    };
+
+    let created: Permission = client.post(
+        &client
+            .endpoints()
+            .drive_url(&format!("files/{file_id}/permissions")),
+        &[(
+            "fields".to_string(),
+            "id,type,role,emailAddress".to_string(),
+        )],
+        &permission,
+    )?;
+
+    let file: model::File = client.get(
+        &client.endpoints().drive_url(&format!("files/{file_id}")),
+        &[(
+            "fields".to_string(),
+            "webViewLink,webContentLink".to_string(),
+        )],
+    )?;
+
+    Ok(Share {
+        permission: created.into(),
+        web_view_link: file.web_view_link,
+        web_content_link: file.web_content_link,
+    })
+}
+
+/// Moves a file to the trash, or deletes it outright when `permanent`.
+pub fn delete_file(
+    client: &GoogleClient,
+    file_id: &str,
+    permanent: bool,
+) -> Result<(), GoogleApiError> {
+    if permanent {
+        client.delete_no_content(
+            &client.endpoints().drive_url(&format!("files/{file_id}")),
+            &[],
+        )?;
+    } else {
+        client.patch::<model::File>(
+            &client.endpoints().drive_url(&format!("files/{file_id}")),
+            &[],
+            &model::File {
+                trashed: Some(true),
+                ..Default::default()
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Copies a file, optionally renaming it or placing it in `folder_id`.
+pub fn copy_file(
+    client: &GoogleClient,
+    file_id: &str,
+    name: Option<&str>,
+    folder_id: Option<&str>,
+) -> Result<FileInfo, GoogleApiError> {
+    let metadata = model::File {
+        name: name.map(ToString::to_string),
+        parents: folder_id.map(|id| vec![id.to_string()]),
+        ..Default::default()
+    };
+
+    let result: model::File = client.post(
+        &client
+            .endpoints()
+            .drive_url(&format!("files/{file_id}/copy")),
+        &[(
+            "fields".to_string(),
+            "id,name,mimeType,webViewLink,parents,createdTime".to_string(),
+        )],
+        &metadata,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Replaces a file's content with the file at `file_path`, optionally
+/// renaming it.
+pub fn update_file(
+    client: &GoogleClient,
+    file_id: &str,
+    file_path: &Path,
+    name: Option<&str>,
+) -> Result<FileInfo, DriveError> {
+    require_file(file_path)?;
+
+    let metadata = model::File {
+        name: name.map(ToString::to_string),
+        ..Default::default()
+    };
+
+    let mime_type = detect_drive_mime_type(file_path).to_string();
+    let file_name = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("file.bin");
+
+    let query = vec![
+        ("uploadType".to_string(), "multipart".to_string()),
+        (
+            "fields".to_string(),
+            "id,name,mimeType,webViewLink,modifiedTime,size".to_string(),
+        ),
+    ];
+
+    let result = client.patch_multipart(
+        &client
+            .endpoints()
+            .drive_upload_url(&format!("files/{file_id}")),
+        &query,
+        &json!(metadata),
+        file_path,
+        &mime_type,
+        file_name,
+    )?;
+
+    Ok(parse_file(result)?.into())
+}
+
+fn require_file(path: &Path) -> Result<(), DriveError> {
+    if path.exists() {
+        Ok(())
+    } else {
+        Err(DriveError::FileNotFound(path.to_path_buf()))
+    }
+}
+
+fn parse_file(value: serde_json::Value) -> Result<model::File, GoogleApiError> {
+    serde_json::from_value(value).map_err(|e| GoogleApiError::Parse(e.to_string()))
+}
+
+impl From<model::File> for FileInfo {
+    fn from(file: model::File) -> Self {
+        Self {
+            id: file.id,
+            name: file.name,
+            mime_type: file.mime_type,
+            web_view_link: file.web_view_link,
+            web_content_link: file.web_content_link,
+            parents: file.parents,
+            created_time: file.created_time,
+            modified_time: file.modified_time,
+            size: file.size,
+            description: file.description,
+            starred: file.starred,
+            trashed: file.trashed,
+            owners: None,
+            permissions: None,
+        }
+    }
+}
+
+impl From<User> for Owner {
+    fn from(user: User) -> Self {
+        Self {
+            email: user.email_address,
+            name: user.display_name,
+        }
+    }
+}
+
+impl From<Permission> for PermissionInfo {
+    fn from(permission: Permission) -> Self {
+        Self {
+            id: permission.id,
+            permission_type: permission.permission_type,
+            role: permission.role,
+            email: permission.email_address,
+        }
+    }
+}
@@ -1,14 +1,99 @@
+use crate::config::Endpoints;
 use anyhow::{Context, Result, anyhow};
 use reqwest::Method;
-use reqwest::blocking::{Client, Response, multipart};
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, RequestBuilder, Response, multipart};
+use reqwest::header::RETRY_AFTER;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::Duration;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_INITIAL_BACKOFF_MS: u64 = 500;
+const DEFAULT_MAX_BACKOFF_MS: u64 = 32_000;
 
 #[derive(Debug, Clone)]
 pub struct GoogleClient {
     http: Client,
     access_token: String,
+    endpoints: Endpoints,
+    retry_policy: RetryPolicy,
+    retries: Arc<AtomicU32>,
+}
+
+/// Controls how transient failures (HTTP 429/5xx, rate-limit 403s and network
+/// errors) are retried. Delays use full-jitter exponential backoff unless the
+/// server sends a `Retry-After` header, which wins up to `max_backoff`.
+///
+/// A POST may already have been applied when it fails with a 5xx or the
+/// connection drops mid-request, so POSTs are only retried on those when sent
+/// with [`GoogleClient::post_idempotent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: Duration::from_millis(DEFAULT_INITIAL_BACKOFF_MS),
+            max_backoff: Duration::from_millis(DEFAULT_MAX_BACKOFF_MS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends every request exactly once.
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Reads `GOOGLE_SKILL_MAX_ATTEMPTS`, `GOOGLE_SKILL_RETRY_BASE_MS` and
+    /// `GOOGLE_SKILL_RETRY_MAX_MS`, falling back to the defaults for unset or
+    /// unparsable values.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env_u64 = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+
+        Self {
+            max_attempts: env_u64("GOOGLE_SKILL_MAX_ATTEMPTS")
+                .map(|v| v.clamp(1, u64::from(u32::MAX)) as u32)
+                .unwrap_or(defaults.max_attempts),
+            initial_backoff: env_u64("GOOGLE_SKILL_RETRY_BASE_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.initial_backoff),
+            max_backoff: env_u64("GOOGLE_SKILL_RETRY_MAX_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.max_backoff),
+        }
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        let exp = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)));
+        let ceiling = exp.min(self.max_backoff).as_millis() as u64;
+        if ceiling == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::random_range(0..=ceiling))
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -25,6 +110,24 @@ pub enum GoogleApiError {
     Parse(String),
 }
 
+impl GoogleApiError {
+    /// The canonical `error.status` of an API error body, such as
+    /// `FAILED_PRECONDITION`.
+    pub fn api_status(&self) -> Option<String> {
+        let Self::Api {
+            body: Some(body), ..
+        } = self
+        else {
+            return None;
+        };
+        let value: Value = serde_json::from_str(body).ok()?;
+        value
+            .pointer("/error/status")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+    }
+}
+
 impl GoogleClient {
     pub fn new(access_token: impl Into<String>) -> Result<Self> {
         let http = Client::builder()
@@ -34,15 +137,110 @@ impl GoogleClient {
         Ok(Self {
             http,
             access_token: access_token.into(),
+            endpoints: Endpoints::default(),
+            retry_policy: RetryPolicy::from_env(),
+            retries: Arc::new(AtomicU32::new(0)),
         })
     }
 
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Number of retried requests issued by this client (and its clones).
+    pub fn retry_count(&self) -> u32 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// Adds a `retries` field to an object payload when any request had to be
+    /// retried, so callers can see that the result was obtained under pressure.
+    pub fn annotate_retries(&self, mut payload: Value) -> Value {
+        let retries = self.retry_count();
+        if retries > 0
+            && let Some(obj) = payload.as_object_mut()
+        {
+            obj.insert("retries".to_string(), Value::from(retries));
+        }
+        payload
+    }
+
+    /// GETs `url` and deserializes the response into `T`.
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(String, String)],
+    ) -> std::result::Result<T, GoogleApiError> {
+        from_response(self.get_json(url, query)?)
+    }
+
+    /// POSTs `body` as JSON and deserializes the response into `T`.
+    pub fn post<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(String, String)],
+        body: &impl Serialize,
+    ) -> std::result::Result<T, GoogleApiError> {
+        from_response(self.post_json(url, query, &to_request(body)?)?)
+    }
+
+    /// Like [`post`](Self::post), for a POST that has the same effect however
+    /// often it is sent (clearing or overwriting values), so it is retried on
+    /// 5xx and dropped connections too.
+    pub fn post_idempotent<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(String, String)],
+        body: &impl Serialize,
+    ) -> std::result::Result<T, GoogleApiError> {
+        from_response(self.request_json(
+            Method::POST,
+            url,
+            query,
+            Some(&to_request(body)?),
+            true,
+        )?)
+    }
+
+    /// PUTs `body` as JSON and deserializes the response into `T`.
+    pub fn put<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(String, String)],
+        body: &impl Serialize,
+    ) -> std::result::Result<T, GoogleApiError> {
+        from_response(self.put_json(url, query, &to_request(body)?)?)
+    }
+
+    /// PATCHes `body` as JSON and deserializes the response into `T`.
+    pub fn patch<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(String, String)],
+        body: &impl Serialize,
+    ) -> std::result::Result<T, GoogleApiError> {
+        from_response(self.patch_json(url, query, &to_request(body)?)?)
+    }
+
     pub fn get_json(
         &self,
         url: &str,
         query: &[(String, String)],
     ) -> std::result::Result<Value, GoogleApiError> {
-        self.request_json(Method::GET, url, query, None)
+        self.request_json(Method::GET, url, query, None, true)
     }
 
     pub fn post_json(
@@ -51,7 +249,7 @@ impl GoogleClient {
         query: &[(String, String)],
         body: &Value,
     ) -> std::result::Result<Value, GoogleApiError> {
-        self.request_json(Method::POST, url, query, Some(body))
+        self.request_json(Method::POST, url, query, Some(body), false)
     }
 
     pub fn put_json(
@@ -60,7 +258,7 @@ impl GoogleClient {
         query: &[(String, String)],
         body: &Value,
     ) -> std::result::Result<Value, GoogleApiError> {
-        self.request_json(Method::PUT, url, query, Some(body))
+        self.request_json(Method::PUT, url, query, Some(body), true)
     }
 
     pub fn patch_json(
@@ -69,7 +267,7 @@ impl GoogleClient {
         query: &[(String, String)],
         body: &Value,
     ) -> std::result::Result<Value, GoogleApiError> {
-        self.request_json(Method::PATCH, url, query, Some(body))
+        self.request_json(Method::PATCH, url, query, Some(body), true)
     }
 
     pub fn delete_no_content(
@@ -77,21 +275,14 @@ impl GoogleClient {
         url: &str,
         query: &[(String, String)],
     ) -> std::result::Result<(), GoogleApiError> {
-        let request = self
-            .http
-            .request(Method::DELETE, url)
-            .bearer_auth(&self.access_token)
-            .query(query);
-
-        let response = request
-            .send()
-            .map_err(|e| GoogleApiError::Network(e.to_string()))?;
-
-        if response.status().is_success() {
-            return Ok(());
-        }
-
-        Err(error_from_response(response))
+        self.send_with_retry(true, || {
+            Ok(self
+                .http
+                .request(Method::DELETE, url)
+                .bearer_auth(&self.access_token)
+                .query(query))
+        })?;
+        Ok(())
     }
 
     pub fn get_bytes_to_path(
@@ -100,19 +291,13 @@ impl GoogleClient {
         query: &[(String, String)],
         output_path: &Path,
     ) -> std::result::Result<(), GoogleApiError> {
-        let request = self
-            .http
-            .request(Method::GET, url)
-            .bearer_auth(&self.access_token)
-            .query(query);
-
-        let response = request
-            .send()
-            .map_err(|e| GoogleApiError::Network(e.to_string()))?;
-
-        if !response.status().is_success() {
-            return Err(error_from_response(response));
-        }
+        let response = self.send_with_retry(true, || {
+            Ok(self
+                .http
+                .request(Method::GET, url)
+                .bearer_auth(&self.access_token)
+                .query(query))
+        })?;
 
         let bytes = response
             .bytes()
@@ -135,29 +320,15 @@ impl GoogleClient {
         mime_type: &str,
         file_name: &str,
     ) -> std::result::Result<Value, GoogleApiError> {
-        let metadata_part = multipart::Part::text(metadata.to_string())
-            .mime_str("application/json")
-            .map_err(|e| GoogleApiError::Parse(e.to_string()))?;
-        let file_bytes = fs::read(file_path).map_err(|e| GoogleApiError::Network(e.to_string()))?;
-        let file_part = multipart::Part::bytes(file_bytes)
-            .file_name(file_name.to_string())
-            .mime_str(mime_type)
-            .map_err(|e| GoogleApiError::Parse(e.to_string()))?;
-
-        let form = multipart::Form::new()
-            .part("metadata", metadata_part)
-            .part("file", file_part);
-
-        let response = self
-            .http
-            .post(url)
-            .bearer_auth(&self.access_token)
-            .query(query)
-            .multipart(form)
-            .send()
-            .map_err(|e| GoogleApiError::Network(e.to_string()))?;
-
-        parse_json_response(response)
+        self.request_multipart(
+            Method::POST,
+            url,
+            query,
+            metadata,
+            file_path,
+            mime_type,
+            file_name,
+        )
     }
 
     pub fn patch_multipart(
@@ -169,27 +340,54 @@ impl GoogleClient {
         mime_type: &str,
         file_name: &str,
     ) -> std::result::Result<Value, GoogleApiError> {
-        let metadata_part = multipart::Part::text(metadata.to_string())
-            .mime_str("application/json")
-            .map_err(|e| GoogleApiError::Parse(e.to_string()))?;
+        self.request_multipart(
+            Method::PATCH,
+            url,
+            query,
+            metadata,
+            file_path,
+            mime_type,
+            file_name,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn request_multipart(
+        &self,
+        method: Method,
+        url: &str,
+        query: &[(String, String)],
+        metadata: &Value,
+        file_path: &Path,
+        mime_type: &str,
+        file_name: &str,
+    ) -> std::result::Result<Value, GoogleApiError> {
+        let metadata_text = metadata.to_string();
         let file_bytes = fs::read(file_path).map_err(|e| GoogleApiError::Network(e.to_string()))?;
-        let file_part = multipart::Part::bytes(file_bytes)
-            .file_name(file_name.to_string())
-            .mime_str(mime_type)
-            .map_err(|e| GoogleApiError::Parse(e.to_string()))?;
-
-        let form = multipart::Form::new()
-            .part("metadata", metadata_part)
-            .part("file", file_part);
-
-        let response = self
-            .http
-            .request(Method::PATCH, url)
-            .bearer_auth(&self.access_token)
-            .query(query)
-            .multipart(form)
-            .send()
-            .map_err(|e| GoogleApiError::Network(e.to_string()))?;
+
+        // A multipart body is consumed when sent, so the form is rebuilt for
+        // every attempt from the bytes read above.
+        let idempotent = method != Method::POST;
+        let response = self.send_with_retry(idempotent, || {
+            let metadata_part = multipart::Part::text(metadata_text.clone())
+                .mime_str("application/json")
+                .map_err(|e| GoogleApiError::Parse(e.to_string()))?;
+            let file_part = multipart::Part::bytes(file_bytes.clone())
+                .file_name(file_name.to_string())
+                .mime_str(mime_type)
+                .map_err(|e| GoogleApiError::Parse(e.to_string()))?;
+
+            let form = multipart::Form::new()
+                .part("metadata", metadata_part)
+                .part("file", file_part);
+
+            Ok(self
+                .http
+                .request(method.clone(), url)
+                .bearer_auth(&self.access_token)
+                .query(query)
+                .multipart(form))
+        })?;
 
         parse_json_response(response)
     }
@@ -200,30 +398,101 @@ impl GoogleClient {
         url: &str,
         query: &[(String, String)],
         body: Option<&Value>,
+        idempotent: bool,
     ) -> std::result::Result<Value, GoogleApiError> {
-        let mut request = self
-            .http
-            .request(method, url)
-            .bearer_auth(&self.access_token)
-            .query(query);
-
-        if let Some(payload) = body {
-            request = request.json(payload);
-        }
+        let response = self.send_with_retry(idempotent, || {
+            let mut request = self
+                .http
+                .request(method.clone(), url)
+                .bearer_auth(&self.access_token)
+                .query(query);
 
-        let response = request
-            .send()
-            .map_err(|e| GoogleApiError::Network(e.to_string()))?;
+            if let Some(payload) = body {
+                request = request.json(payload);
+            }
+
+            Ok(request)
+        })?;
 
         parse_json_response(response)
     }
+
+    /// Sends the request built by `build`, retrying transient failures per the
+    /// client's [`RetryPolicy`]. Unless the request is `idempotent`, 5xx
+    /// replies and failures after the request may have been sent are final.
+    /// Only successful responses are returned; any final non-2xx reply is
+    /// converted into [`GoogleApiError::Api`].
+    fn send_with_retry<F>(
+        &self,
+        idempotent: bool,
+        build: F,
+    ) -> std::result::Result<Response, GoogleApiError>
+    where
+        F: Fn() -> std::result::Result<RequestBuilder, GoogleApiError>,
+    {
+        let max_attempts = self.retry_policy.max_attempts.max(1);
+        let mut attempt = 1u32;
+
+        loop {
+            let can_retry = attempt < max_attempts;
+
+            match build()?.send() {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = retry_after_delay(&response);
+                    let body = response.text().ok();
+
+                    let retryable = is_retryable_status(status, body.as_deref())
+                        && (idempotent || !status.is_server_error());
+                    if !(can_retry && retryable) {
+                        return Err(api_error(status.as_u16(), body));
+                    }
+
+                    let delay = retry_after
+                        .map(|delay| delay.min(self.retry_policy.max_backoff))
+                        .unwrap_or_else(|| self.retry_policy.backoff(attempt));
+                    thread::sleep(delay);
+                }
+                Err(err) => {
+                    // A connection that was never made sent nothing.
+                    if !can_retry || err.is_builder() || !(idempotent || err.is_connect()) {
+                        return Err(GoogleApiError::Network(err.to_string()));
+                    }
+                    thread::sleep(self.retry_policy.backoff(attempt));
+                }
+            }
+
+            self.retries.fetch_add(1, Ordering::Relaxed);
+            attempt += 1;
+        }
+    }
 }
 
-fn parse_json_response(response: Response) -> std::result::Result<Value, GoogleApiError> {
-    if !response.status().is_success() {
-        return Err(error_from_response(response));
+fn is_retryable_status(status: StatusCode, body: Option<&str>) -> bool {
+    match status.as_u16() {
+        429 | 500 | 502 | 503 | 504 => true,
+        // Drive reports per-user quota exhaustion as 403 with a rate-limit reason.
+        403 => body.is_some_and(|b| {
+            b.contains("rateLimitExceeded") || b.contains("userRateLimitExceeded")
+        }),
+        _ => false,
     }
+}
+
+fn retry_after_delay(response: &Response) -> Option<Duration> {
+    let raw = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = raw.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(raw).ok()?;
+    let wait_ms = at.timestamp_millis() - chrono::Utc::now().timestamp_millis();
+    Some(Duration::from_millis(wait_ms.max(0) as u64))
+}
 
+fn parse_json_response(response: Response) -> std::result::Result<Value, GoogleApiError> {
     let text = response
         .text()
         .map_err(|e| GoogleApiError::Network(e.to_string()))?;
@@ -235,10 +504,23 @@ fn parse_json_response(response: Response) -> std::result::Result<Value, GoogleA
     serde_json::from_str(&text).map_err(|e| GoogleApiError::Parse(e.to_string()))
 }
 
-fn error_from_response(response: Response) -> GoogleApiError {
-    let status = response.status().as_u16();
-    let body = response.text().ok();
+fn to_request(body: &impl Serialize) -> std::result::Result<Value, GoogleApiError> {
+    serde_json::to_value(body).map_err(|e| GoogleApiError::Parse(e.to_string()))
+}
+
+/// Deserializes a parsed response. An empty body (`null`) is read as `{}`
+/// when `T` accepts that, so a struct whose fields all default can stand in
+/// for a request without a meaningful reply; `()` works too.
+fn from_response<T: DeserializeOwned>(value: Value) -> std::result::Result<T, GoogleApiError> {
+    if value.is_null()
+        && let Ok(parsed) = T::deserialize(&Value::Object(Default::default()))
+    {
+        return Ok(parsed);
+    }
+    serde_json::from_value(value).map_err(|e| GoogleApiError::Parse(e.to_string()))
+}
 
+fn api_error(status: u16, body: Option<String>) -> GoogleApiError {
     let message = body
         .as_deref()
         .and_then(extract_google_error_message)
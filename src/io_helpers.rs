@@ -1,5 +1,5 @@
+use serde::Serialize;
 use serde_json::{Value, json};
-use std::io::{self, Read};
 use std::path::PathBuf;
 
 pub fn print_json(value: &Value) {
@@ -15,14 +15,30 @@ pub fn print_error(error_code: &str, message: impl AsRef<str>) {
     }));
 }
 
-pub fn read_stdin_json() -> anyhow::Result<Value> {
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-    if input.trim().is_empty() {
-        anyhow::bail!("Expected JSON input via stdin");
+/// Builds the `{"status": "success", "operation": ...}` payload the binaries
+/// print, with the fields of `result` alongside.
+pub fn success_payload(operation: &str, result: &impl Serialize) -> Value {
+    let mut payload = json!({
+        "status": "success",
+        "operation": operation
+    });
+    if let (Some(target), Ok(Value::Object(fields))) =
+        (payload.as_object_mut(), serde_json::to_value(result))
+    {
+        target.extend(fields);
+    }
+    payload
+}
+
+/// Reads an integer from a JSON number, truncating floats.
+pub fn value_to_i64(value: &Value) -> Option<i64> {
+    if let Some(v) = value.as_i64() {
+        Some(v)
+    } else if let Some(v) = value.as_u64() {
+        i64::try_from(v).ok()
+    } else {
+        value.as_f64().map(|v| v as i64)
     }
-    let parsed: Value = serde_json::from_str(&input)?;
-    Ok(parsed)
 }
 
 pub fn home_dir() -> anyhow::Result<PathBuf> {
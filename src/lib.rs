@@ -1,3 +1,12 @@
 pub mod auth;
+pub mod cli;
+pub mod config;
+pub mod docs;
+pub mod drive;
 pub mod google_api;
 pub mod io_helpers;
+pub mod login;
+pub mod models;
+pub mod service_account;
+pub mod sheets;
+pub mod token_store;
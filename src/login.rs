@@ -0,0 +1,203 @@
+use crate::auth::{
+    AuthPaths, DeviceAuthorization, Pkce, StoredToken, build_auth_url_with,
+    exchange_authorization_code, load_client_config, poll_device_token, random_url_token,
+    request_device_code,
+};
+use anyhow::{Context, Result, anyhow};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use url::Url;
+
+const DEFAULT_LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Runs the loopback-redirect authorization flow: listens on an ephemeral
+/// `127.0.0.1` port, sends the user to Google with a PKCE challenge, captures
+/// the redirected code and stores the resulting token.
+///
+/// `on_auth_url` receives the URL before the listener starts waiting so the
+/// caller can show it; a browser is opened as well unless
+/// `GOOGLE_SKILL_NO_BROWSER` is set.
+pub fn loopback_login(
+    paths: &AuthPaths,
+    scopes: &[&str],
+    on_auth_url: impl FnOnce(&str),
+) -> Result<StoredToken> {
+    let config = load_client_config(paths)?;
+
+    let listener =
+        TcpListener::bind("127.0.0.1:0").context("Failed to start local redirect listener")?;
+    let port = listener
+        .local_addr()
+        .context("Failed to read local redirect listener address")?
+        .port();
+    let redirect_uri = format!("http://127.0.0.1:{port}");
+
+    let pkce = Pkce::generate();
+    let state = random_url_token(16);
+    let auth_url = build_auth_url_with(&config, scopes, &redirect_uri, Some(&pkce), Some(&state))?;
+
+    on_auth_url(&auth_url);
+    if std::env::var_os("GOOGLE_SKILL_NO_BROWSER").is_none() {
+        open_browser(&auth_url);
+    }
+
+    let code = wait_for_code(&listener, &state, login_timeout())?;
+    let existing_refresh = paths.load_token().ok().and_then(|t| t.refresh_token);
+    let token = exchange_authorization_code(
+        &config,
+        &code,
+        &redirect_uri,
+        Some(&pkce.verifier),
+        existing_refresh,
+    )?;
+    paths.save_token(&token)?;
+
+    Ok(token)
+}
+
+/// Runs the device authorization flow for machines without a browser.
+/// `on_user_code` receives the verification URL and user code to show while
+/// the token endpoint is polled; the approved token is stored on success.
+pub fn device_login(
+    paths: &AuthPaths,
+    scopes: &[&str],
+    on_user_code: impl FnOnce(&DeviceAuthorization),
+) -> Result<StoredToken> {
+    let config = load_client_config(paths)?;
+    let device = request_device_code(&config, scopes)?;
+    on_user_code(&device);
+
+    let existing_refresh = paths.load_token().ok().and_then(|t| t.refresh_token);
+    let token = poll_device_token(&config, &device, existing_refresh)?;
+    paths.save_token(&token)?;
+
+    Ok(token)
+}
+
+fn login_timeout() -> Duration {
+    std::env::var("GOOGLE_SKILL_LOGIN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_LOGIN_TIMEOUT)
+}
+
+/// Accepts redirect requests until one carries a code (or an error) for the
+/// expected `state`. Unrelated requests such as `/favicon.ico` get a 404.
+fn wait_for_code(
+    listener: &TcpListener,
+    expected_state: &str,
+    timeout: Duration,
+) -> Result<String> {
+    listener
+        .set_nonblocking(true)
+        .context("Failed to configure local redirect listener")?;
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Some(outcome) = handle_redirect(stream, expected_state)? {
+                    return outcome;
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err(anyhow!(
+                        "Timed out after {}s waiting for the browser redirect",
+                        timeout.as_secs()
+                    ));
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(err) => return Err(err).context("Local redirect listener failed"),
+        }
+    }
+}
+
+fn handle_redirect(mut stream: TcpStream, expected_state: &str) -> Result<Option<Result<String>>> {
+    stream
+        .set_nonblocking(false)
+        .context("Failed to configure redirect connection")?;
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+
+    let mut request_line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut request_line)
+        .context("Failed reading redirect request")?;
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let url = Url::parse(&format!("http://127.0.0.1{target}"))
+        .context("Failed parsing redirect request")?;
+
+    let param = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    };
+
+    if param("code").is_none() && param("error").is_none() {
+        respond(&mut stream, "404 Not Found", "Not found.");
+        return Ok(None);
+    }
+
+    if param("state").as_deref() != Some(expected_state) {
+        respond(
+            &mut stream,
+            "400 Bad Request",
+            "Authorization failed: state mismatch. Close this window and retry.",
+        );
+        return Ok(Some(Err(anyhow!(
+            "Redirect state did not match; possible cross-site request"
+        ))));
+    }
+
+    if let Some(error) = param("error") {
+        respond(
+            &mut stream,
+            "400 Bad Request",
+            "Authorization was not granted. You can close this window.",
+        );
+        return Ok(Some(Err(anyhow!("Authorization denied: {error}"))));
+    }
+
+    respond(
+        &mut stream,
+        "200 OK",
+        "Authorization complete. You can close this window and return to the terminal.",
+    );
+    Ok(param("code").map(Ok))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!("<!doctype html><html><body><p>{message}</p></body></html>");
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.flush();
+}
+
+fn open_browser(url: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut c = Command::new("open");
+        c.arg(url);
+        c
+    } else if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", "", url]);
+        c
+    } else {
+        let mut c = Command::new("xdg-open");
+        c.arg(url);
+        c
+    };
+
+    let _ = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
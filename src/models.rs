@@ -0,0 +1,3 @@
+pub mod docs;
+pub mod drive;
+pub mod sheets;
@@ -0,0 +1,667 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A `documents.get` resource. Only the parts of the document the tools read
+/// are modelled; everything else is ignored when deserializing. As in the
+/// other models, only fields Google may leave out (empty lists, zero values)
+/// default; a missing ID or other required field is an error.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+    pub document_id: String,
+    #[serde(default)]
+    pub title: String,
+    pub revision_id: Option<String>,
+    #[serde(default)]
+    pub body: Body,
+    /// The document's lists by ID, referenced from [`Bullet::list_id`].
+    #[serde(default)]
+    pub lists: BTreeMap<String, List>,
+    /// Images and other embedded objects by ID, referenced from
+    /// [`InlineObjectElement::inline_object_id`].
+    #[serde(default)]
+    pub inline_objects: BTreeMap<String, InlineObject>,
+}
+
+impl Document {
+    /// End index of the last structural element of the body, which is one
+    /// past the body's trailing newline.
+    pub fn end_index(&self) -> Option<i64> {
+        self.body
+            .content
+            .last()
+            .and_then(|element| element.end_index)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Body {
+    #[serde(default)]
+    pub content: Vec<StructuralElement>,
+}
+
+/// A paragraph, table or section break. Exactly one of the content fields is
+/// set; section breaks have neither `paragraph` nor `table`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralElement {
+    pub start_index: Option<i64>,
+    pub end_index: Option<i64>,
+    pub paragraph: Option<Paragraph>,
+    pub table: Option<Table>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Paragraph {
+    #[serde(default)]
+    pub elements: Vec<ParagraphElement>,
+    #[serde(default)]
+    pub paragraph_style: ParagraphStyle,
+    pub bullet: Option<Bullet>,
+}
+
+impl Paragraph {
+    /// Concatenated content of the paragraph's text runs, trailing newline
+    /// included.
+    pub fn text(&self) -> String {
+        self.elements
+            .iter()
+            .filter_map(|element| element.text_run.as_ref())
+            .map(|run| run.content.as_str())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParagraphElement {
+    pub start_index: Option<i64>,
+    pub end_index: Option<i64>,
+    pub text_run: Option<TextRun>,
+    pub inline_object_element: Option<InlineObjectElement>,
+    pub horizontal_rule: Option<HorizontalRule>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextRun {
+    pub content: String,
+    #[serde(default)]
+    pub text_style: TextStyle,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineObjectElement {
+    pub inline_object_id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HorizontalRule {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineObject {
+    pub inline_object_properties: InlineObjectProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineObjectProperties {
+    pub embedded_object: EmbeddedObject,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedObject {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_properties: Option<ImageProperties>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageProperties {
+    /// Short-lived URL Google serves the image from.
+    pub content_uri: Option<String>,
+    /// URL the image was inserted from, when it was inserted by URL.
+    pub source_uri: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct List {
+    pub list_properties: ListProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListProperties {
+    /// One entry per nesting level, outermost first.
+    #[serde(default)]
+    pub nesting_levels: Vec<NestingLevel>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NestingLevel {
+    /// `DECIMAL`, `ALPHA`, `ROMAN` and so on for numbered levels.
+    pub glyph_type: Option<String>,
+    /// The bullet character for unordered levels, e.g. `●`.
+    pub glyph_symbol: Option<String>,
+}
+
+impl NestingLevel {
+    pub fn is_ordered(&self) -> bool {
+        matches!(
+            self.glyph_type.as_deref(),
+            Some("DECIMAL" | "ZERO_DECIMAL" | "ALPHA" | "UPPER_ALPHA" | "ROMAN" | "UPPER_ROMAN")
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bullet {
+    pub list_id: Option<String>,
+    pub nesting_level: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParagraphStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_style_type: Option<NamedStyleType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent_start: Option<Dimension>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent_first_line: Option<Dimension>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NamedStyleType {
+    #[serde(rename = "NAMED_STYLE_TYPE_UNSPECIFIED")]
+    Unspecified,
+    #[serde(rename = "NORMAL_TEXT")]
+    NormalText,
+    #[serde(rename = "TITLE")]
+    Title,
+    #[serde(rename = "SUBTITLE")]
+    Subtitle,
+    #[serde(rename = "HEADING_1")]
+    Heading1,
+    #[serde(rename = "HEADING_2")]
+    Heading2,
+    #[serde(rename = "HEADING_3")]
+    Heading3,
+    #[serde(rename = "HEADING_4")]
+    Heading4,
+    #[serde(rename = "HEADING_5")]
+    Heading5,
+    #[serde(rename = "HEADING_6")]
+    Heading6,
+}
+
+impl NamedStyleType {
+    /// 1 to 6 for the heading styles, `None` for everything else.
+    pub fn heading_level(self) -> Option<i64> {
+        match self {
+            Self::Heading1 => Some(1),
+            Self::Heading2 => Some(2),
+            Self::Heading3 => Some(3),
+            Self::Heading4 => Some(4),
+            Self::Heading5 => Some(5),
+            Self::Heading6 => Some(6),
+            _ => None,
+        }
+    }
+
+    /// The heading style for `level`, clamped to 1..=6.
+    pub fn heading(level: i64) -> Self {
+        match level {
+            i64::MIN..=1 => Self::Heading1,
+            2 => Self::Heading2,
+            3 => Self::Heading3,
+            4 => Self::Heading4,
+            5 => Self::Heading5,
+            _ => Self::Heading6,
+        }
+    }
+}
+
+/// Character style. In requests, unset fields are omitted and only those named
+/// in the request's `fields` mask are changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weighted_font_family: Option<WeightedFontFamily>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_color: Option<OptionalColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foreground_color: Option<OptionalColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<Link>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeightedFontFamily {
+    pub font_family: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionalColor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+}
+
+impl OptionalColor {
+    pub fn rgb(red: f64, green: f64, blue: f64) -> Self {
+        Self {
+            color: Some(Color {
+                rgb_color: Some(RgbColor { red, green, blue }),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Color {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rgb_color: Option<RgbColor>,
+}
+
+/// Color components in `0.0..=1.0`; Google omits components that are zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RgbColor {
+    #[serde(default)]
+    pub red: f64,
+    #[serde(default)]
+    pub green: f64,
+    #[serde(default)]
+    pub blue: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Link {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bookmark_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Table {
+    pub rows: i64,
+    pub columns: i64,
+    #[serde(default)]
+    pub table_rows: Vec<TableRow>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRow {
+    pub start_index: Option<i64>,
+    pub end_index: Option<i64>,
+    #[serde(default)]
+    pub table_cells: Vec<TableCell>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableCell {
+    pub start_index: Option<i64>,
+    pub end_index: Option<i64>,
+    #[serde(default)]
+    pub content: Vec<StructuralElement>,
+}
+
+/// Body of `documents.batchUpdate`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateDocumentRequest {
+    pub requests: Vec<Request>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_control: Option<WriteControl>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateDocumentResponse {
+    pub document_id: String,
+    /// One reply per request, in request order.
+    #[serde(default)]
+    pub replies: Vec<Response>,
+    pub write_control: Option<WriteControl>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteControl {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_revision_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_revision_id: Option<String>,
+}
+
+/// One `documents.batchUpdate` request. Serializes to the single-key object
+/// the API expects, e.g. `{"insertText": {...}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Request {
+    InsertText(InsertTextRequest),
+    DeleteContentRange(DeleteContentRangeRequest),
+    ReplaceAllText(ReplaceAllTextRequest),
+    UpdateTextStyle(UpdateTextStyleRequest),
+    UpdateParagraphStyle(UpdateParagraphStyleRequest),
+    InsertPageBreak(InsertPageBreakRequest),
+    InsertInlineImage(InsertInlineImageRequest),
+    InsertTable(InsertTableRequest),
+    InsertTableRow(InsertTableRowRequest),
+    DeleteTableRow(DeleteTableRowRequest),
+    CreateParagraphBullets(CreateParagraphBulletsRequest),
+}
+
+impl Request {
+    pub fn insert_text(text: impl Into<String>, index: i64) -> Self {
+        Self::InsertText(InsertTextRequest {
+            text: text.into(),
+            location: Some(Location::at(index)),
+            end_of_segment_location: None,
+        })
+    }
+
+    pub fn delete_content_range(start_index: i64, end_index: i64) -> Self {
+        Self::DeleteContentRange(DeleteContentRangeRequest {
+            range: Range::new(start_index, end_index),
+        })
+    }
+
+    /// Sets the fields of `text_style` named in `fields` on `range`.
+    pub fn update_text_style(range: Range, text_style: TextStyle, fields: &[&str]) -> Self {
+        Self::UpdateTextStyle(UpdateTextStyleRequest {
+            range,
+            text_style,
+            fields: fields.join(","),
+        })
+    }
+
+    /// Sets the fields of `paragraph_style` named in `fields` on every
+    /// paragraph overlapping `range`.
+    pub fn update_paragraph_style(
+        range: Range,
+        paragraph_style: ParagraphStyle,
+        fields: &[&str],
+    ) -> Self {
+        Self::UpdateParagraphStyle(UpdateParagraphStyleRequest {
+            range,
+            paragraph_style,
+            fields: fields.join(","),
+        })
+    }
+
+    /// Adds an empty row below row `row_index` of the table starting at
+    /// `table_start_index`.
+    pub fn insert_table_row_below(table_start_index: i64, row_index: i64) -> Self {
+        Self::InsertTableRow(InsertTableRowRequest {
+            table_cell_location: TableCellLocation::row(table_start_index, row_index),
+            insert_below: true,
+        })
+    }
+
+    pub fn delete_table_row(table_start_index: i64, row_index: i64) -> Self {
+        Self::DeleteTableRow(DeleteTableRowRequest {
+            table_cell_location: TableCellLocation::row(table_start_index, row_index),
+        })
+    }
+
+    /// Turns every paragraph overlapping `range` into a list item. Leading
+    /// tabs set each paragraph's nesting level and are removed.
+    pub fn create_paragraph_bullets(range: Range, bullet_preset: BulletGlyphPreset) -> Self {
+        Self::CreateParagraphBullets(CreateParagraphBulletsRequest {
+            range,
+            bullet_preset,
+        })
+    }
+}
+
+/// A position in the body (or in the segment named by `segment_id`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub index: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_id: Option<String>,
+}
+
+impl Location {
+    pub fn at(index: i64) -> Self {
+        Self {
+            index,
+            segment_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndOfSegmentLocation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_id: Option<String>,
+}
+
+/// The half-open index range `start_index..end_index`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Range {
+    pub start_index: i64,
+    pub end_index: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment_id: Option<String>,
+}
+
+impl Range {
+    pub fn new(start_index: i64, end_index: i64) -> Self {
+        Self {
+            start_index,
+            end_index,
+            segment_id: None,
+        }
+    }
+}
+
+/// Inserts text at `location`, or at `end_of_segment_location` when that is
+/// set instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertTextRequest {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_of_segment_location: Option<EndOfSegmentLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteContentRangeRequest {
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceAllTextRequest {
+    pub contains_text: SubstringMatchCriteria,
+    pub replace_text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubstringMatchCriteria {
+    pub text: String,
+    #[serde(default)]
+    pub match_case: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTextStyleRequest {
+    pub range: Range,
+    pub text_style: TextStyle,
+    /// Comma-separated field mask, e.g. `bold,italic`.
+    pub fields: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateParagraphStyleRequest {
+    pub range: Range,
+    pub paragraph_style: ParagraphStyle,
+    /// Comma-separated field mask, e.g. `namedStyleType`.
+    pub fields: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertPageBreakRequest {
+    pub location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertInlineImageRequest {
+    pub uri: String,
+    pub location: Location,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_size: Option<Size>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Size {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<Dimension>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<Dimension>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dimension {
+    pub magnitude: f64,
+    /// `PT` is the only unit the API accepts.
+    pub unit: String,
+}
+
+impl Dimension {
+    pub fn points(magnitude: f64) -> Self {
+        Self {
+            magnitude,
+            unit: "PT".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertTableRequest {
+    pub rows: i64,
+    pub columns: i64,
+    pub location: Location,
+}
+
+/// A cell, addressed by the index where its table starts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableCellLocation {
+    pub table_start_location: Location,
+    pub row_index: i64,
+    pub column_index: i64,
+}
+
+impl TableCellLocation {
+    /// The first cell of row `row_index`.
+    pub fn row(table_start_index: i64, row_index: i64) -> Self {
+        Self {
+            table_start_location: Location::at(table_start_index),
+            row_index,
+            column_index: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertTableRowRequest {
+    pub table_cell_location: TableCellLocation,
+    pub insert_below: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteTableRowRequest {
+    pub table_cell_location: TableCellLocation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateParagraphBulletsRequest {
+    pub range: Range,
+    pub bullet_preset: BulletGlyphPreset,
+}
+
+/// The list presets the tools create; the API offers more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BulletGlyphPreset {
+    /// `●`, `○`, `■` by nesting level.
+    BulletDiscCircleSquare,
+    /// `1.`, `a.`, `i.` by nesting level.
+    NumberedDecimalAlphaRoman,
+    BulletCheckbox,
+}
+
+/// A reply in [`BatchUpdateDocumentResponse::replies`]. Requests without a
+/// reply get an empty one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub replace_all_text: Option<ReplaceAllTextResponse>,
+    pub insert_inline_image: Option<InsertInlineImageResponse>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceAllTextResponse {
+    #[serde(default)]
+    pub occurrences_changed: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertInlineImageResponse {
+    pub object_id: String,
+}
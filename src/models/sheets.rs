@@ -0,0 +1,430 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A `spreadsheets.get` resource, also sent (with just titles set) to
+/// `spreadsheets.create`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Spreadsheet {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spreadsheet_id: Option<String>,
+    #[serde(default)]
+    pub properties: SpreadsheetProperties,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sheets: Vec<Sheet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spreadsheet_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpreadsheetProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sheet {
+    pub properties: SheetProperties,
+}
+
+/// Properties of one tab. In `updateSheetProperties` requests only the
+/// fields named in the `fields` mask are applied.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sheet_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sheet_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_properties: Option<GridProperties>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GridProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frozen_row_count: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frozen_column_count: Option<i64>,
+}
+
+/// Cell values of a range, row-major unless `major_dimension` says `COLUMNS`.
+/// Google omits `values` for an empty range.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueRange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub major_dimension: Option<String>,
+    #[serde(default)]
+    pub values: Vec<Vec<Value>>,
+}
+
+/// Response of `values.update`, and the `updates` of `values.append`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateValuesResponse {
+    pub spreadsheet_id: Option<String>,
+    pub updated_range: Option<String>,
+    pub updated_rows: Option<i64>,
+    pub updated_columns: Option<i64>,
+    pub updated_cells: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppendValuesResponse {
+    pub spreadsheet_id: Option<String>,
+    pub table_range: Option<String>,
+    pub updates: UpdateValuesResponse,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchGetValuesResponse {
+    pub spreadsheet_id: Option<String>,
+    #[serde(default)]
+    pub value_ranges: Vec<ValueRange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateValuesRequest {
+    /// `RAW` or `USER_ENTERED`.
+    pub value_input_option: String,
+    pub data: Vec<ValueRange>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateValuesResponse {
+    pub spreadsheet_id: Option<String>,
+    pub total_updated_rows: Option<i64>,
+    pub total_updated_columns: Option<i64>,
+    pub total_updated_cells: Option<i64>,
+    pub total_updated_sheets: Option<i64>,
+}
+
+/// A zero-based, half-open cell range on one sheet. Unset bounds are
+/// unbounded, so `A:C` leaves the row indices unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GridRange {
+    #[serde(default)]
+    pub sheet_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_row_index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_row_index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_column_index: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column_index: Option<i64>,
+}
+
+/// Rows or columns `start_index..end_index` of a sheet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimensionRange {
+    pub sheet_id: i64,
+    /// `ROWS` or `COLUMNS`.
+    pub dimension: String,
+    pub start_index: i64,
+    pub end_index: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateSpreadsheetRequest {
+    pub requests: Vec<Request>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpdateSpreadsheetResponse {
+    pub spreadsheet_id: Option<String>,
+    /// One reply per request, in request order.
+    #[serde(default)]
+    pub replies: Vec<Response>,
+}
+
+/// One `spreadsheets.batchUpdate` request. Serializes to the single-key
+/// object the API expects, e.g. `{"addSheet": {...}}`.
+///
+/// Cell formats, chart specs and conditional format rules are large,
+/// deeply optional schemas and are carried as raw JSON (see
+/// `sheets::build_cell_format` and friends for builders).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Request {
+    AddSheet(AddSheetRequest),
+    DeleteSheet(DeleteSheetRequest),
+    UpdateSheetProperties(UpdateSheetPropertiesRequest),
+    RepeatCell(RepeatCellRequest),
+    MergeCells(MergeCellsRequest),
+    UnmergeCells(UnmergeCellsRequest),
+    AutoResizeDimensions(AutoResizeDimensionsRequest),
+    SortRange(SortRangeRequest),
+    FindReplace(FindReplaceRequest),
+    UpdateDimensionProperties(UpdateDimensionPropertiesRequest),
+    SetBasicFilter(SetBasicFilterRequest),
+    AddChart(AddChartRequest),
+    AddProtectedRange(AddProtectedRangeRequest),
+    AddConditionalFormatRule(AddConditionalFormatRuleRequest),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddSheetRequest {
+    pub properties: SheetProperties,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteSheetRequest {
+    pub sheet_id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSheetPropertiesRequest {
+    pub properties: SheetProperties,
+    /// Comma-separated field mask, e.g. `title` or
+    /// `gridProperties.frozenRowCount`.
+    pub fields: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepeatCellRequest {
+    pub range: GridRange,
+    pub cell: CellData,
+    pub fields: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CellData {
+    /// A `CellFormat` object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_entered_format: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeCellsRequest {
+    pub range: GridRange,
+    /// `MERGE_ALL`, `MERGE_COLUMNS` or `MERGE_ROWS`.
+    pub merge_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmergeCellsRequest {
+    pub range: GridRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoResizeDimensionsRequest {
+    pub dimensions: DimensionRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SortRangeRequest {
+    pub range: GridRange,
+    pub sort_specs: Vec<SortSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SortSpec {
+    pub dimension_index: i64,
+    /// `ASCENDING` or `DESCENDING`.
+    pub sort_order: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindReplaceRequest {
+    pub find: String,
+    #[serde(default)]
+    pub replacement: String,
+    #[serde(default)]
+    pub match_case: bool,
+    #[serde(default)]
+    pub match_entire_cell: bool,
+    #[serde(default)]
+    pub search_by_regex: bool,
+    #[serde(default)]
+    pub include_formulas: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sheet_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_sheets: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDimensionPropertiesRequest {
+    pub range: DimensionRange,
+    pub properties: DimensionProperties,
+    pub fields: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimensionProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pixel_size: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBasicFilterRequest {
+    pub filter: BasicFilter,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasicFilter {
+    pub range: GridRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddChartRequest {
+    pub chart: EmbeddedChart,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedChart {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chart_id: Option<i64>,
+    /// A `ChartSpec` object.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub spec: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<EmbeddedObjectPosition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedObjectPosition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overlay_position: Option<OverlayPosition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayPosition {
+    pub anchor_cell: GridCoordinate,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GridCoordinate {
+    #[serde(default)]
+    pub sheet_id: i64,
+    #[serde(default)]
+    pub row_index: i64,
+    #[serde(default)]
+    pub column_index: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddProtectedRangeRequest {
+    pub protected_range: ProtectedRange,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectedRange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protected_range_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<GridRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub warning_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editors: Option<Editors>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Editors {
+    /// Email addresses.
+    #[serde(default)]
+    pub users: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddConditionalFormatRuleRequest {
+    /// A `ConditionalFormatRule` object.
+    pub rule: Value,
+    pub index: i64,
+}
+
+/// A reply in [`BatchUpdateSpreadsheetResponse::replies`]. Requests without a
+/// reply get an empty one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub add_sheet: Option<AddSheetResponse>,
+    pub find_replace: Option<FindReplaceResponse>,
+    pub add_chart: Option<AddChartResponse>,
+    pub add_protected_range: Option<AddProtectedRangeResponse>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddSheetResponse {
+    pub properties: SheetProperties,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindReplaceResponse {
+    #[serde(default)]
+    pub occurrences_changed: i64,
+    #[serde(default)]
+    pub values_changed: i64,
+    #[serde(default)]
+    pub sheets_changed: i64,
+    #[serde(default)]
+    pub formulas_changed: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddChartResponse {
+    pub chart: EmbeddedChart,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddProtectedRangeResponse {
+    pub protected_range: ProtectedRange,
+}
@@ -0,0 +1,222 @@
+use crate::auth::{
+    AuthPaths, ScopeField, StoredToken, extract_google_error_message, missing_scopes,
+    token_is_expired,
+};
+use crate::config::{Endpoints, ServiceAccountSettings};
+use anyhow::{Context, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use chrono::Utc;
+use reqwest::blocking::Client;
+use ring::rand::SystemRandom;
+use ring::signature::{RSA_PKCS1_SHA256, RsaKeyPair};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const JWT_BEARER_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+const ASSERTION_LIFETIME_SECS: i64 = 3600;
+
+/// The fields of a service account JSON key that the JWT grant needs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServiceAccountKey {
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub client_email: String,
+    pub private_key: String,
+    #[serde(default)]
+    pub private_key_id: Option<String>,
+    #[serde(default)]
+    pub token_uri: Option<String>,
+}
+
+/// Returns the service account key stored at `path`, or `None` when the file
+/// is an ordinary installed/web OAuth client secret.
+pub fn load_service_account_key(path: &Path) -> Result<Option<ServiceAccountKey>> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read credentials file: {}", path.display()))?;
+    let value: Value = serde_json::from_str(&raw)
+        .with_context(|| format!("Failed to parse credentials JSON: {}", path.display()))?;
+
+    if value.get("type").and_then(Value::as_str) != Some("service_account") {
+        return Ok(None);
+    }
+
+    serde_json::from_value(value)
+        .map(Some)
+        .with_context(|| format!("Invalid service account key: {}", path.display()))
+}
+
+/// Returns a cached service account token for `scopes`, minting a new one via
+/// the JWT bearer grant when the cache is missing, expired, or was issued for
+/// a different account or impersonated user.
+pub fn ensure_service_account_token(
+    paths: &AuthPaths,
+    key: &ServiceAccountKey,
+    scopes: &[&str],
+) -> Result<StoredToken> {
+    let settings = ServiceAccountSettings::resolve(&paths.config_path)?;
+    let cached = || {
+        paths
+            .load_token()
+            .ok()
+            .filter(|t| t.client_id == key.client_email && t.subject == settings.subject)
+    };
+    let usable =
+        |token: &StoredToken| !token_is_expired(token) && missing_scopes(token, scopes).is_empty();
+
+    if let Some(token) = cached().filter(usable) {
+        return Ok(token);
+    }
+
+    let _lock = paths.lock_tokens()?;
+    // Keep scopes cached for the same account so alternating between the
+    // tools does not mint a new token on every switch.
+    let mut wanted: Vec<String> = scopes.iter().map(ToString::to_string).collect();
+    if let Some(token) = cached() {
+        if usable(&token) {
+            return Ok(token);
+        }
+        for scope in token
+            .scope
+            .as_ref()
+            .map(ScopeField::scopes)
+            .unwrap_or_default()
+        {
+            if !wanted.contains(&scope) {
+                wanted.push(scope);
+            }
+        }
+    }
+    let scopes: Vec<&str> = wanted.iter().map(String::as_str).collect();
+
+    let token_uri = Endpoints::resolve(&paths.config_path)?
+        .oauth_token
+        .or_else(|| key.token_uri.clone())
+        .unwrap_or_else(|| DEFAULT_TOKEN_URI.to_string());
+    let token = fetch_service_account_token(key, &scopes, settings.subject.as_deref(), &token_uri)?;
+    paths.save_token(&token)?;
+
+    Ok(token)
+}
+
+/// Signs an RS256 assertion for `key` and exchanges it at `token_uri`.
+/// `subject` impersonates a Workspace user through domain-wide delegation.
+pub fn fetch_service_account_token(
+    key: &ServiceAccountKey,
+    scopes: &[&str],
+    subject: Option<&str>,
+    token_uri: &str,
+) -> Result<StoredToken> {
+    let assertion = build_assertion(key, scopes, subject, token_uri)?;
+
+    let client = Client::builder()
+        .user_agent("google-docs-skill/1.0")
+        .build()
+        .context("Failed building HTTP client")?;
+
+    let resp = client
+        .post(token_uri)
+        .form(&[
+            ("grant_type", JWT_BEARER_GRANT_TYPE),
+            ("assertion", assertion.as_str()),
+        ])
+        .send()
+        .context("Service account token request failed")?;
+
+    let status = resp.status();
+    let body = resp
+        .text()
+        .context("Failed reading service account token response body")?;
+
+    if !status.is_success() {
+        let msg = extract_google_error_message(&body).unwrap_or_else(|| {
+            format!("Service account token request failed with status {status}")
+        });
+        return Err(anyhow!("{msg}"));
+    }
+
+    let payload: Value = serde_json::from_str(&body).with_context(|| {
+        format!("Failed parsing service account token response JSON. Body: {body}")
+    })?;
+    let access_token = payload
+        .get("access_token")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("Service account token response had no access_token"))?;
+    let expires_in = payload
+        .get("expires_in")
+        .and_then(Value::as_i64)
+        .unwrap_or(ASSERTION_LIFETIME_SECS)
+        .max(1);
+
+    Ok(StoredToken {
+        client_id: key.client_email.clone(),
+        access_token: access_token.to_string(),
+        refresh_token: None,
+        scope: Some(ScopeField::Multiple(
+            scopes.iter().map(ToString::to_string).collect(),
+        )),
+        expiration_time_millis: Utc::now().timestamp_millis() + expires_in * 1000,
+        subject: subject.map(ToString::to_string),
+    })
+}
+
+fn build_assertion(
+    key: &ServiceAccountKey,
+    scopes: &[&str],
+    subject: Option<&str>,
+    audience: &str,
+) -> Result<String> {
+    let mut header = json!({"alg": "RS256", "typ": "JWT"});
+    if let Some(kid) = &key.private_key_id {
+        header["kid"] = json!(kid);
+    }
+
+    let issued_at = Utc::now().timestamp();
+    let mut claims = json!({
+        "iss": key.client_email,
+        "scope": scopes.join(" "),
+        "aud": audience,
+        "iat": issued_at,
+        "exp": issued_at + ASSERTION_LIFETIME_SECS
+    });
+    if let Some(subject) = subject {
+        claims["sub"] = json!(subject);
+    }
+
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+
+    let key_pair = RsaKeyPair::from_pkcs8(&pem_to_der(&key.private_key)?)
+        .map_err(|err| anyhow!("Service account private key rejected: {err}"))?;
+    let mut signature = vec![0u8; key_pair.public().modulus_len()];
+    key_pair
+        .sign(
+            &RSA_PKCS1_SHA256,
+            &SystemRandom::new(),
+            signing_input.as_bytes(),
+            &mut signature,
+        )
+        .map_err(|_| anyhow!("Failed signing service account assertion"))?;
+
+    Ok(format!(
+        "{signing_input}.{}",
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+fn pem_to_der(pem: &str) -> Result<Vec<u8>> {
+    let body: String = pem
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    STANDARD
+        .decode(body)
+        .context("Service account private_key is not valid PEM")
+}
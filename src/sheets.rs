@@ -0,0 +1,1217 @@
+use crate::google_api::{GoogleApiError, GoogleClient};
+use crate::models::sheets::{
+    self as model, AddChartRequest, AddConditionalFormatRuleRequest, AddProtectedRangeRequest,
+    AddSheetRequest, AppendValuesResponse, AutoResizeDimensionsRequest, BasicFilter,
+    BatchGetValuesResponse, BatchUpdateSpreadsheetRequest, BatchUpdateSpreadsheetResponse,
+    BatchUpdateValuesRequest, BatchUpdateValuesResponse, CellData, DeleteSheetRequest,
+    DimensionProperties, DimensionRange, Editors, EmbeddedChart, EmbeddedObjectPosition,
+    FindReplaceRequest, GridCoordinate, GridProperties, GridRange, MergeCellsRequest,
+    OverlayPosition, RepeatCellRequest, Request, Response, SetBasicFilterRequest, Sheet,
+    SheetProperties, SortRangeRequest, SortSpec, Spreadsheet, SpreadsheetProperties,
+    UnmergeCellsRequest, UpdateDimensionPropertiesRequest, UpdateSheetPropertiesRequest,
+    UpdateValuesResponse, ValueRange,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+/// Cell values for an A1 range, row-major.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RangeValues {
+    pub range: String,
+    #[serde(default)]
+    pub values: Vec<Vec<Value>>,
+}
+
+impl RangeValues {
+    pub fn rows(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Width of the first row; Sheets trims trailing empty cells per row.
+    pub fn columns(&self) -> usize {
+        self.values.first().map(Vec::len).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedSpreadsheet {
+    pub spreadsheet_id: String,
+    pub title: Option<String>,
+    pub spreadsheet_url: Option<String>,
+    pub sheets: Vec<SheetSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SheetSummary {
+    pub title: Option<String>,
+    pub sheet_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<i64>,
+}
+
+/// Counts reported by a values write or append.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdatedRange {
+    pub updated_range: Option<String>,
+    pub updated_rows: Option<i64>,
+    pub updated_columns: Option<i64>,
+    pub updated_cells: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchWriteSummary {
+    pub total_updated_rows: Option<i64>,
+    pub total_updated_columns: Option<i64>,
+    pub total_updated_cells: Option<i64>,
+    pub total_updated_sheets: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpreadsheetInfo {
+    pub spreadsheet_id: Option<String>,
+    pub title: Option<String>,
+    pub locale: Option<String>,
+    pub time_zone: Option<String>,
+    pub spreadsheet_url: Option<String>,
+    pub sheets: Vec<SheetInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SheetInfo {
+    pub title: Option<String>,
+    pub sheet_id: Option<i64>,
+    pub index: Option<i64>,
+    pub sheet_type: Option<String>,
+    pub row_count: Option<i64>,
+    pub column_count: Option<i64>,
+    pub frozen_row_count: Option<i64>,
+    pub frozen_column_count: Option<i64>,
+}
+
+/// A sheet created by [`copy_sheet`] in the destination spreadsheet.
+#[derive(Debug, Clone, Serialize)]
+pub struct CopiedSheet {
+    pub destination_spreadsheet_id: String,
+    pub new_sheet_id: Option<i64>,
+    pub new_title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FindReplaceCounts {
+    pub occurrences_changed: i64,
+    pub values_changed: i64,
+    pub sheets_changed: i64,
+    pub formulas_changed: i64,
+}
+
+/// Options for [`find_replace`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FindReplaceOptions {
+    /// Limit the search to one sheet; all sheets when `None`.
+    pub sheet_id: Option<i64>,
+    pub match_case: bool,
+    pub match_entire_cell: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtectedRange {
+    pub protected_range_id: Option<i64>,
+    pub description: Option<String>,
+}
+
+/// Creates a spreadsheet with the named tabs (Sheets' default single tab when
+/// `sheets` is empty) and writes `data` to the first tab from A1.
+pub fn create_spreadsheet(
+    client: &GoogleClient,
+    title: &str,
+    sheets: &[String],
+    data: Option<&[Vec<Value>]>,
+) -> Result<CreatedSpreadsheet, GoogleApiError> {
+    let spreadsheet = Spreadsheet {
+        properties: SpreadsheetProperties {
+            title: Some(title.to_string()),
+            ..Default::default()
+        },
+        sheets: sheets
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Sheet {
+                properties: SheetProperties {
+                    title: Some(name.clone()),
+                    index: Some(i as i64),
+                    ..Default::default()
+                },
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let result: Spreadsheet = client.post(
+        &client.endpoints().sheets_url("spreadsheets"),
+        &[],
+        &spreadsheet,
+    )?;
+
+    let spreadsheet_id = result.spreadsheet_id.ok_or_else(|| {
+        GoogleApiError::Parse("Missing spreadsheetId in create response".to_string())
+    })?;
+
+    let created_sheets = result
+        .sheets
+        .into_iter()
+        .map(|sheet| SheetSummary {
+            title: sheet.properties.title,
+            sheet_id: sheet.properties.sheet_id,
+            index: None,
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(values) = data
+        && !values.is_empty()
+    {
+        let first_sheet = created_sheets
+            .first()
+            .and_then(|sheet| sheet.title.as_deref())
+            .unwrap_or("Sheet1");
+        write_range(
+            client,
+            &spreadsheet_id,
+            &format!("{first_sheet}!A1"),
+            values,
+        )?;
+    }
+
+    Ok(CreatedSpreadsheet {
+        spreadsheet_id,
+        title: result.properties.title,
+        spreadsheet_url: result.spreadsheet_url,
+        sheets: created_sheets,
+    })
+}
+
+/// Reads the values in an A1 `range` such as `Sheet1!A1:C10`.
+pub fn read_range(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    range: &str,
+) -> Result<RangeValues, GoogleApiError> {
+    let result: ValueRange = client.get(
+        &client.endpoints().sheets_url(&format!(
+            "spreadsheets/{}/values/{}",
+            spreadsheet_id,
+            encode_range(range)
+        )),
+        &[],
+    )?;
+
+    Ok(range_values(result, range))
+}
+
+/// Writes `values` starting at `range`, parsing them as if typed by a user.
+pub fn write_range(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    range: &str,
+    values: &[Vec<Value>],
+) -> Result<UpdatedRange, GoogleApiError> {
+    let payload = value_range(range, values);
+
+    let result: UpdateValuesResponse = client.put(
+        &client.endpoints().sheets_url(&format!(
+            "spreadsheets/{}/values/{}",
+            spreadsheet_id,
+            encode_range(range)
+        )),
+        &[("valueInputOption".to_string(), "USER_ENTERED".to_string())],
+        &payload,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Appends `values` as new rows after the table found at `range`.
+pub fn append_rows(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    range: &str,
+    values: &[Vec<Value>],
+) -> Result<UpdatedRange, GoogleApiError> {
+    let payload = value_range(range, values);
+
+    let result: AppendValuesResponse = client.post(
+        &client.endpoints().sheets_url(&format!(
+            "spreadsheets/{}/values/{}:append",
+            spreadsheet_id,
+            encode_range(range)
+        )),
+        &[
+            ("valueInputOption".to_string(), "USER_ENTERED".to_string()),
+            ("insertDataOption".to_string(), "INSERT_ROWS".to_string()),
+        ],
+        &payload,
+    )?;
+
+    Ok(result.updates.into())
+}
+
+/// Clears values (not formatting) in `range`.
+pub fn clear_range(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    range: &str,
+) -> Result<(), GoogleApiError> {
+    client.post_idempotent::<Value>(
+        &client.endpoints().sheets_url(&format!(
+            "spreadsheets/{}/values/{}:clear",
+            spreadsheet_id,
+            encode_range(range)
+        )),
+        &[],
+        &json!({}),
+    )?;
+    Ok(())
+}
+
+/// Reads several ranges in one request, in the order given.
+pub fn batch_read(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    ranges: &[String],
+) -> Result<Vec<RangeValues>, GoogleApiError> {
+    let query = ranges
+        .iter()
+        .map(|range| ("ranges".to_string(), range.clone()))
+        .collect::<Vec<_>>();
+
+    let result: BatchGetValuesResponse = client.get(
+        &client
+            .endpoints()
+            .sheets_url(&format!("spreadsheets/{spreadsheet_id}/values:batchGet")),
+        &query,
+    )?;
+
+    Ok(result
+        .value_ranges
+        .into_iter()
+        .zip(
+            ranges
+                .iter()
+                .map(String::as_str)
+                .chain(std::iter::repeat("")),
+        )
+        .map(|(vr, requested)| range_values(vr, requested))
+        .collect())
+}
+
+/// Writes several ranges in one request.
+pub fn batch_write(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    data: &[RangeValues],
+) -> Result<BatchWriteSummary, GoogleApiError> {
+    let payload = BatchUpdateValuesRequest {
+        value_input_option: "USER_ENTERED".to_string(),
+        data: data
+            .iter()
+            .map(|entry| value_range(&entry.range, &entry.values))
+            .collect(),
+    };
+
+    let result: BatchUpdateValuesResponse = client.post_idempotent(
+        &client
+            .endpoints()
+            .sheets_url(&format!("spreadsheets/{spreadsheet_id}/values:batchUpdate")),
+        &[],
+        &payload,
+    )?;
+
+    Ok(BatchWriteSummary {
+        total_updated_rows: result.total_updated_rows,
+        total_updated_columns: result.total_updated_columns,
+        total_updated_cells: result.total_updated_cells,
+        total_updated_sheets: result.total_updated_sheets,
+    })
+}
+
+/// Spreadsheet properties and the properties of every tab.
+pub fn get_metadata(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+) -> Result<SpreadsheetInfo, GoogleApiError> {
+    let result: Spreadsheet = client.get(
+        &client
+            .endpoints()
+            .sheets_url(&format!("spreadsheets/{spreadsheet_id}")),
+        &[],
+    )?;
+
+    let sheets = result
+        .sheets
+        .into_iter()
+        .map(|sheet| {
+            let props = sheet.properties;
+            let grid = props.grid_properties.unwrap_or_default();
+            SheetInfo {
+                title: props.title,
+                sheet_id: props.sheet_id,
+                index: props.index,
+                sheet_type: props.sheet_type,
+                row_count: grid.row_count,
+                column_count: grid.column_count,
+                frozen_row_count: grid.frozen_row_count,
+                frozen_column_count: grid.frozen_column_count,
+            }
+        })
+        .collect();
+
+    Ok(SpreadsheetInfo {
+        spreadsheet_id: result.spreadsheet_id,
+        title: result.properties.title,
+        locale: result.properties.locale,
+        time_zone: result.properties.time_zone,
+        spreadsheet_url: result.spreadsheet_url,
+        sheets,
+    })
+}
+
+/// Adds a tab titled `title` and returns its properties.
+pub fn add_sheet(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    title: &str,
+) -> Result<SheetSummary, GoogleApiError> {
+    let requests = vec![Request::AddSheet(AddSheetRequest {
+        properties: SheetProperties {
+            title: Some(title.to_string()),
+            ..Default::default()
+        },
+    })];
+
+    let replies = batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    let new_sheet = replies
+        .into_iter()
+        .next()
+        .and_then(|reply| reply.add_sheet)
+        .map(|add| add.properties)
+        .unwrap_or_default();
+
+    Ok(SheetSummary {
+        title: new_sheet.title,
+        sheet_id: new_sheet.sheet_id,
+        index: new_sheet.index,
+    })
+}
+
+pub fn delete_sheet(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+) -> Result<(), GoogleApiError> {
+    let requests = vec![Request::DeleteSheet(DeleteSheetRequest { sheet_id })];
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+pub fn rename_sheet(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    title: &str,
+) -> Result<(), GoogleApiError> {
+    let requests = vec![Request::UpdateSheetProperties(
+        UpdateSheetPropertiesRequest {
+            properties: SheetProperties {
+                sheet_id: Some(sheet_id),
+                title: Some(title.to_string()),
+                ..Default::default()
+            },
+            fields: "title".to_string(),
+        },
+    )];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Copies a tab into `destination_spreadsheet_id`, or into the same
+/// spreadsheet when `None`.
+pub fn copy_sheet(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    destination_spreadsheet_id: Option<&str>,
+) -> Result<CopiedSheet, GoogleApiError> {
+    let destination = destination_spreadsheet_id.unwrap_or(spreadsheet_id);
+
+    let payload = json!({
+        "destinationSpreadsheetId": destination
+    });
+
+    let result: SheetProperties = client.post(
+        &client.endpoints().sheets_url(&format!(
+            "spreadsheets/{spreadsheet_id}/sheets/{sheet_id}:copyTo"
+        )),
+        &[],
+        &payload,
+    )?;
+
+    Ok(CopiedSheet {
+        destination_spreadsheet_id: destination.to_string(),
+        new_sheet_id: result.sheet_id,
+        new_title: result.title,
+    })
+}
+
+/// Applies the formatting described by `format_options` (the snake_case
+/// `bold`, `font_size`, `background_color`, `borders`, ... keys accepted by
+/// the `format` command) to `range`. Only the given options are changed.
+pub fn format_cells(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    range: &str,
+    format_options: &Map<String, Value>,
+) -> Result<(), GoogleApiError> {
+    let grid_range = parse_a1_to_grid_range(range, sheet_id);
+    let cell_format = build_cell_format(format_options);
+    let fields = build_format_fields(format_options);
+
+    let requests = vec![Request::RepeatCell(RepeatCellRequest {
+        range: grid_range,
+        cell: CellData {
+            user_entered_format: Some(cell_format),
+        },
+        fields: format!("userEnteredFormat({fields})"),
+    })];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Merges `range`; `merge_type` is `MERGE_ALL`, `MERGE_COLUMNS` or `MERGE_ROWS`.
+pub fn merge_cells(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    range: &str,
+    merge_type: &str,
+) -> Result<(), GoogleApiError> {
+    let grid_range = parse_a1_to_grid_range(range, sheet_id);
+    let requests = vec![Request::MergeCells(MergeCellsRequest {
+        range: grid_range,
+        merge_type: merge_type.to_string(),
+    })];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+pub fn unmerge_cells(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    range: &str,
+) -> Result<(), GoogleApiError> {
+    let grid_range = parse_a1_to_grid_range(range, sheet_id);
+    let requests = vec![Request::UnmergeCells(UnmergeCellsRequest {
+        range: grid_range,
+    })];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Freezes the first `rows` rows and/or `cols` columns; `None` leaves that
+/// dimension unchanged.
+pub fn freeze(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    rows: Option<i64>,
+    cols: Option<i64>,
+) -> Result<(), GoogleApiError> {
+    let mut fields = Vec::new();
+    if rows.is_some() {
+        fields.push("gridProperties.frozenRowCount");
+    }
+    if cols.is_some() {
+        fields.push("gridProperties.frozenColumnCount");
+    }
+
+    let requests = vec![Request::UpdateSheetProperties(
+        UpdateSheetPropertiesRequest {
+            properties: SheetProperties {
+                sheet_id: Some(sheet_id),
+                grid_properties: Some(GridProperties {
+                    frozen_row_count: rows,
+                    frozen_column_count: cols,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            fields: fields.join(","),
+        },
+    )];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Fits the width of columns `start_col..end_col` (zero-based) to their content.
+pub fn auto_resize(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    start_col: i64,
+    end_col: i64,
+) -> Result<(), GoogleApiError> {
+    let requests = vec![Request::AutoResizeDimensions(AutoResizeDimensionsRequest {
+        dimensions: DimensionRange {
+            sheet_id,
+            dimension: "COLUMNS".to_string(),
+            start_index: start_col,
+            end_index: end_col,
+        },
+    })];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Sorts `range` by the zero-based `sort_column`.
+pub fn sort_range(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    range: &str,
+    sort_column: i64,
+    ascending: bool,
+) -> Result<(), GoogleApiError> {
+    let grid_range = parse_a1_to_grid_range(range, sheet_id);
+
+    let requests = vec![Request::SortRange(SortRangeRequest {
+        range: grid_range,
+        sort_specs: vec![SortSpec {
+            dimension_index: sort_column,
+            sort_order: if ascending { "ASCENDING" } else { "DESCENDING" }.to_string(),
+        }],
+    })];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Replaces `find` with `replace` in cell values across the spreadsheet.
+pub fn find_replace(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    find: &str,
+    replace: &str,
+    options: FindReplaceOptions,
+) -> Result<FindReplaceCounts, GoogleApiError> {
+    let requests = vec![Request::FindReplace(FindReplaceRequest {
+        find: find.to_string(),
+        replacement: replace.to_string(),
+        match_case: options.match_case,
+        match_entire_cell: options.match_entire_cell,
+        search_by_regex: false,
+        include_formulas: false,
+        sheet_id: options.sheet_id,
+        all_sheets: None,
+    })];
+
+    let replies = batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    let counts = replies
+        .into_iter()
+        .next()
+        .and_then(|reply| reply.find_replace)
+        .unwrap_or_default();
+
+    Ok(FindReplaceCounts {
+        occurrences_changed: counts.occurrences_changed,
+        values_changed: counts.values_changed,
+        sheets_changed: counts.sheets_changed,
+        formulas_changed: counts.formulas_changed,
+    })
+}
+
+/// Sets columns `start_col..end_col` (zero-based) to `width` pixels.
+pub fn set_column_width(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    start_col: i64,
+    end_col: i64,
+    width: i64,
+) -> Result<(), GoogleApiError> {
+    set_dimension_size(
+        client,
+        spreadsheet_id,
+        sheet_id,
+        "COLUMNS",
+        start_col,
+        end_col,
+        width,
+    )
+}
+
+/// Sets rows `start_row..end_row` (zero-based) to `height` pixels.
+pub fn set_row_height(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    start_row: i64,
+    end_row: i64,
+    height: i64,
+) -> Result<(), GoogleApiError> {
+    set_dimension_size(
+        client,
+        spreadsheet_id,
+        sheet_id,
+        "ROWS",
+        start_row,
+        end_row,
+        height,
+    )
+}
+
+fn set_dimension_size(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    dimension: &str,
+    start: i64,
+    end: i64,
+    pixels: i64,
+) -> Result<(), GoogleApiError> {
+    let requests = vec![Request::UpdateDimensionProperties(
+        UpdateDimensionPropertiesRequest {
+            range: DimensionRange {
+                sheet_id,
+                dimension: dimension.to_string(),
+                start_index: start,
+                end_index: end,
+            },
+            properties: DimensionProperties {
+                pixel_size: Some(pixels),
+            },
+            fields: "pixelSize".to_string(),
+        },
+    )];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Sets the sheet's basic filter to `range`.
+pub fn add_filter(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    range: &str,
+) -> Result<(), GoogleApiError> {
+    let grid_range = parse_a1_to_grid_range(range, sheet_id);
+    let requests = vec![Request::SetBasicFilter(SetBasicFilterRequest {
+        filter: BasicFilter { range: grid_range },
+    })];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Adds a basic chart of `range` (first row as headers) to the right of the
+/// data and returns its chart ID.
+pub fn add_chart(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    range: &str,
+    chart_type: &str,
+    title: &str,
+) -> Result<Option<i64>, GoogleApiError> {
+    let grid_range = parse_a1_to_grid_range(range, sheet_id);
+    let chart_spec = build_chart_spec(chart_type, title, &grid_range);
+
+    let anchor_col = grid_range.end_column_index.unwrap_or(0) + 1;
+
+    let requests = vec![Request::AddChart(AddChartRequest {
+        chart: EmbeddedChart {
+            chart_id: None,
+            spec: chart_spec,
+            position: Some(EmbeddedObjectPosition {
+                overlay_position: Some(OverlayPosition {
+                    anchor_cell: GridCoordinate {
+                        sheet_id,
+                        row_index: 0,
+                        column_index: anchor_col,
+                    },
+                }),
+            }),
+        },
+    })];
+
+    let replies = batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(replies
+        .into_iter()
+        .next()
+        .and_then(|reply| reply.add_chart)
+        .and_then(|add| add.chart.chart_id))
+}
+
+/// Protects `range`, leaving it editable only by `editors` (emails) and the
+/// owner.
+pub fn protect_range(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    range: &str,
+    description: Option<&str>,
+    editors: &[String],
+) -> Result<ProtectedRange, GoogleApiError> {
+    let grid_range = parse_a1_to_grid_range(range, sheet_id);
+
+    let requests = vec![Request::AddProtectedRange(AddProtectedRangeRequest {
+        protected_range: model::ProtectedRange {
+            range: Some(grid_range),
+            description: description.map(ToString::to_string),
+            warning_only: false,
+            editors: (!editors.is_empty()).then(|| Editors {
+                users: editors.to_vec(),
+            }),
+            ..Default::default()
+        },
+    })];
+
+    let replies = batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    let protected = replies
+        .into_iter()
+        .next()
+        .and_then(|reply| reply.add_protected_range)
+        .map(|add| add.protected_range)
+        .unwrap_or_default();
+
+    Ok(ProtectedRange {
+        protected_range_id: protected.protected_range_id,
+        description: protected.description,
+    })
+}
+
+/// Adds a `boolean` or `gradient` conditional format rule to `range`. See
+/// [`build_conditional_format_rule`] for the accepted `rule_params`.
+pub fn add_conditional_format(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    sheet_id: i64,
+    range: &str,
+    rule_type: &str,
+    rule_params: &Map<String, Value>,
+) -> Result<(), GoogleApiError> {
+    let grid_range = parse_a1_to_grid_range(range, sheet_id);
+    let rule = build_conditional_format_rule(rule_type, &grid_range, rule_params);
+
+    let requests = vec![Request::AddConditionalFormatRule(
+        AddConditionalFormatRuleRequest { rule, index: 0 },
+    )];
+
+    batch_update_spreadsheet(client, spreadsheet_id, requests)?;
+    Ok(())
+}
+
+/// Sends `requests` as one `spreadsheets.batchUpdate` and returns the replies,
+/// one per request.
+pub fn batch_update_spreadsheet(
+    client: &GoogleClient,
+    spreadsheet_id: &str,
+    requests: Vec<Request>,
+) -> Result<Vec<Response>, GoogleApiError> {
+    let result: BatchUpdateSpreadsheetResponse = client.post(
+        &client
+            .endpoints()
+            .sheets_url(&format!("spreadsheets/{spreadsheet_id}:batchUpdate")),
+        &[],
+        &BatchUpdateSpreadsheetRequest { requests },
+    )?;
+
+    Ok(result.replies)
+}
+
+/// Converts an A1 range (`Sheet1!B2:D10`, `A:C`, `C5`) into a `GridRange` on
+/// `sheet_id`. The sheet name prefix is ignored; open-ended ranges leave the
+/// corresponding bounds unset.
+pub fn parse_a1_to_grid_range(range: &str, sheet_id: i64) -> GridRange {
+    let cell_range = if let Some((_, suffix)) = range.split_once('!') {
+        suffix
+    } else {
+        range
+    };
+
+    let mut grid = GridRange {
+        sheet_id,
+        ..Default::default()
+    };
+
+    if let Some((start_ref, end_ref)) = cell_range.split_once(':') {
+        let (start_col, start_row) = parse_cell_ref(start_ref);
+        let (end_col, end_row) = parse_cell_ref(end_ref);
+
+        grid.start_column_index = start_col;
+        grid.start_row_index = start_row;
+        grid.end_column_index = end_col.map(|col| col + 1);
+        grid.end_row_index = end_row.map(|row| row + 1);
+    } else {
+        let (col, row) = parse_cell_ref(cell_range);
+        grid.start_column_index = col;
+        grid.end_column_index = col.map(|col| col + 1);
+        grid.start_row_index = row;
+        grid.end_row_index = row.map(|row| row + 1);
+    }
+
+    grid
+}
+
+/// Splits a cell reference like `AB12` into zero-based (column, row).
+pub fn parse_cell_ref(reference: &str) -> (Option<i64>, Option<i64>) {
+    let mut letters = String::new();
+    let mut numbers = String::new();
+
+    for ch in reference.chars() {
+        if ch.is_ascii_alphabetic() && numbers.is_empty() {
+            letters.push(ch.to_ascii_uppercase());
+        } else if ch.is_ascii_digit() {
+            numbers.push(ch);
+        }
+    }
+
+    let col = if letters.is_empty() {
+        None
+    } else {
+        Some(col_letters_to_index(&letters))
+    };
+
+    let row = if numbers.is_empty() {
+        None
+    } else {
+        numbers.parse::<i64>().ok().map(|r| r - 1)
+    };
+
+    (col, row)
+}
+
+/// `A` is 0, `Z` is 25, `AA` is 26.
+pub fn col_letters_to_index(letters: &str) -> i64 {
+    let mut result = 0i64;
+    for c in letters.chars() {
+        result = result * 26 + (c as i64 - 'A' as i64 + 1);
+    }
+    result - 1
+}
+
+/// Builds a `CellFormat` from the `format` command's snake_case options.
+pub fn build_cell_format(options: &Map<String, Value>) -> Value {
+    let mut format = Map::new();
+    let mut text_format = Map::new();
+
+    for key in ["bold", "italic", "underline"] {
+        if let Some(value) = options.get(key) {
+            text_format.insert(key.to_string(), value.clone());
+        }
+    }
+
+    if let Some(font_size) = options.get("font_size") {
+        text_format.insert("fontSize".to_string(), font_size.clone());
+    }
+    if let Some(font_family) = options.get("font_family") {
+        text_format.insert("fontFamily".to_string(), font_family.clone());
+    }
+    if let Some(foreground) = options.get("foreground_color") {
+        text_format.insert(
+            "foregroundColorStyle".to_string(),
+            json!({"rgbColor": foreground}),
+        );
+    }
+
+    if !text_format.is_empty() {
+        format.insert("textFormat".to_string(), Value::Object(text_format));
+    }
+
+    if let Some(background) = options.get("background_color") {
+        format.insert(
+            "backgroundColorStyle".to_string(),
+            json!({"rgbColor": background}),
+        );
+    }
+    if let Some(horizontal) = options.get("horizontal_alignment") {
+        format.insert("horizontalAlignment".to_string(), horizontal.clone());
+    }
+    if let Some(vertical) = options.get("vertical_alignment") {
+        format.insert("verticalAlignment".to_string(), vertical.clone());
+    }
+
+    if let Some(number_format) = options.get("number_format").and_then(|v| v.as_object()) {
+        format.insert(
+            "numberFormat".to_string(),
+            json!({
+                "type": number_format.get("type"),
+                "pattern": number_format.get("pattern")
+            }),
+        );
+    }
+
+    if let Some(wrap) = options.get("wrap_strategy") {
+        format.insert("wrapStrategy".to_string(), wrap.clone());
+    }
+
+    if let Some(rotation) = options.get("text_rotation") {
+        format.insert("textRotation".to_string(), json!({"angle": rotation}));
+    }
+
+    if let Some(borders) = options.get("borders").and_then(|v| v.as_object()) {
+        format.insert("borders".to_string(), build_borders(borders));
+    }
+
+    Value::Object(format)
+}
+
+/// The `userEnteredFormat` field mask matching [`build_cell_format`].
+pub fn build_format_fields(options: &Map<String, Value>) -> String {
+    let mut fields = Vec::new();
+    if options.contains_key("bold") {
+        fields.push("textFormat.bold");
+    }
+    if options.contains_key("italic") {
+        fields.push("textFormat.italic");
+    }
+    if options.contains_key("underline") {
+        fields.push("textFormat.underline");
+    }
+    if options.contains_key("font_size") {
+        fields.push("textFormat.fontSize");
+    }
+    if options.contains_key("font_family") {
+        fields.push("textFormat.fontFamily");
+    }
+    if options.contains_key("foreground_color") {
+        fields.push("textFormat.foregroundColorStyle");
+    }
+    if options.contains_key("background_color") {
+        fields.push("backgroundColorStyle");
+    }
+    if options.contains_key("horizontal_alignment") {
+        fields.push("horizontalAlignment");
+    }
+    if options.contains_key("vertical_alignment") {
+        fields.push("verticalAlignment");
+    }
+    if options.contains_key("number_format") {
+        fields.push("numberFormat");
+    }
+    if options.contains_key("wrap_strategy") {
+        fields.push("wrapStrategy");
+    }
+    if options.contains_key("text_rotation") {
+        fields.push("textRotation");
+    }
+    if options.contains_key("borders") {
+        fields.push("borders");
+    }
+    fields.join(",")
+}
+
+fn build_borders(border_config: &Map<String, Value>) -> Value {
+    let mut borders = Map::new();
+
+    for side in ["top", "bottom", "left", "right"] {
+        let Some(side_cfg) = border_config.get(side).and_then(|v| v.as_object()) else {
+            continue;
+        };
+
+        let mut border = Map::new();
+        border.insert(
+            "style".to_string(),
+            side_cfg
+                .get("style")
+                .cloned()
+                .unwrap_or(Value::String("SOLID".to_string())),
+        );
+        if let Some(color) = side_cfg.get("color") {
+            border.insert("colorStyle".to_string(), json!({"rgbColor": color}));
+        }
+
+        borders.insert(side.to_string(), Value::Object(border));
+    }
+
+    Value::Object(borders)
+}
+
+/// A `ChartSpec` for a basic chart (`bar`, `line`, `column`, ...) over
+/// `grid_range`.
+pub fn build_chart_spec(chart_type: &str, title: &str, grid_range: &GridRange) -> Value {
+    json!({
+        "title": title,
+        "basicChart": {
+            "chartType": chart_type.to_uppercase(),
+            "legendPosition": "BOTTOM_LEGEND",
+            "domains": [{
+                "domain": {
+                    "sourceRange": {"sources": [grid_range]}
+                }
+            }],
+            "series": [{
+                "series": {
+                    "sourceRange": {"sources": [grid_range]}
+                },
+                "targetAxis": "LEFT_AXIS"
+            }],
+            "headerCount": 1
+        }
+    })
+}
+
+/// A `ConditionalFormatRule` on `grid_range`.
+///
+/// `boolean` rules read `condition_type`, `condition_values`,
+/// `format_background_color`, `format_foreground_color` and `format_bold`;
+/// `gradient` rules read `min_color`/`min_type`, `max_color`/`max_type` and
+/// optionally `mid_color`/`mid_type`/`mid_value`.
+pub fn build_conditional_format_rule(
+    rule_type: &str,
+    grid_range: &GridRange,
+    params: &Map<String, Value>,
+) -> Value {
+    let mut rule = json!({
+        "ranges": [grid_range]
+    });
+
+    if rule_type.eq_ignore_ascii_case("boolean") {
+        let condition_type = params
+            .get("condition_type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("NUMBER_GREATER");
+
+        let values = params
+            .get("condition_values")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|v| {
+                let s = match v {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                json!({"userEnteredValue": s})
+            })
+            .collect::<Vec<_>>();
+
+        let mut format = Map::new();
+        if let Some(bg) = params.get("format_background_color") {
+            format.insert("backgroundColorStyle".to_string(), json!({"rgbColor": bg}));
+        }
+
+        let mut text_format = Map::new();
+        if let Some(bold) = params.get("format_bold").and_then(|v| v.as_bool())
+            && bold
+        {
+            text_format.insert("bold".to_string(), Value::Bool(true));
+        }
+        if let Some(fg) = params.get("format_foreground_color") {
+            text_format.insert("foregroundColorStyle".to_string(), json!({"rgbColor": fg}));
+        }
+        if !text_format.is_empty() {
+            format.insert("textFormat".to_string(), Value::Object(text_format));
+        }
+
+        let mut boolean_rule = json!({
+            "condition": {
+                "type": condition_type,
+            },
+            "format": format
+        });
+
+        if !values.is_empty() {
+            boolean_rule
+                .as_object_mut()
+                .expect("object")
+                .get_mut("condition")
+                .and_then(|c| c.as_object_mut())
+                .expect("condition")
+                .insert("values".to_string(), Value::Array(values));
+        }
+
+        rule.as_object_mut()
+            .expect("object")
+            .insert("booleanRule".to_string(), boolean_rule);
+    } else if rule_type.eq_ignore_ascii_case("gradient") {
+        let min_color = params.get("min_color").cloned().unwrap_or(json!({
+            "red": 0.8,
+            "green": 0.2,
+            "blue": 0.2
+        }));
+        let max_color = params.get("max_color").cloned().unwrap_or(json!({
+            "red": 0.2,
+            "green": 0.8,
+            "blue": 0.2
+        }));
+
+        let mut gradient_rule = json!({
+            "minpoint": {
+                "colorStyle": {"rgbColor": min_color},
+                "type": params.get("min_type").cloned().unwrap_or(Value::String("MIN".to_string()))
+            },
+            "maxpoint": {
+                "colorStyle": {"rgbColor": max_color},
+                "type": params.get("max_type").cloned().unwrap_or(Value::String("MAX".to_string()))
+            }
+        });
+
+        if let Some(mid_color) = params.get("mid_color") {
+            gradient_rule
+                .as_object_mut()
+                .expect("object")
+                .insert(
+                    "midpoint".to_string(),
+                    json!({
+                        "colorStyle": {"rgbColor": mid_color},
+                        "type": params.get("mid_type").cloned().unwrap_or(Value::String("PERCENTILE".to_string())),
+                        "value": params.get("mid_value").cloned().unwrap_or(Value::String("50".to_string()))
+                    }),
+                );
+        }
+
+        rule.as_object_mut()
+            .expect("object")
+            .insert("gradientRule".to_string(), gradient_rule);
+    }
+
+    rule
+}
+
+fn range_values(value_range: ValueRange, requested: &str) -> RangeValues {
+    RangeValues {
+        range: value_range.range.unwrap_or_else(|| requested.to_string()),
+        values: value_range.values,
+    }
+}
+
+fn value_range(range: &str, values: &[Vec<Value>]) -> ValueRange {
+    ValueRange {
+        range: Some(range.to_string()),
+        major_dimension: None,
+        values: values.to_vec(),
+    }
+}
+
+impl From<UpdateValuesResponse> for UpdatedRange {
+    fn from(response: UpdateValuesResponse) -> Self {
+        Self {
+            updated_range: response.updated_range,
+            updated_rows: response.updated_rows,
+            updated_columns: response.updated_columns,
+            updated_cells: response.updated_cells,
+        }
+    }
+}
+
+fn encode_range(range: &str) -> String {
+    urlencoding::encode(range).to_string()
+}
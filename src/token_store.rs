@@ -0,0 +1,358 @@
+use crate::auth::{
+    StoredToken, delete_stored_token, is_encrypted_entry, load_stored_token, read_token_entry,
+    save_stored_token, token_file_profiles, write_token_entry,
+};
+use crate::config::{TokenBackend, TokenStoreSettings};
+use anyhow::{Context, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const PASSPHRASE_ENV: &str = "GOOGLE_SKILL_TOKEN_PASSPHRASE";
+const ENVELOPE_PREFIX: &str = "enc:v1";
+const KDF_ITERATIONS: u32 = 200_000;
+const SALT_LEN: usize = 16;
+const SECRET_SERVICE_NAME: &str = "google-docs-skill";
+
+/// Why a token could not be read or written, carried inside the
+/// `anyhow::Error` the stores return. Only [`NotFound`](Self::NotFound) means
+/// the profile has to log in; the others need the store itself fixed.
+#[derive(Debug, thiserror::Error)]
+pub enum TokenStoreError {
+    #[error("{0}")]
+    NotFound(String),
+    /// The passphrase for the encrypted store is missing or wrong.
+    #[error("{0}")]
+    Passphrase(String),
+    /// The backend cannot be reached, e.g. `secret-tool` is not installed.
+    #[error("{0}")]
+    Unavailable(String),
+    /// A stored entry cannot be parsed or decoded.
+    #[error("{0}")]
+    Corrupt(String),
+}
+
+impl TokenStoreError {
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "AUTH_REQUIRED",
+            Self::Passphrase(_) => "TOKEN_PASSPHRASE",
+            Self::Unavailable(_) => "TOKEN_STORE_UNAVAILABLE",
+            Self::Corrupt(_) => "TOKEN_CORRUPT",
+        }
+    }
+}
+
+/// Backend that persists OAuth tokens, one entry per profile.
+pub trait TokenStore {
+    /// Short backend name reported by `auth status`.
+    fn name(&self) -> &'static str;
+    fn load(&self, profile: &str) -> Result<StoredToken>;
+    fn save(&self, profile: &str, token: &StoredToken) -> Result<()>;
+    /// Returns whether an entry existed.
+    fn delete(&self, profile: &str) -> Result<bool>;
+    fn profiles(&self) -> Result<Vec<String>>;
+}
+
+/// Opens the backend selected in the config file at `config_path`.
+/// `token_path` is the YAML token file, which the file backend uses directly
+/// and the keyring backend migrates from.
+pub fn open_token_store(token_path: &Path, config_path: &Path) -> Result<Box<dyn TokenStore>> {
+    let settings = TokenStoreSettings::resolve(config_path)?;
+    let passphrase = std::env::var(PASSPHRASE_ENV).ok().filter(|v| !v.is_empty());
+    if settings.encrypt && passphrase.is_none() {
+        return Err(TokenStoreError::Passphrase(format!(
+            "Token encryption is enabled but {PASSPHRASE_ENV} is not set"
+        ))
+        .into());
+    }
+
+    let file = FileTokenStore {
+        path: token_path.to_path_buf(),
+        passphrase: passphrase.filter(|_| settings.encrypt),
+    };
+
+    match settings.backend {
+        TokenBackend::File => Ok(Box::new(file)),
+        TokenBackend::SecretService => {
+            if !cfg!(target_os = "linux") {
+                return Err(TokenStoreError::Unavailable(
+                    "The secret-service token store is only available on Linux".to_string(),
+                )
+                .into());
+            }
+            Ok(Box::new(SecretServiceTokenStore { legacy: file }))
+        }
+    }
+}
+
+/// Tokens in the YAML token file, optionally sealed with ChaCha20-Poly1305
+/// under a PBKDF2-derived key. Plain entries found while encryption is on are
+/// re-written encrypted on first load.
+pub struct FileTokenStore {
+    path: PathBuf,
+    passphrase: Option<String>,
+}
+
+impl TokenStore for FileTokenStore {
+    fn name(&self) -> &'static str {
+        if self.passphrase.is_some() {
+            "encrypted-file"
+        } else {
+            "file"
+        }
+    }
+
+    fn load(&self, profile: &str) -> Result<StoredToken> {
+        let Some(passphrase) = &self.passphrase else {
+            return load_stored_token(&self.path, profile);
+        };
+
+        let entry = read_token_entry(&self.path, profile)?.ok_or_else(|| {
+            TokenStoreError::NotFound(format!("No token stored for profile '{profile}'"))
+        })?;
+        if is_encrypted_entry(&entry) {
+            let plain = open_envelope(&entry, passphrase, profile)?;
+            return serde_json::from_str(&plain).with_context(|| {
+                TokenStoreError::Corrupt(format!("Failed parsing decrypted {profile} token"))
+            });
+        }
+
+        let token: StoredToken = serde_json::from_str(&entry).with_context(|| {
+            TokenStoreError::Corrupt(format!("Failed parsing {profile} token payload as JSON"))
+        })?;
+        self.save(profile, &token)?;
+        Ok(token)
+    }
+
+    fn save(&self, profile: &str, token: &StoredToken) -> Result<()> {
+        let Some(passphrase) = &self.passphrase else {
+            return save_stored_token(&self.path, profile, token);
+        };
+
+        let payload =
+            serde_json::to_string(token).context("Failed serializing token JSON payload")?;
+        write_token_entry(
+            &self.path,
+            profile,
+            seal_envelope(&payload, passphrase, profile)?,
+        )
+    }
+
+    fn delete(&self, profile: &str) -> Result<bool> {
+        delete_stored_token(&self.path, profile)
+    }
+
+    fn profiles(&self) -> Result<Vec<String>> {
+        token_file_profiles(&self.path)
+    }
+}
+
+/// Tokens in the desktop keyring, reached through libsecret's `secret-tool`.
+/// A profile missing from the keyring is migrated from the token file the
+/// first time it is loaded, and its file entry removed.
+pub struct SecretServiceTokenStore {
+    legacy: FileTokenStore,
+}
+
+impl TokenStore for SecretServiceTokenStore {
+    fn name(&self) -> &'static str {
+        "secret-service"
+    }
+
+    fn load(&self, profile: &str) -> Result<StoredToken> {
+        if let Some(secret) = secret_tool_lookup(profile)? {
+            return serde_json::from_str(&secret).with_context(|| {
+                TokenStoreError::Corrupt(format!(
+                    "Failed parsing keyring token for profile '{profile}'"
+                ))
+            });
+        }
+
+        let token = self.legacy.load(profile)?;
+        self.save(profile, &token)?;
+        self.legacy.delete(profile)?;
+        Ok(token)
+    }
+
+    fn save(&self, profile: &str, token: &StoredToken) -> Result<()> {
+        let payload =
+            serde_json::to_string(token).context("Failed serializing token JSON payload")?;
+        let mut child = secret_tool()
+            .args([
+                "store",
+                "--label",
+                &format!("Google skill token ({profile})"),
+                "service",
+                SECRET_SERVICE_NAME,
+                "profile",
+                profile,
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(secret_tool_missing)?;
+        child
+            .stdin
+            .take()
+            .context("Failed opening secret-tool stdin")?
+            .write_all(payload.as_bytes())
+            .context("Failed passing token to secret-tool")?;
+
+        let output = child
+            .wait_with_output()
+            .context("secret-tool store failed")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "secret-tool store failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    fn delete(&self, profile: &str) -> Result<bool> {
+        let existed = secret_tool_lookup(profile)?.is_some();
+        if existed {
+            let status = secret_tool()
+                .args(["clear", "service", SECRET_SERVICE_NAME, "profile", profile])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .with_context(secret_tool_missing)?;
+            if !status.success() {
+                return Err(anyhow!("secret-tool clear failed for profile '{profile}'"));
+            }
+        }
+        let legacy = self.legacy.delete(profile)?;
+        Ok(existed || legacy)
+    }
+
+    fn profiles(&self) -> Result<Vec<String>> {
+        let output = secret_tool()
+            .args(["search", "--all", "service", SECRET_SERVICE_NAME])
+            .stderr(Stdio::piped())
+            .output()
+            .with_context(secret_tool_missing)?;
+
+        // secret-tool prints item attributes on stderr and secrets on stdout.
+        let listing = String::from_utf8_lossy(&output.stderr).to_string()
+            + &String::from_utf8_lossy(&output.stdout);
+        let mut names: Vec<String> = listing
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("attribute.profile = "))
+            .map(ToString::to_string)
+            .collect();
+        names.extend(self.legacy.profiles()?);
+        Ok(names)
+    }
+}
+
+fn secret_tool() -> Command {
+    Command::new("secret-tool")
+}
+
+fn secret_tool_missing() -> TokenStoreError {
+    TokenStoreError::Unavailable("Failed to run secret-tool; install libsecret-tools".to_string())
+}
+
+fn secret_tool_lookup(profile: &str) -> Result<Option<String>> {
+    let output = secret_tool()
+        .args(["lookup", "service", SECRET_SERVICE_NAME, "profile", profile])
+        .stderr(Stdio::null())
+        .output()
+        .with_context(secret_tool_missing)?;
+
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((output.status.success() && !secret.is_empty()).then_some(secret))
+}
+
+/// Encrypts `plain` as `enc:v1:<iterations>:<salt>:<nonce>:<ciphertext>`,
+/// binding the profile name as associated data.
+fn seal_envelope(plain: &str, passphrase: &str, profile: &str) -> Result<String> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| anyhow!("Failed generating token encryption nonce"))?;
+
+    let key = derive_key(passphrase, &salt, KDF_ITERATIONS)?;
+    let mut sealed = plain.as_bytes().to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(profile.as_bytes()),
+        &mut sealed,
+    )
+    .map_err(|_| anyhow!("Failed encrypting token"))?;
+
+    Ok(format!(
+        "{ENVELOPE_PREFIX}:{KDF_ITERATIONS}:{}:{}:{}",
+        URL_SAFE_NO_PAD.encode(salt),
+        URL_SAFE_NO_PAD.encode(nonce),
+        URL_SAFE_NO_PAD.encode(sealed)
+    ))
+}
+
+fn open_envelope(envelope: &str, passphrase: &str, profile: &str) -> Result<String> {
+    let malformed = || {
+        TokenStoreError::Corrupt(format!(
+            "Encrypted token for profile '{profile}' is malformed"
+        ))
+    };
+
+    let rest = envelope
+        .strip_prefix(ENVELOPE_PREFIX)
+        .and_then(|r| r.strip_prefix(':'))
+        .ok_or_else(malformed)?;
+    let parts: Vec<&str> = rest.split(':').collect();
+    let [iterations, salt, nonce, sealed] = parts[..] else {
+        return Err(malformed().into());
+    };
+    let iterations: u32 = iterations.parse().map_err(|_| malformed())?;
+    let salt = URL_SAFE_NO_PAD.decode(salt).map_err(|_| malformed())?;
+    let nonce: [u8; NONCE_LEN] = URL_SAFE_NO_PAD
+        .decode(nonce)
+        .ok()
+        .and_then(|n| n.try_into().ok())
+        .ok_or_else(malformed)?;
+    let mut sealed = URL_SAFE_NO_PAD.decode(sealed).map_err(|_| malformed())?;
+
+    let key = derive_key(passphrase, &salt, iterations)?;
+    let plain = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(profile.as_bytes()),
+            &mut sealed,
+        )
+        .map_err(|_| {
+            TokenStoreError::Passphrase(format!(
+                "Failed decrypting token for profile '{profile}'; wrong {PASSPHRASE_ENV}?"
+            ))
+        })?;
+
+    Ok(String::from_utf8(plain.to_vec()).map_err(|_| malformed())?)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey> {
+    let iterations =
+        NonZeroU32::new(iterations).ok_or_else(|| anyhow!("Invalid key derivation settings"))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    UnboundKey::new(&CHACHA20_POLY1305, &key)
+        .map(LessSafeKey::new)
+        .map_err(|_| anyhow!("Failed preparing token encryption key"))
+}
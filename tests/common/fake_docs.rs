@@ -0,0 +1,789 @@
+//! In-memory model of a Google Docs body, indexed in UTF-16 code units like the
+//! real API. Only the request types the binaries send are implemented.
+
+use serde_json::{Map, Value, json};
+
+#[derive(Debug, Clone)]
+enum Unit {
+    Text {
+        ch: char,
+        style: Map<String, Value>,
+    },
+    Newline {
+        style: Map<String, Value>,
+        paragraph_style: Map<String, Value>,
+        bullet: Option<Value>,
+    },
+    Image {
+        uri: String,
+    },
+    PageBreak,
+    TableStart,
+    RowStart,
+    CellStart,
+    TableEnd,
+}
+
+impl Unit {
+    fn width(&self) -> i64 {
+        match self {
+            Unit::Text { ch, .. } => ch.len_utf16() as i64,
+            _ => 1,
+        }
+    }
+
+    fn is_table_marker(&self) -> bool {
+        matches!(
+            self,
+            Unit::TableStart | Unit::RowStart | Unit::CellStart | Unit::TableEnd
+        )
+    }
+
+    fn newline() -> Self {
+        Unit::Newline {
+            style: Map::new(),
+            paragraph_style: Map::new(),
+            bullet: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeDocument {
+    pub id: String,
+    pub title: String,
+    pub revision: u64,
+    units: Vec<Unit>,
+    lists: Map<String, Value>,
+}
+
+pub type DocResult<T> = Result<T, String>;
+
+impl FakeDocument {
+    pub fn new(id: &str, title: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            revision: 1,
+            units: vec![Unit::newline()],
+            lists: Map::new(),
+        }
+    }
+
+    pub fn revision_id(&self) -> String {
+        format!("rev-{}", self.revision)
+    }
+
+    /// Plain text of the body with table markers dropped.
+    pub fn plain_text(&self) -> String {
+        self.units
+            .iter()
+            .filter_map(|u| match u {
+                Unit::Text { ch, .. } => Some(*ch),
+                Unit::Newline { .. } => Some('\n'),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn end_index(&self) -> i64 {
+        1 + self.units.iter().map(Unit::width).sum::<i64>()
+    }
+
+    fn position(&self, index: i64) -> DocResult<usize> {
+        if index < 1 {
+            return Err(format!("Index {index} must be at least 1."));
+        }
+        let mut current = 1i64;
+        for (pos, unit) in self.units.iter().enumerate() {
+            if current == index {
+                return Ok(pos);
+            }
+            current += unit.width();
+            if current > index {
+                return Err(format!("Index {index} splits a surrogate pair"));
+            }
+        }
+        if current == index {
+            return Ok(self.units.len());
+        }
+        Err(format!(
+            "Index {index} must be less than the end index of the referenced segment, {}.",
+            self.end_index()
+        ))
+    }
+
+    fn index_of(&self, pos: usize) -> i64 {
+        1 + self.units[..pos].iter().map(Unit::width).sum::<i64>()
+    }
+
+    fn paragraph_bounds(&self, pos: usize) -> (usize, usize) {
+        let mut start = pos.min(self.units.len().saturating_sub(1));
+        while start > 0 && !self.is_paragraph_break(start - 1) {
+            start -= 1;
+        }
+        let mut end = pos;
+        while end < self.units.len() && !matches!(self.units[end], Unit::Newline { .. }) {
+            end += 1;
+        }
+        (start, end)
+    }
+
+    fn is_paragraph_break(&self, pos: usize) -> bool {
+        matches!(self.units[pos], Unit::Newline { .. }) || self.units[pos].is_table_marker()
+    }
+
+    fn insertion_position(&self, location: &Value) -> DocResult<usize> {
+        if let Some(index) = location.get("index").and_then(Value::as_i64) {
+            let pos = self.position(index)?;
+            match self.units.get(pos) {
+                Some(unit) if !unit.is_table_marker() => Ok(pos),
+                _ => Err(
+                    "The insertion index must be inside the bounds of an existing paragraph."
+                        .to_string(),
+                ),
+            }
+        } else {
+            Ok(self.units.len() - 1)
+        }
+    }
+
+    /// Applies one batchUpdate request and returns its reply.
+    pub fn apply(&mut self, request: &Value) -> DocResult<Value> {
+        let (kind, body) = request
+            .as_object()
+            .and_then(|o| o.iter().next())
+            .ok_or_else(|| "Empty request".to_string())?;
+
+        match kind.as_str() {
+            "insertText" => {
+                let location = body
+                    .get("location")
+                    .or_else(|| body.get("endOfSegmentLocation"))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                let pos = self.insertion_position(&location)?;
+                let text = body.get("text").and_then(Value::as_str).unwrap_or_default();
+                self.insert_text_at(pos, text);
+                Ok(json!({}))
+            }
+            "deleteContentRange" => {
+                let (start, end) = range_of(body)?;
+                self.delete_range(start, end)?;
+                Ok(json!({}))
+            }
+            "replaceAllText" => {
+                let find = body
+                    .get("containsText")
+                    .and_then(|c| c.get("text"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let match_case = body
+                    .get("containsText")
+                    .and_then(|c| c.get("matchCase"))
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                let replace = body
+                    .get("replaceText")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let changed = self.replace_all(find, replace, match_case);
+                Ok(json!({"replaceAllText": {"occurrencesChanged": changed}}))
+            }
+            "updateTextStyle" => {
+                let (start, end) = range_of(body)?;
+                let style = body.get("textStyle").cloned().unwrap_or(json!({}));
+                let fields = body.get("fields").and_then(Value::as_str).unwrap_or("*");
+                self.update_text_style(start, end, &style, fields)?;
+                Ok(json!({}))
+            }
+            "updateParagraphStyle" => {
+                let (start, end) = range_of(body)?;
+                let style = body.get("paragraphStyle").cloned().unwrap_or(json!({}));
+                let fields = body.get("fields").and_then(Value::as_str).unwrap_or("*");
+                self.update_paragraph_style(start, end, &style, fields)?;
+                Ok(json!({}))
+            }
+            "insertPageBreak" => {
+                let location = body.get("location").cloned().unwrap_or(Value::Null);
+                let pos = self.insertion_position(&location)?;
+                self.units.insert(pos, Unit::PageBreak);
+                Ok(json!({}))
+            }
+            "insertInlineImage" => {
+                let location = body.get("location").cloned().unwrap_or(Value::Null);
+                let pos = self.insertion_position(&location)?;
+                let uri = body.get("uri").and_then(Value::as_str).unwrap_or_default();
+                self.units.insert(
+                    pos,
+                    Unit::Image {
+                        uri: uri.to_string(),
+                    },
+                );
+                Ok(json!({"insertInlineImage": {"objectId": format!("kix.{pos}")}}))
+            }
+            "insertTable" => {
+                let location = body
+                    .get("location")
+                    .or_else(|| body.get("endOfSegmentLocation"))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                let pos = self.insertion_position(&location)?;
+                let rows = body.get("rows").and_then(Value::as_i64).unwrap_or(1);
+                let cols = body.get("columns").and_then(Value::as_i64).unwrap_or(1);
+                let mut table = vec![Unit::newline(), Unit::TableStart];
+                for _ in 0..rows {
+                    table.push(Unit::RowStart);
+                    for _ in 0..cols {
+                        table.push(Unit::CellStart);
+                        table.push(Unit::newline());
+                    }
+                }
+                table.push(Unit::TableEnd);
+                self.units.splice(pos..pos, table);
+                Ok(json!({}))
+            }
+            "insertTableRow" | "deleteTableRow" => {
+                let location = body
+                    .get("tableCellLocation")
+                    .ok_or("Missing tableCellLocation")?;
+                let table_start = location
+                    .get("tableStartLocation")
+                    .and_then(|l| l.get("index"))
+                    .and_then(Value::as_i64)
+                    .ok_or("Missing tableStartLocation")?;
+                let row_index = location
+                    .get("rowIndex")
+                    .and_then(Value::as_i64)
+                    .unwrap_or(0);
+                let (row_start, row_end) = self.table_row(table_start, row_index)?;
+                if kind == "deleteTableRow" {
+                    self.units.drain(row_start..row_end);
+                } else {
+                    let cells = self.units[row_start..row_end]
+                        .iter()
+                        .filter(|u| matches!(u, Unit::CellStart))
+                        .count();
+                    let mut row = vec![Unit::RowStart];
+                    for _ in 0..cells {
+                        row.push(Unit::CellStart);
+                        row.push(Unit::newline());
+                    }
+                    let at = if body.get("insertBelow").and_then(Value::as_bool) == Some(true) {
+                        row_end
+                    } else {
+                        row_start
+                    };
+                    self.units.splice(at..at, row);
+                }
+                Ok(json!({}))
+            }
+            "createParagraphBullets" => {
+                let (start, end) = range_of(body)?;
+                let preset = body
+                    .get("bulletPreset")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                self.create_bullets(start, end, preset)?;
+                Ok(json!({}))
+            }
+            other => Err(format!("Unsupported request in fake server: {other}")),
+        }
+    }
+
+    /// Unit positions spanning row `row_index` of the table starting at
+    /// `table_start`. Nested tables are not supported.
+    fn table_row(&self, table_start: i64, row_index: i64) -> DocResult<(usize, usize)> {
+        let start = self.position(table_start)?;
+        if !matches!(self.units.get(start), Some(Unit::TableStart)) {
+            return Err(format!("No table starts at index {table_start}"));
+        }
+        let end = start
+            + self.units[start..]
+                .iter()
+                .position(|u| matches!(u, Unit::TableEnd))
+                .ok_or("Unterminated table")?;
+        let rows: Vec<usize> = (start..end)
+            .filter(|&pos| matches!(self.units[pos], Unit::RowStart))
+            .collect();
+        let row = usize::try_from(row_index)
+            .ok()
+            .and_then(|i| rows.get(i).copied())
+            .ok_or_else(|| format!("Invalid rowIndex {row_index}"))?;
+        let row_end = rows.iter().copied().find(|&pos| pos > row).unwrap_or(end);
+        Ok((row, row_end))
+    }
+
+    fn insert_text_at(&mut self, pos: usize, text: &str) {
+        let (_, para_end) = self.paragraph_bounds(pos);
+        let paragraph_style = match self.units.get(para_end) {
+            Some(Unit::Newline {
+                paragraph_style, ..
+            }) => paragraph_style.clone(),
+            _ => Map::new(),
+        };
+
+        let units = text.chars().map(|ch| {
+            if ch == '\n' {
+                Unit::Newline {
+                    style: Map::new(),
+                    paragraph_style: paragraph_style.clone(),
+                    bullet: None,
+                }
+            } else {
+                Unit::Text {
+                    ch,
+                    style: Map::new(),
+                }
+            }
+        });
+        let units: Vec<Unit> = units.collect();
+        self.units.splice(pos..pos, units);
+    }
+
+    fn delete_range(&mut self, start: i64, end: i64) -> DocResult<()> {
+        if end <= start {
+            return Err("Invalid range: endIndex must be greater than startIndex".to_string());
+        }
+        if end >= self.end_index() {
+            return Err(
+                "Invalid requests[0].deleteContentRange: The range cannot include the newline character at the end of the segment."
+                    .to_string(),
+            );
+        }
+        let from = self.position(start)?;
+        let to = self.position(end)?;
+
+        let mut depth = 0i64;
+        for unit in &self.units[from..to] {
+            match unit {
+                Unit::TableStart => depth += 1,
+                Unit::TableEnd => {
+                    depth -= 1;
+                    if depth < 0 {
+                        return Err("Cannot partially delete a table".to_string());
+                    }
+                }
+                Unit::RowStart | Unit::CellStart if depth == 0 => {
+                    return Err("Cannot delete across table cell boundaries".to_string());
+                }
+                _ => {}
+            }
+        }
+        if depth != 0 {
+            return Err("Cannot partially delete a table".to_string());
+        }
+
+        self.units.drain(from..to);
+        Ok(())
+    }
+
+    fn replace_all(&mut self, find: &str, replace: &str, match_case: bool) -> i64 {
+        if find.is_empty() {
+            return 0;
+        }
+        let normalize = |c: char| {
+            if match_case {
+                c
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        };
+        let needle: Vec<char> = find.chars().map(normalize).collect();
+
+        let mut matches = Vec::new();
+        let mut pos = 0usize;
+        while pos + needle.len() <= self.units.len() {
+            let hit = needle.iter().enumerate().all(|(offset, expected)| {
+                match &self.units[pos + offset] {
+                    Unit::Text { ch, .. } => normalize(*ch) == *expected,
+                    Unit::Newline { .. } => *expected == '\n',
+                    _ => false,
+                }
+            });
+            if hit {
+                matches.push(pos);
+                pos += needle.len();
+            } else {
+                pos += 1;
+            }
+        }
+
+        for &start in matches.iter().rev() {
+            let style = match &self.units[start] {
+                Unit::Text { style, .. } => style.clone(),
+                _ => Map::new(),
+            };
+            let replacement: Vec<Unit> = replace
+                .chars()
+                .map(|ch| Unit::Text {
+                    ch,
+                    style: style.clone(),
+                })
+                .collect();
+            self.units.splice(start..start + needle.len(), replacement);
+        }
+
+        matches.len() as i64
+    }
+
+    fn update_text_style(
+        &mut self,
+        start: i64,
+        end: i64,
+        style: &Value,
+        fields: &str,
+    ) -> DocResult<()> {
+        let from = self.position(start)?;
+        let to = self.position(end)?;
+        for unit in &mut self.units[from..to] {
+            if let Unit::Text { style: current, .. } | Unit::Newline { style: current, .. } = unit {
+                apply_fields(current, style, fields);
+            }
+        }
+        Ok(())
+    }
+
+    fn update_paragraph_style(
+        &mut self,
+        start: i64,
+        end: i64,
+        style: &Value,
+        fields: &str,
+    ) -> DocResult<()> {
+        let from = self.position(start)?;
+        let to = self.position(end)?.max(from + 1);
+        let (_, mut para_end) = self.paragraph_bounds(from);
+        loop {
+            if let Some(Unit::Newline {
+                paragraph_style, ..
+            }) = self.units.get_mut(para_end)
+            {
+                apply_fields(paragraph_style, style, fields);
+            }
+            if para_end + 1 >= to || para_end + 1 >= self.units.len() {
+                break;
+            }
+            para_end = self.paragraph_bounds(para_end + 1).1;
+        }
+        Ok(())
+    }
+
+    /// Makes every paragraph overlapping `start..end` an item of a new list,
+    /// turning leading tabs into nesting levels like the real API.
+    fn create_bullets(&mut self, start: i64, end: i64, preset: &str) -> DocResult<()> {
+        let glyphs: [Value; 3] = match preset {
+            "BULLET_DISC_CIRCLE_SQUARE" => [
+                json!({"glyphSymbol": "●"}),
+                json!({"glyphSymbol": "○"}),
+                json!({"glyphSymbol": "■"}),
+            ],
+            "NUMBERED_DECIMAL_ALPHA_ROMAN" => [
+                json!({"glyphType": "DECIMAL", "glyphFormat": "%0."}),
+                json!({"glyphType": "ALPHA", "glyphFormat": "%1."}),
+                json!({"glyphType": "ROMAN", "glyphFormat": "%2."}),
+            ],
+            "BULLET_CHECKBOX" => [
+                json!({"glyphType": "GLYPH_TYPE_UNSPECIFIED"}),
+                json!({"glyphType": "GLYPH_TYPE_UNSPECIFIED"}),
+                json!({"glyphType": "GLYPH_TYPE_UNSPECIFIED"}),
+            ],
+            other => return Err(format!("Unsupported bullet preset: {other}")),
+        };
+        let list_id = format!("kix.list{}", self.lists.len() + 1);
+        self.lists.insert(
+            list_id.clone(),
+            json!({"listProperties": {
+                "nestingLevels": (0..9).map(|level| glyphs[level % 3].clone()).collect::<Vec<_>>()
+            }}),
+        );
+
+        let from = self.position(start)?;
+        let to = self.position(end)?.max(from + 1);
+        let mut paragraphs = Vec::new();
+        let mut pos = from;
+        while pos < to && pos < self.units.len() {
+            let (para_start, para_end) = self.paragraph_bounds(pos);
+            paragraphs.push(para_start);
+            pos = para_end + 1;
+        }
+
+        for para_start in paragraphs.into_iter().rev() {
+            let tabs = self.units[para_start..]
+                .iter()
+                .take_while(|u| matches!(u, Unit::Text { ch: '\t', .. }))
+                .count();
+            self.units.drain(para_start..para_start + tabs);
+            let (_, para_end) = self.paragraph_bounds(para_start);
+            if let Some(Unit::Newline { bullet, .. }) = self.units.get_mut(para_end) {
+                let mut value = json!({"listId": list_id, "textStyle": {}});
+                if tabs > 0 {
+                    value["nestingLevel"] = json!(tabs);
+                }
+                *bullet = Some(value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Each body paragraph's text with its `namedStyleType`, if set.
+    pub fn paragraph_styles(&self) -> Vec<(String, Option<String>)> {
+        let mut paragraphs = Vec::new();
+        let mut text = String::new();
+        for unit in &self.units {
+            match unit {
+                Unit::Text { ch, .. } => text.push(*ch),
+                Unit::Newline {
+                    paragraph_style, ..
+                } => {
+                    text.push('\n');
+                    let style = paragraph_style
+                        .get("namedStyleType")
+                        .and_then(Value::as_str)
+                        .map(ToString::to_string);
+                    paragraphs.push((std::mem::take(&mut text), style));
+                }
+                _ => {}
+            }
+        }
+        paragraphs
+    }
+
+    /// Renders the `documents.get` response.
+    pub fn to_json(&self) -> Value {
+        let mut pos = 0usize;
+        let mut index = 1i64;
+        let mut inline_objects = Map::new();
+        let mut content = vec![json!({
+            "endIndex": 1,
+            "sectionBreak": {"sectionStyle": {}}
+        })];
+        content.extend(self.render_content(&mut pos, &mut index, &mut inline_objects));
+
+        json!({
+            "documentId": self.id,
+            "title": self.title,
+            "revisionId": self.revision_id(),
+            "body": {"content": content},
+            "lists": self.lists,
+            "inlineObjects": inline_objects
+        })
+    }
+
+    fn render_content(
+        &self,
+        pos: &mut usize,
+        index: &mut i64,
+        inline_objects: &mut Map<String, Value>,
+    ) -> Vec<Value> {
+        let mut elements = Vec::new();
+        while *pos < self.units.len() {
+            match &self.units[*pos] {
+                Unit::RowStart | Unit::CellStart | Unit::TableEnd => break,
+                Unit::TableStart => elements.push(self.render_table(pos, index, inline_objects)),
+                _ => elements.push(self.render_paragraph(pos, index, inline_objects)),
+            }
+        }
+        elements
+    }
+
+    fn render_paragraph(
+        &self,
+        pos: &mut usize,
+        index: &mut i64,
+        inline_objects: &mut Map<String, Value>,
+    ) -> Value {
+        let start = *index;
+        let mut elements: Vec<Value> = Vec::new();
+        let mut run: Option<(i64, String, Map<String, Value>)> = None;
+        let mut paragraph_style = Map::new();
+        let mut bullet = None;
+
+        let flush = |run: &mut Option<(i64, String, Map<String, Value>)>,
+                     elements: &mut Vec<Value>,
+                     end: i64| {
+            if let Some((run_start, text, style)) = run.take() {
+                elements.push(json!({
+                    "startIndex": run_start,
+                    "endIndex": end,
+                    "textRun": {"content": text, "textStyle": style}
+                }));
+            }
+        };
+
+        while *pos < self.units.len() {
+            let unit = &self.units[*pos];
+            match unit {
+                Unit::Text { ch, style } => {
+                    let same = run.as_ref().is_some_and(|(_, _, s)| s == style);
+                    if !same {
+                        flush(&mut run, &mut elements, *index);
+                        run = Some((*index, String::new(), style.clone()));
+                    }
+                    if let Some((_, text, _)) = run.as_mut() {
+                        text.push(*ch);
+                    }
+                }
+                Unit::Newline {
+                    style,
+                    paragraph_style: ps,
+                    bullet: b,
+                } => {
+                    let same = run.as_ref().is_some_and(|(_, _, s)| s == style);
+                    if !same {
+                        flush(&mut run, &mut elements, *index);
+                        run = Some((*index, String::new(), style.clone()));
+                    }
+                    if let Some((_, text, _)) = run.as_mut() {
+                        text.push('\n');
+                    }
+                    paragraph_style = ps.clone();
+                    bullet = b.clone();
+                    *index += 1;
+                    *pos += 1;
+                    flush(&mut run, &mut elements, *index);
+                    break;
+                }
+                Unit::Image { uri } => {
+                    flush(&mut run, &mut elements, *index);
+                    let object_id = format!("kix.img{}", inline_objects.len() + 1);
+                    inline_objects.insert(
+                        object_id.clone(),
+                        json!({
+                            "objectId": object_id,
+                            "inlineObjectProperties": {
+                                "embeddedObject": {
+                                    "imageProperties": {"contentUri": uri, "sourceUri": uri}
+                                }
+                            }
+                        }),
+                    );
+                    elements.push(json!({
+                        "startIndex": *index,
+                        "endIndex": *index + 1,
+                        "inlineObjectElement": {"inlineObjectId": object_id, "textStyle": {}}
+                    }));
+                }
+                Unit::PageBreak => {
+                    flush(&mut run, &mut elements, *index);
+                    elements.push(json!({
+                        "startIndex": *index,
+                        "endIndex": *index + 1,
+                        "pageBreak": {"textStyle": {}}
+                    }));
+                }
+                _ => break,
+            }
+            *index += unit.width();
+            *pos += 1;
+        }
+        flush(&mut run, &mut elements, *index);
+
+        if !paragraph_style.contains_key("namedStyleType") {
+            paragraph_style.insert(
+                "namedStyleType".to_string(),
+                Value::String("NORMAL_TEXT".to_string()),
+            );
+        }
+
+        let mut paragraph = json!({
+            "elements": elements,
+            "paragraphStyle": paragraph_style
+        });
+        if let Some(bullet) = bullet {
+            paragraph["bullet"] = bullet;
+        }
+
+        json!({
+            "startIndex": start,
+            "endIndex": *index,
+            "paragraph": paragraph
+        })
+    }
+
+    fn render_table(
+        &self,
+        pos: &mut usize,
+        index: &mut i64,
+        inline_objects: &mut Map<String, Value>,
+    ) -> Value {
+        let table_start = *index;
+        *pos += 1;
+        *index += 1;
+
+        let mut rows = Vec::new();
+        while matches!(self.units.get(*pos), Some(Unit::RowStart)) {
+            let row_start = *index;
+            *pos += 1;
+            *index += 1;
+            let mut cells = Vec::new();
+            while matches!(self.units.get(*pos), Some(Unit::CellStart)) {
+                let cell_start = *index;
+                *pos += 1;
+                *index += 1;
+                let content = self.render_content(pos, index, inline_objects);
+                cells.push(json!({
+                    "startIndex": cell_start,
+                    "endIndex": *index,
+                    "content": content
+                }));
+            }
+            rows.push(json!({
+                "startIndex": row_start,
+                "endIndex": *index,
+                "tableCells": cells
+            }));
+        }
+
+        // Consume the table end marker.
+        *pos += 1;
+        *index += 1;
+
+        let columns = rows
+            .first()
+            .and_then(|r| r.get("tableCells"))
+            .and_then(Value::as_array)
+            .map(Vec::len)
+            .unwrap_or(0);
+
+        json!({
+            "startIndex": table_start,
+            "endIndex": *index,
+            "table": {
+                "rows": rows.len(),
+                "columns": columns,
+                "tableRows": rows
+            }
+        })
+    }
+}
+
+fn range_of(body: &Value) -> DocResult<(i64, i64)> {
+    let range = body.get("range").ok_or("Missing range")?;
+    let start = range
+        .get("startIndex")
+        .and_then(Value::as_i64)
+        .ok_or("Missing startIndex")?;
+    let end = range
+        .get("endIndex")
+        .and_then(Value::as_i64)
+        .ok_or("Missing endIndex")?;
+    Ok((start, end))
+}
+
+fn apply_fields(target: &mut Map<String, Value>, style: &Value, fields: &str) {
+    if fields.trim() == "*" {
+        *target = style.as_object().cloned().unwrap_or_default();
+        return;
+    }
+    for field in fields.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        match style.get(field) {
+            Some(value) => {
+                target.insert(field.to_string(), value.clone());
+            }
+            None => {
+                target.remove(field);
+            }
+        }
+    }
+}
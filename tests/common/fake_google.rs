@@ -0,0 +1,1346 @@
+//! A test-only stand-in for the Google APIs used by the binaries. It serves the
+//! subset of Docs, Drive, Sheets and OAuth endpoints they call, keeps all state
+//! in memory and records every request for assertions.
+
+use super::fake_docs::FakeDocument;
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Request, Response, Server};
+
+pub const ACCESS_TOKEN: &str = "test-access-token";
+pub const REFRESHED_ACCESS_TOKEN: &str = "refreshed-access-token";
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl RecordedRequest {
+    pub fn query_value(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeFile {
+    pub metadata: Map<String, Value>,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeSheet {
+    pub sheet_id: i64,
+    pub title: String,
+    pub values: Vec<Vec<Value>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeSpreadsheet {
+    pub id: String,
+    pub title: String,
+    pub sheets: Vec<FakeSheet>,
+}
+
+#[derive(Default)]
+pub struct State {
+    pub documents: BTreeMap<String, FakeDocument>,
+    pub files: BTreeMap<String, FakeFile>,
+    pub spreadsheets: BTreeMap<String, FakeSpreadsheet>,
+    pub requests: Vec<RecordedRequest>,
+    pub valid_tokens: Vec<String>,
+    /// Device-code polls answered with `authorization_pending` before the
+    /// grant is approved.
+    pub device_pending_polls: u32,
+    failures: VecDeque<(u16, Option<String>)>,
+    next_id: u64,
+}
+
+impl State {
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}-{}", self.next_id)
+    }
+}
+
+pub struct FakeGoogle {
+    server: Arc<Server>,
+    state: Arc<Mutex<State>>,
+    handle: Option<JoinHandle<()>>,
+    base_url: String,
+}
+
+impl FakeGoogle {
+    pub fn start() -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").expect("bind fake server"));
+        let port = server.server_addr().to_ip().expect("ip listener").port();
+        let state = Arc::new(Mutex::new(State {
+            valid_tokens: vec![ACCESS_TOKEN.to_string(), REFRESHED_ACCESS_TOKEN.to_string()],
+            ..State::default()
+        }));
+
+        let handle = {
+            let server = Arc::clone(&server);
+            let state = Arc::clone(&state);
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle_request(&state, request);
+                }
+            })
+        };
+
+        Self {
+            server,
+            state,
+            handle: Some(handle),
+            base_url: format!("http://127.0.0.1:{port}"),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("fake state lock")
+    }
+
+    /// Makes the next `count` API requests fail with `status`, optionally
+    /// sending a `Retry-After` header.
+    pub fn fail_next(&self, status: u16, count: usize, retry_after: Option<&str>) {
+        let mut state = self.state();
+        for _ in 0..count {
+            state
+                .failures
+                .push_back((status, retry_after.map(ToString::to_string)));
+        }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    pub fn add_document(&self, title: &str, text: &str) -> String {
+        let mut state = self.state();
+        let id = state.next_id("doc");
+        let mut document = FakeDocument::new(&id, title);
+        if !text.is_empty() {
+            document
+                .apply(&json!({"insertText": {"location": {"index": 1}, "text": text}}))
+                .expect("seed document text");
+        }
+        register_document_file(&mut state, &id, title);
+        state.documents.insert(id.clone(), document);
+        id
+    }
+
+    /// Applies a `batchUpdate` request to a document directly, for shapes the
+    /// binaries can't produce on their own.
+    pub fn apply_to_document(&self, id: &str, request: Value) {
+        let mut state = self.state();
+        let document = state.documents.get_mut(id).expect("document exists");
+        document.apply(&request).expect("request applies");
+        document.revision += 1;
+    }
+
+    pub fn document(&self, id: &str) -> FakeDocument {
+        self.state()
+            .documents
+            .get(id)
+            .cloned()
+            .expect("document exists")
+    }
+
+    pub fn add_file(&self, name: &str, mime_type: &str, content: &[u8]) -> String {
+        let mut state = self.state();
+        let id = state.next_id("file");
+        let metadata = file_metadata(&id, name, mime_type, content.len(), None);
+        state.files.insert(
+            id.clone(),
+            FakeFile {
+                metadata,
+                content: content.to_vec(),
+            },
+        );
+        id
+    }
+
+    pub fn file(&self, id: &str) -> Option<FakeFile> {
+        self.state().files.get(id).cloned()
+    }
+
+    pub fn add_spreadsheet(&self, title: &str, sheet_titles: &[&str]) -> String {
+        let mut state = self.state();
+        let id = state.next_id("sheet");
+        let sheets = sheet_titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| FakeSheet {
+                sheet_id: i as i64,
+                title: title.to_string(),
+                values: Vec::new(),
+            })
+            .collect();
+        state.spreadsheets.insert(
+            id.clone(),
+            FakeSpreadsheet {
+                id: id.clone(),
+                title: title.to_string(),
+                sheets,
+            },
+        );
+        id
+    }
+
+    pub fn spreadsheet(&self, id: &str) -> FakeSpreadsheet {
+        self.state()
+            .spreadsheets
+            .get(id)
+            .cloned()
+            .expect("spreadsheet exists")
+    }
+}
+
+impl Drop for FakeGoogle {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+type Reply = (u16, Value);
+
+fn handle_request(state: &Mutex<State>, mut request: Request) {
+    let method = request.method().as_str().to_ascii_uppercase();
+    let raw_url = request.url().to_string();
+    let (path, query_string) = raw_url.split_once('?').unwrap_or((raw_url.as_str(), ""));
+    let path = path.to_string();
+    let query: Vec<(String, String)> = url::form_urlencoded::parse(query_string.as_bytes())
+        .into_owned()
+        .collect();
+
+    let content_type = header_value(&request, "Content-Type").unwrap_or_default();
+    let authorization = header_value(&request, "Authorization");
+    let mut raw_body = Vec::new();
+    let _ = request.as_reader().read_to_end(&mut raw_body);
+
+    let body = if content_type.starts_with("application/json") {
+        serde_json::from_slice(&raw_body).unwrap_or(Value::Null)
+    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+        let form: Map<String, Value> = url::form_urlencoded::parse(&raw_body)
+            .into_owned()
+            .map(|(k, v)| (k, Value::String(v)))
+            .collect();
+        Value::Object(form)
+    } else {
+        Value::Null
+    };
+
+    let mut guard = state.lock().expect("fake state lock");
+    guard.requests.push(RecordedRequest {
+        method: method.clone(),
+        path: path.clone(),
+        query: query.clone(),
+        body: body.clone(),
+    });
+
+    if let Some((status, retry_after)) = guard.failures.pop_front() {
+        let mut response = json_response(
+            status,
+            &json!({"error": {"code": status, "message": "Injected failure", "status": "UNAVAILABLE"}}),
+        );
+        if let Some(retry_after) = retry_after {
+            response.add_header(
+                Header::from_bytes(&b"Retry-After"[..], retry_after.as_bytes()).expect("header"),
+            );
+        }
+        drop(guard);
+        let _ = request.respond(response);
+        return;
+    }
+
+    if path.starts_with("/upload/drive/v3/files") && method != "GET" {
+        let reply = authorized(&guard, authorization.as_deref())
+            .and_then(|_| handle_upload(&mut guard, &method, &path, &content_type, &raw_body));
+        drop(guard);
+        respond(request, reply);
+        return;
+    }
+
+    let reply = if path == "/token" {
+        handle_token(&mut guard, &body)
+    } else if path == "/device/code" {
+        Ok((
+            200,
+            json!({
+                "device_code": "test-device-code",
+                "user_code": "ABCD-EFGH",
+                "verification_url": "https://www.google.com/device",
+                "expires_in": 1800,
+                "interval": 0
+            }),
+        ))
+    } else if path == "/revoke" {
+        guard.valid_tokens.clear();
+        Ok((200, json!({})))
+    } else {
+        authorized(&guard, authorization.as_deref()).and_then(|_| {
+            if path.starts_with("/v1/documents") {
+                handle_docs(&mut guard, &method, &path, &body)
+            } else if path.starts_with("/drive/v3/") {
+                handle_drive(&mut guard, &method, &path, &query, &body)
+            } else if path.starts_with("/v4/spreadsheets") {
+                handle_sheets(&mut guard, &method, &path, &query, &body)
+            } else {
+                Err(not_found(&path))
+            }
+        })
+    };
+
+    if let Ok((200, Value::Null)) = &reply
+        && method == "GET"
+        && let Some(bytes) = media_download(&guard, &path, &query)
+    {
+        drop(guard);
+        let _ = request.respond(Response::from_data(bytes));
+        return;
+    }
+
+    drop(guard);
+    respond(request, reply);
+}
+
+fn respond(request: Request, reply: Result<Reply, Reply>) {
+    let (status, body) = match reply {
+        Ok(reply) | Err(reply) => reply,
+    };
+    let response = if status == 204 {
+        Response::from_data(Vec::new()).with_status_code(204)
+    } else {
+        json_response(status, &body)
+    };
+    let _ = request.respond(response);
+}
+
+fn json_response(status: u16, body: &Value) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_data(body.to_string().into_bytes())
+        .with_status_code(status)
+        .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap())
+}
+
+fn header_value(request: &Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str().to_string())
+}
+
+fn api_error(status: u16, message: &str) -> Reply {
+    let canonical = match status {
+        400 => "INVALID_ARGUMENT",
+        401 => "UNAUTHENTICATED",
+        403 => "PERMISSION_DENIED",
+        404 => "NOT_FOUND",
+        _ => "UNKNOWN",
+    };
+    api_error_with_status(status, canonical, message)
+}
+
+fn api_error_with_status(status: u16, canonical: &str, message: &str) -> Reply {
+    (
+        status,
+        json!({"error": {"code": status, "message": message, "status": canonical}}),
+    )
+}
+
+fn not_found(path: &str) -> Reply {
+    api_error(404, &format!("Requested entity was not found: {path}"))
+}
+
+fn authorized(state: &State, authorization: Option<&str>) -> Result<(), Reply> {
+    let token = authorization
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if state.valid_tokens.iter().any(|t| t == token) {
+        Ok(())
+    } else {
+        Err(api_error(
+            401,
+            "Request had invalid authentication credentials.",
+        ))
+    }
+}
+
+fn handle_token(state: &mut State, form: &Value) -> Result<Reply, Reply> {
+    let grant = form.get("grant_type").and_then(Value::as_str);
+    if grant == Some("urn:ietf:params:oauth:grant-type:device_code")
+        && state.device_pending_polls > 0
+    {
+        state.device_pending_polls -= 1;
+        return Err((
+            428,
+            json!({"error": "authorization_pending", "error_description": "Precondition Required"}),
+        ));
+    }
+
+    match grant {
+        Some("refresh_token")
+        | Some("authorization_code")
+        | Some("urn:ietf:params:oauth:grant-type:device_code")
+        | Some("urn:ietf:params:oauth:grant-type:jwt-bearer") => Ok((
+            200,
+            json!({
+                "access_token": REFRESHED_ACCESS_TOKEN,
+                "expires_in": 3599,
+                "refresh_token": "test-refresh-token",
+                "scope": "https://www.googleapis.com/auth/documents https://www.googleapis.com/auth/drive https://www.googleapis.com/auth/spreadsheets",
+                "token_type": "Bearer"
+            }),
+        )),
+        _ => Err((
+            400,
+            json!({"error": "unsupported_grant_type", "error_description": "Invalid grant_type"}),
+        )),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Docs
+// ---------------------------------------------------------------------------
+
+fn register_document_file(state: &mut State, id: &str, title: &str) {
+    let metadata = file_metadata(id, title, "application/vnd.google-apps.document", 0, None);
+    state.files.insert(
+        id.to_string(),
+        FakeFile {
+            metadata,
+            content: Vec::new(),
+        },
+    );
+}
+
+fn handle_docs(state: &mut State, method: &str, path: &str, body: &Value) -> Result<Reply, Reply> {
+    let rest = path.trim_start_matches("/v1/documents");
+
+    if rest.is_empty() && method == "POST" {
+        let id = state.next_id("doc");
+        let title = body
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("Untitled document");
+        let document = FakeDocument::new(&id, title);
+        let rendered = document.to_json();
+        register_document_file(state, &id, title);
+        state.documents.insert(id, document);
+        return Ok((200, rendered));
+    }
+
+    let rest = rest.trim_start_matches('/');
+    if let Some(id) = rest.strip_suffix(":batchUpdate") {
+        let document = state.documents.get_mut(id).ok_or_else(|| not_found(path))?;
+        if let Some(required) = body
+            .pointer("/writeControl/requiredRevisionId")
+            .and_then(Value::as_str)
+            && required != document.revision_id()
+        {
+            return Err(api_error_with_status(
+                400,
+                "FAILED_PRECONDITION",
+                &format!(
+                    "The required revision ID '{required}' does not match the latest revision."
+                ),
+            ));
+        }
+        let requests = body
+            .get("requests")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        // batchUpdate is atomic: apply to a scratch copy and commit on success.
+        let mut scratch = document.clone();
+        let mut replies = Vec::new();
+        for (i, request) in requests.iter().enumerate() {
+            let reply = scratch
+                .apply(request)
+                .map_err(|msg| api_error(400, &format!("Invalid requests[{i}]: {msg}")))?;
+            replies.push(reply);
+        }
+        scratch.revision += 1;
+        *document = scratch;
+
+        return Ok((
+            200,
+            json!({
+                "documentId": id,
+                "replies": replies,
+                "writeControl": {"requiredRevisionId": document.revision_id()}
+            }),
+        ));
+    }
+
+    if method == "GET" {
+        let document = state.documents.get(rest).ok_or_else(|| not_found(path))?;
+        return Ok((200, document.to_json()));
+    }
+
+    Err(not_found(path))
+}
+
+// ---------------------------------------------------------------------------
+// Drive
+// ---------------------------------------------------------------------------
+
+fn file_metadata(
+    id: &str,
+    name: &str,
+    mime_type: &str,
+    size: usize,
+    parents: Option<Value>,
+) -> Map<String, Value> {
+    let mut metadata = Map::new();
+    metadata.insert("id".to_string(), json!(id));
+    metadata.insert("name".to_string(), json!(name));
+    metadata.insert("mimeType".to_string(), json!(mime_type));
+    metadata.insert(
+        "webViewLink".to_string(),
+        json!(format!("https://drive.example.test/{id}/view")),
+    );
+    metadata.insert("parents".to_string(), parents.unwrap_or(json!(["root"])));
+    metadata.insert("createdTime".to_string(), json!("2026-01-01T00:00:00.000Z"));
+    metadata.insert(
+        "modifiedTime".to_string(),
+        json!("2026-01-01T00:00:00.000Z"),
+    );
+    metadata.insert("trashed".to_string(), json!(false));
+    metadata.insert("starred".to_string(), json!(false));
+    if !mime_type.starts_with("application/vnd.google-apps.") {
+        metadata.insert("size".to_string(), json!(size.to_string()));
+    }
+    metadata.insert(
+        "owners".to_string(),
+        json!([{"emailAddress": "owner@example.test", "displayName": "Test Owner"}]),
+    );
+    metadata.insert("permissions".to_string(), json!([]));
+    metadata
+}
+
+fn media_download(state: &State, path: &str, query: &[(String, String)]) -> Option<Vec<u8>> {
+    let id = path.strip_prefix("/drive/v3/files/")?;
+    let param = |key: &str| {
+        query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+
+    if let Some(id) = id.strip_suffix("/export") {
+        let file = state.files.get(id)?;
+        let mime = param("mimeType").unwrap_or("application/pdf");
+        return Some(
+            format!(
+                "exported {} as {mime}",
+                file.metadata["name"].as_str().unwrap_or_default()
+            )
+            .into_bytes(),
+        );
+    }
+
+    if param("alt") == Some("media") {
+        return state.files.get(id).map(|f| f.content.clone());
+    }
+    None
+}
+
+fn handle_drive(
+    state: &mut State,
+    method: &str,
+    path: &str,
+    query: &[(String, String)],
+    body: &Value,
+) -> Result<Reply, Reply> {
+    let param = |key: &str| {
+        query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    let rest = path.trim_start_matches("/drive/v3/");
+
+    if rest == "about" {
+        return Ok((
+            200,
+            json!({"user": {"emailAddress": "owner@example.test", "displayName": "Test Owner"}}),
+        ));
+    }
+
+    if rest == "files" {
+        return match method {
+            "GET" => {
+                let q = param("q").unwrap_or_default();
+                let page_size = param("pageSize")
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(100);
+                let files: Vec<Value> = state
+                    .files
+                    .values()
+                    .filter(|f| drive_query_matches(q, &f.metadata))
+                    .take(page_size)
+                    .map(|f| Value::Object(f.metadata.clone()))
+                    .collect();
+                Ok((200, json!({"files": files})))
+            }
+            "POST" => {
+                let id = state.next_id("file");
+                let name = body
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Untitled");
+                let mime = body
+                    .get("mimeType")
+                    .and_then(Value::as_str)
+                    .unwrap_or("application/octet-stream");
+                let metadata = file_metadata(&id, name, mime, 0, body.get("parents").cloned());
+                state.files.insert(
+                    id,
+                    FakeFile {
+                        metadata: metadata.clone(),
+                        content: Vec::new(),
+                    },
+                );
+                Ok((200, Value::Object(metadata)))
+            }
+            _ => Err(api_error(405, "Method not allowed")),
+        };
+    }
+
+    let Some(rest) = rest.strip_prefix("files/") else {
+        return Err(not_found(path));
+    };
+
+    if rest.ends_with("/export") {
+        let id = rest.trim_end_matches("/export");
+        return if state.files.contains_key(id) {
+            Ok((200, Value::Null))
+        } else {
+            Err(not_found(path))
+        };
+    }
+
+    if let Some(id) = rest.strip_suffix("/permissions") {
+        let file = state.files.get_mut(id).ok_or_else(|| not_found(path))?;
+        let permissions = file
+            .metadata
+            .entry("permissions")
+            .or_insert_with(|| json!([]));
+        let mut permission = body.as_object().cloned().unwrap_or_default();
+        let perm_id = format!("perm-{}", permissions.as_array().map_or(0, Vec::len) + 1);
+        permission.insert("id".to_string(), json!(perm_id));
+        if let Some(list) = permissions.as_array_mut() {
+            list.push(Value::Object(permission.clone()));
+        }
+        return Ok((200, Value::Object(permission)));
+    }
+
+    if let Some(id) = rest.strip_suffix("/copy") {
+        let source = state
+            .files
+            .get(id)
+            .cloned()
+            .ok_or_else(|| not_found(path))?;
+        let new_id = state.next_id("file");
+        let mut metadata = source.metadata.clone();
+        metadata.insert("id".to_string(), json!(new_id));
+        let name = body
+            .get("name")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| {
+                format!(
+                    "Copy of {}",
+                    source.metadata["name"].as_str().unwrap_or_default()
+                )
+            });
+        metadata.insert("name".to_string(), json!(name));
+        if let Some(parents) = body.get("parents") {
+            metadata.insert("parents".to_string(), parents.clone());
+        }
+        if let Some(document) = state.documents.get(id).cloned() {
+            let mut copy = document;
+            copy.id = new_id.clone();
+            copy.title = name;
+            state.documents.insert(new_id.clone(), copy);
+        }
+        state.files.insert(
+            new_id,
+            FakeFile {
+                metadata: metadata.clone(),
+                content: source.content,
+            },
+        );
+        return Ok((200, Value::Object(metadata)));
+    }
+
+    let id = rest;
+    match method {
+        "GET" => {
+            let file = state.files.get(id).ok_or_else(|| not_found(path))?;
+            if param("alt") == Some("media") {
+                return Ok((200, Value::Null));
+            }
+            Ok((200, Value::Object(file.metadata.clone())))
+        }
+        "PATCH" => {
+            let file = state.files.get_mut(id).ok_or_else(|| not_found(path))?;
+            if let Some(updates) = body.as_object() {
+                for (k, v) in updates {
+                    file.metadata.insert(k.clone(), v.clone());
+                }
+            }
+            if let Some(add) = param("addParents") {
+                let removed: Vec<&str> = param("removeParents")
+                    .unwrap_or_default()
+                    .split(',')
+                    .collect();
+                let mut parents: Vec<Value> = file.metadata["parents"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|p| !removed.contains(&p.as_str().unwrap_or_default()))
+                    .collect();
+                parents.push(json!(add));
+                file.metadata
+                    .insert("parents".to_string(), Value::Array(parents));
+            }
+            Ok((200, Value::Object(file.metadata.clone())))
+        }
+        "DELETE" => {
+            state.files.remove(id).ok_or_else(|| not_found(path))?;
+            state.documents.remove(id);
+            Ok((204, Value::Null))
+        }
+        _ => Err(api_error(405, "Method not allowed")),
+    }
+}
+
+/// Understands the clauses the binaries generate: `trashed = false`,
+/// `'<id>' in parents` and `name contains '<text>'`, joined with `and`.
+fn drive_query_matches(query: &str, metadata: &Map<String, Value>) -> bool {
+    query
+        .split(" and ")
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .all(|clause| {
+            if clause == "trashed = false" {
+                metadata.get("trashed") != Some(&json!(true))
+            } else if let Some(parent) = clause
+                .strip_suffix(" in parents")
+                .map(|p| p.trim_matches('\''))
+            {
+                metadata["parents"]
+                    .as_array()
+                    .is_some_and(|ps| ps.iter().any(|p| p == parent))
+            } else if let Some(needle) = clause.strip_prefix("name contains ") {
+                metadata["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .contains(needle.trim_matches('\''))
+            } else {
+                true
+            }
+        })
+}
+
+fn handle_upload(
+    state: &mut State,
+    method: &str,
+    path: &str,
+    content_type: &str,
+    raw_body: &[u8],
+) -> Result<Reply, Reply> {
+    let boundary = content_type
+        .split("boundary=")
+        .nth(1)
+        .map(|b| b.trim_matches('"').to_string())
+        .ok_or_else(|| api_error(400, "Missing multipart boundary"))?;
+    let parts = parse_multipart(raw_body, &boundary);
+    let metadata: Value = parts
+        .get("metadata")
+        .and_then(|(_, bytes)| serde_json::from_slice(bytes).ok())
+        .unwrap_or(json!({}));
+    let (file_mime, content) = parts
+        .get("file")
+        .cloned()
+        .unwrap_or_else(|| ("application/octet-stream".to_string(), Vec::new()));
+
+    match method {
+        "POST" => {
+            let id = state.next_id("file");
+            let name = metadata
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("upload.bin");
+            let file = FakeFile {
+                metadata: file_metadata(
+                    &id,
+                    name,
+                    &file_mime,
+                    content.len(),
+                    metadata.get("parents").cloned(),
+                ),
+                content,
+            };
+            let reply = Value::Object(file.metadata.clone());
+            state.files.insert(id, file);
+            Ok((200, reply))
+        }
+        "PATCH" => {
+            let id = path.trim_start_matches("/upload/drive/v3/files/");
+            let file = state.files.get_mut(id).ok_or_else(|| not_found(path))?;
+            if let Some(name) = metadata.get("name") {
+                file.metadata.insert("name".to_string(), name.clone());
+            }
+            file.metadata
+                .insert("size".to_string(), json!(content.len().to_string()));
+            file.metadata
+                .insert("mimeType".to_string(), json!(file_mime));
+            file.content = content;
+            Ok((200, Value::Object(file.metadata.clone())))
+        }
+        _ => Err(api_error(405, "Method not allowed")),
+    }
+}
+
+fn parse_multipart(body: &[u8], boundary: &str) -> HashMap<String, (String, Vec<u8>)> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = HashMap::new();
+
+    for chunk in split_bytes(body, delimiter.as_bytes()) {
+        let chunk = chunk.strip_prefix(b"\r\n").unwrap_or(chunk);
+        let Some(split) = find_bytes(chunk, b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&chunk[..split]).to_string();
+        let mut content = chunk[split + 4..].to_vec();
+        if content.ends_with(b"\r\n") {
+            content.truncate(content.len() - 2);
+        }
+
+        let name = headers
+            .split("name=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap_or_default()
+            .to_string();
+        let mime = headers
+            .lines()
+            .find_map(|l| {
+                l.to_ascii_lowercase()
+                    .starts_with("content-type:")
+                    .then(|| l[13..].trim().to_string())
+            })
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        parts.insert(name, (mime, content));
+    }
+
+    parts
+}
+
+fn split_bytes<'a>(haystack: &'a [u8], needle: &[u8]) -> Vec<&'a [u8]> {
+    let mut pieces = Vec::new();
+    let mut rest = haystack;
+    while let Some(at) = find_bytes(rest, needle) {
+        pieces.push(&rest[..at]);
+        rest = &rest[at + needle.len()..];
+    }
+    pieces.push(rest);
+    pieces
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+// ---------------------------------------------------------------------------
+// Sheets
+// ---------------------------------------------------------------------------
+
+impl FakeSpreadsheet {
+    fn to_json(&self) -> Value {
+        json!({
+            "spreadsheetId": self.id,
+            "properties": {"title": self.title, "locale": "en_US", "timeZone": "Etc/GMT"},
+            "spreadsheetUrl": format!("https://sheets.example.test/{}", self.id),
+            "sheets": self.sheets.iter().enumerate().map(|(i, s)| json!({
+                "properties": {
+                    "sheetId": s.sheet_id,
+                    "title": s.title,
+                    "index": i,
+                    "sheetType": "GRID",
+                    "gridProperties": {"rowCount": 1000, "columnCount": 26}
+                }
+            })).collect::<Vec<_>>()
+        })
+    }
+
+    fn sheet_mut(&mut self, title: Option<&str>) -> Option<&mut FakeSheet> {
+        match title {
+            Some(t) => self.sheets.iter_mut().find(|s| s.title == t),
+            None => self.sheets.first_mut(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GridRange {
+    start_row: usize,
+    start_col: usize,
+    end_row: Option<usize>,
+    end_col: Option<usize>,
+}
+
+fn parse_a1(range: &str) -> (Option<String>, GridRange) {
+    let (sheet, cells) = match range.rsplit_once('!') {
+        Some((sheet, cells)) => (Some(sheet.trim_matches('\'').to_string()), cells),
+        None if !range.chars().any(|c| c.is_ascii_digit()) && !range.contains(':') => {
+            (Some(range.to_string()), "")
+        }
+        None => (None, range),
+    };
+
+    let parse_ref = |r: &str| {
+        let letters: String = r.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+        let digits: String = r.chars().skip(letters.len()).collect();
+        let col = (!letters.is_empty()).then(|| {
+            letters
+                .to_ascii_uppercase()
+                .chars()
+                .fold(0usize, |acc, c| acc * 26 + (c as usize - 'A' as usize + 1))
+                - 1
+        });
+        let row = digits.parse::<usize>().ok().map(|r| r - 1);
+        (col, row)
+    };
+
+    if cells.is_empty() {
+        return (
+            sheet,
+            GridRange {
+                start_row: 0,
+                start_col: 0,
+                end_row: None,
+                end_col: None,
+            },
+        );
+    }
+
+    let (start, end) = cells.split_once(':').unwrap_or((cells, cells));
+    let (start_col, start_row) = parse_ref(start);
+    let (end_col, end_row) = parse_ref(end);
+    let single = !cells.contains(':');
+    (
+        sheet,
+        GridRange {
+            start_row: start_row.unwrap_or(0),
+            start_col: start_col.unwrap_or(0),
+            end_row: if single { None } else { end_row.map(|r| r + 1) },
+            end_col: if single { None } else { end_col.map(|c| c + 1) },
+        },
+    )
+}
+
+fn column_letters(mut index: usize) -> String {
+    let mut letters = String::new();
+    loop {
+        letters.insert(0, (b'A' + (index % 26) as u8) as char);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    letters
+}
+
+fn a1_of(sheet: &str, row: usize, col: usize, rows: usize, cols: usize) -> String {
+    format!(
+        "{sheet}!{}{}:{}{}",
+        column_letters(col),
+        row + 1,
+        column_letters(col + cols.max(1) - 1),
+        row + rows.max(1)
+    )
+}
+
+fn cell_string(value: &Value) -> Value {
+    match value {
+        Value::String(_) => value.clone(),
+        Value::Null => json!(""),
+        other => json!(other.to_string()),
+    }
+}
+
+fn write_values(sheet: &mut FakeSheet, row: usize, col: usize, values: &[Value]) -> (usize, usize) {
+    let mut max_cols = 0;
+    for (r, row_values) in values.iter().enumerate() {
+        let cells = row_values.as_array().cloned().unwrap_or_default();
+        max_cols = max_cols.max(cells.len());
+        let target_row = row + r;
+        if sheet.values.len() <= target_row {
+            sheet.values.resize(target_row + 1, Vec::new());
+        }
+        let target = &mut sheet.values[target_row];
+        for (c, cell) in cells.iter().enumerate() {
+            if target.len() <= col + c {
+                target.resize(col + c + 1, json!(""));
+            }
+            target[col + c] = cell_string(cell);
+        }
+    }
+    (values.len(), max_cols)
+}
+
+fn read_values(sheet: &FakeSheet, grid: GridRange) -> Vec<Value> {
+    let end_row = grid
+        .end_row
+        .unwrap_or(sheet.values.len())
+        .min(sheet.values.len());
+    let mut rows = Vec::new();
+    for row in sheet.values.iter().take(end_row).skip(grid.start_row) {
+        let end_col = grid.end_col.unwrap_or(row.len()).min(row.len());
+        let mut cells: Vec<Value> = row
+            .iter()
+            .take(end_col)
+            .skip(grid.start_col)
+            .cloned()
+            .collect();
+        while cells.last() == Some(&json!("")) {
+            cells.pop();
+        }
+        rows.push(Value::Array(cells));
+    }
+    while rows
+        .last()
+        .is_some_and(|r| r.as_array().is_some_and(Vec::is_empty))
+    {
+        rows.pop();
+    }
+    rows
+}
+
+fn handle_sheets(
+    state: &mut State,
+    method: &str,
+    path: &str,
+    query: &[(String, String)],
+    body: &Value,
+) -> Result<Reply, Reply> {
+    let rest = path.trim_start_matches("/v4/spreadsheets");
+
+    if rest.is_empty() && method == "POST" {
+        let id = state.next_id("sheet");
+        let title = body["properties"]["title"]
+            .as_str()
+            .unwrap_or("Untitled spreadsheet");
+        let titles: Vec<String> = body
+            .get("sheets")
+            .and_then(Value::as_array)
+            .map(|sheets| {
+                sheets
+                    .iter()
+                    .filter_map(|s| s["properties"]["title"].as_str().map(ToString::to_string))
+                    .collect()
+            })
+            .filter(|t: &Vec<String>| !t.is_empty())
+            .unwrap_or_else(|| vec!["Sheet1".to_string()]);
+        let spreadsheet = FakeSpreadsheet {
+            id: id.clone(),
+            title: title.to_string(),
+            sheets: titles
+                .into_iter()
+                .enumerate()
+                .map(|(i, title)| FakeSheet {
+                    sheet_id: i as i64,
+                    title,
+                    values: Vec::new(),
+                })
+                .collect(),
+        };
+        let rendered = spreadsheet.to_json();
+        state.spreadsheets.insert(id, spreadsheet);
+        return Ok((200, rendered));
+    }
+
+    let rest = rest.trim_start_matches('/');
+    let (id, tail) = match rest.find(['/', ':']) {
+        Some(at) => (&rest[..at], &rest[at..]),
+        None => (rest, ""),
+    };
+    let spreadsheet = state
+        .spreadsheets
+        .get_mut(id)
+        .ok_or_else(|| not_found(path))?;
+
+    if tail.is_empty() && method == "GET" {
+        return Ok((200, spreadsheet.to_json()));
+    }
+
+    if tail == ":batchUpdate" {
+        return sheets_batch_update(spreadsheet, body);
+    }
+
+    if tail == "/values:batchGet" {
+        let ranges: Vec<Value> = query
+            .iter()
+            .filter(|(k, _)| k == "ranges")
+            .map(|(_, range)| {
+                let (sheet_name, grid) = parse_a1(range);
+                let sheet = spreadsheet.sheet_mut(sheet_name.as_deref());
+                let values = sheet.map(|s| read_values(s, grid)).unwrap_or_default();
+                json!({"range": range, "majorDimension": "ROWS", "values": values})
+            })
+            .collect();
+        return Ok((200, json!({"spreadsheetId": id, "valueRanges": ranges})));
+    }
+
+    if tail == "/values:batchUpdate" {
+        let mut total_rows = 0;
+        let mut total_cells = 0;
+        let mut total_cols = 0;
+        let data = body["data"].as_array().cloned().unwrap_or_default();
+        for entry in &data {
+            let range = entry["range"].as_str().unwrap_or_default();
+            let (sheet_name, grid) = parse_a1(range);
+            let sheet = spreadsheet
+                .sheet_mut(sheet_name.as_deref())
+                .ok_or_else(|| api_error(400, &format!("Unable to parse range: {range}")))?;
+            let values = entry["values"].as_array().cloned().unwrap_or_default();
+            let (rows, cols) = write_values(sheet, grid.start_row, grid.start_col, &values);
+            total_rows += rows;
+            total_cols += cols;
+            total_cells += values
+                .iter()
+                .map(|r| r.as_array().map_or(0, Vec::len))
+                .sum::<usize>();
+        }
+        return Ok((
+            200,
+            json!({
+                "spreadsheetId": id,
+                "totalUpdatedRows": total_rows,
+                "totalUpdatedColumns": total_cols,
+                "totalUpdatedCells": total_cells,
+                "totalUpdatedSheets": data.len()
+            }),
+        ));
+    }
+
+    if let Some(sheet_tail) = tail.strip_prefix("/sheets/") {
+        let sheet_id = sheet_tail
+            .trim_end_matches(":copyTo")
+            .parse::<i64>()
+            .map_err(|_| not_found(path))?;
+        let source = spreadsheet
+            .sheets
+            .iter()
+            .find(|s| s.sheet_id == sheet_id)
+            .cloned()
+            .ok_or_else(|| not_found(path))?;
+        let destination_id = body["destinationSpreadsheetId"].as_str().unwrap_or(id);
+        let destination = state
+            .spreadsheets
+            .get_mut(destination_id)
+            .ok_or_else(|| not_found(destination_id))?;
+        let new_id = destination
+            .sheets
+            .iter()
+            .map(|s| s.sheet_id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let title = format!("Copy of {}", source.title);
+        destination.sheets.push(FakeSheet {
+            sheet_id: new_id,
+            title: title.clone(),
+            values: source.values,
+        });
+        return Ok((200, json!({"sheetId": new_id, "title": title})));
+    }
+
+    let Some(values_tail) = tail.strip_prefix("/values/") else {
+        return Err(not_found(path));
+    };
+    let (encoded_range, action) = match values_tail.rsplit_once(':') {
+        Some((range, action)) if action == "append" || action == "clear" => (range, Some(action)),
+        _ => (values_tail, None),
+    };
+    let range = urlencoding::decode(encoded_range)
+        .map(|r| r.into_owned())
+        .unwrap_or_else(|_| encoded_range.to_string());
+    let (sheet_name, grid) = parse_a1(&range);
+    let sheet = spreadsheet
+        .sheet_mut(sheet_name.as_deref())
+        .ok_or_else(|| api_error(400, &format!("Unable to parse range: {range}")))?;
+    let sheet_title = sheet.title.clone();
+
+    match (method, action) {
+        ("GET", None) => {
+            let values = read_values(sheet, grid);
+            let mut reply = json!({"range": range, "majorDimension": "ROWS"});
+            if !values.is_empty() {
+                reply["values"] = Value::Array(values);
+            }
+            Ok((200, reply))
+        }
+        ("PUT", None) => {
+            let values = body["values"].as_array().cloned().unwrap_or_default();
+            let (rows, cols) = write_values(sheet, grid.start_row, grid.start_col, &values);
+            Ok((
+                200,
+                json!({
+                    "spreadsheetId": id,
+                    "updatedRange": a1_of(&sheet_title, grid.start_row, grid.start_col, rows, cols),
+                    "updatedRows": rows,
+                    "updatedColumns": cols,
+                    "updatedCells": rows * cols
+                }),
+            ))
+        }
+        ("POST", Some("append")) => {
+            let values = body["values"].as_array().cloned().unwrap_or_default();
+            let start_row = sheet.values.len();
+            let (rows, cols) = write_values(sheet, start_row, grid.start_col, &values);
+            Ok((
+                200,
+                json!({
+                    "spreadsheetId": id,
+                    "updates": {
+                        "updatedRange": a1_of(&sheet_title, start_row, grid.start_col, rows, cols),
+                        "updatedRows": rows,
+                        "updatedColumns": cols,
+                        "updatedCells": rows * cols
+                    }
+                }),
+            ))
+        }
+        ("POST", Some("clear")) => {
+            let end_row = grid.end_row.unwrap_or(sheet.values.len());
+            for row in sheet.values.iter_mut().take(end_row).skip(grid.start_row) {
+                let end_col = grid.end_col.unwrap_or(row.len()).min(row.len());
+                for cell in row.iter_mut().take(end_col).skip(grid.start_col) {
+                    *cell = json!("");
+                }
+            }
+            Ok((200, json!({"spreadsheetId": id, "clearedRange": range})))
+        }
+        _ => Err(api_error(405, "Method not allowed")),
+    }
+}
+
+fn sheets_batch_update(spreadsheet: &mut FakeSpreadsheet, body: &Value) -> Result<Reply, Reply> {
+    let requests = body["requests"].as_array().cloned().unwrap_or_default();
+    let mut replies = Vec::new();
+
+    for request in &requests {
+        let Some((kind, params)) = request.as_object().and_then(|o| o.iter().next()) else {
+            continue;
+        };
+        let reply = match kind.as_str() {
+            "addSheet" => {
+                let sheet_id = spreadsheet
+                    .sheets
+                    .iter()
+                    .map(|s| s.sheet_id)
+                    .max()
+                    .unwrap_or(-1)
+                    + 1;
+                let title = params["properties"]["title"]
+                    .as_str()
+                    .map(ToString::to_string)
+                    .unwrap_or_else(|| format!("Sheet{}", sheet_id + 1));
+                spreadsheet.sheets.push(FakeSheet {
+                    sheet_id,
+                    title: title.clone(),
+                    values: Vec::new(),
+                });
+                json!({"addSheet": {"properties": {
+                    "sheetId": sheet_id,
+                    "title": title,
+                    "index": spreadsheet.sheets.len() - 1
+                }}})
+            }
+            "deleteSheet" => {
+                let sheet_id = params["sheetId"].as_i64().unwrap_or(-1);
+                let before = spreadsheet.sheets.len();
+                spreadsheet.sheets.retain(|s| s.sheet_id != sheet_id);
+                if spreadsheet.sheets.len() == before {
+                    return Err(api_error(400, &format!("No sheet with id: {sheet_id}")));
+                }
+                json!({})
+            }
+            "updateSheetProperties" => {
+                let props = &params["properties"];
+                let sheet_id = props["sheetId"].as_i64().unwrap_or(-1);
+                let sheet = spreadsheet
+                    .sheets
+                    .iter_mut()
+                    .find(|s| s.sheet_id == sheet_id)
+                    .ok_or_else(|| api_error(400, &format!("No sheet with id: {sheet_id}")))?;
+                if let Some(title) = props["title"].as_str() {
+                    sheet.title = title.to_string();
+                }
+                json!({})
+            }
+            "findReplace" => {
+                let find = params["find"].as_str().unwrap_or_default();
+                let replacement = params["replacement"].as_str().unwrap_or_default();
+                let mut occurrences = 0;
+                let mut values_changed = 0;
+                for sheet in &mut spreadsheet.sheets {
+                    if let Some(id) = params["sheetId"].as_i64()
+                        && sheet.sheet_id != id
+                    {
+                        continue;
+                    }
+                    for cell in sheet.values.iter_mut().flatten() {
+                        if let Some(text) = cell.as_str()
+                            && !find.is_empty()
+                            && text.contains(find)
+                        {
+                            occurrences += text.matches(find).count();
+                            values_changed += 1;
+                            *cell = json!(text.replace(find, replacement));
+                        }
+                    }
+                }
+                json!({"findReplace": {
+                    "occurrencesChanged": occurrences,
+                    "valuesChanged": values_changed,
+                    "sheetsChanged": usize::from(values_changed > 0)
+                }})
+            }
+            "addChart" => json!({"addChart": {"chart": {"chartId": 1001}}}),
+            "addProtectedRange" => json!({"addProtectedRange": {"protectedRange": {
+                "protectedRangeId": 2001,
+                "description": params["protectedRange"]["description"]
+            }}}),
+            _ => json!({}),
+        };
+        replies.push(reply);
+    }
+
+    Ok((
+        200,
+        json!({"spreadsheetId": spreadsheet.id, "replies": replies}),
+    ))
+}
@@ -0,0 +1,247 @@
+#![allow(dead_code)]
+
+pub mod fake_docs;
+pub mod fake_google;
+
+use fake_google::{ACCESS_TOKEN, FakeGoogle};
+use serde_json::{Value, json};
+use std::cell::RefCell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+/// A fake Google backend plus an isolated `HOME` holding a client secret and a
+/// token that the binaries accept without a browser round-trip.
+pub struct TestEnv {
+    pub google: FakeGoogle,
+    pub home: TempDir,
+    extra_env: RefCell<Vec<(String, String)>>,
+}
+
+pub struct Output {
+    pub code: i32,
+    pub json: Value,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl TestEnv {
+    pub fn new() -> Self {
+        let env = Self::without_token();
+        env.write_token(
+            ACCESS_TOKEN,
+            chrono::Utc::now().timestamp_millis() + 3_600_000,
+        );
+        env
+    }
+
+    pub fn without_token() -> Self {
+        let google = FakeGoogle::start();
+        let home = TempDir::new().expect("temp home");
+        let env = Self {
+            google,
+            home,
+            extra_env: RefCell::new(Vec::new()),
+        };
+
+        std::fs::create_dir_all(env.google_dir()).expect("create .google dir");
+        std::fs::write(
+            env.google_dir().join("client_secret.json"),
+            json!({
+                "installed": {
+                    "client_id": "test-client.apps.googleusercontent.com",
+                    "client_secret": "test-secret",
+                    "auth_uri": "https://accounts.google.com/o/oauth2/auth",
+                    "token_uri": "https://oauth2.googleapis.com/token"
+                }
+            })
+            .to_string(),
+        )
+        .expect("write client secret");
+        env
+    }
+
+    pub fn google_dir(&self) -> PathBuf {
+        self.home.path().join(".claude/.google")
+    }
+
+    pub fn token_path(&self) -> PathBuf {
+        self.google_dir().join("token.json")
+    }
+
+    /// Writes a token in the same YAML-wrapped format `save_stored_token` uses,
+    /// granted the Docs, Drive and Sheets scopes.
+    pub fn write_token(&self, access_token: &str, expiration_time_millis: i64) {
+        self.write_token_with_scopes(
+            access_token,
+            expiration_time_millis,
+            &[
+                "https://www.googleapis.com/auth/documents",
+                "https://www.googleapis.com/auth/drive",
+                "https://www.googleapis.com/auth/spreadsheets",
+            ],
+        );
+    }
+
+    pub fn write_token_with_scopes(
+        &self,
+        access_token: &str,
+        expiration_time_millis: i64,
+        scopes: &[&str],
+    ) {
+        let token = json!({
+            "client_id": "test-client.apps.googleusercontent.com",
+            "access_token": access_token,
+            "refresh_token": "test-refresh-token",
+            "scope": scopes,
+            "expiration_time_millis": expiration_time_millis
+        });
+        let yaml = format!("default: '{}'\n", token);
+        std::fs::write(self.token_path(), yaml).expect("write token");
+    }
+
+    /// Replaces the OAuth client secret with the service account key fixture.
+    pub fn use_service_account(&self) {
+        std::fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/service_account.json"),
+            self.google_dir().join("client_secret.json"),
+        )
+        .expect("copy service account key");
+        let _ = std::fs::remove_file(self.token_path());
+    }
+
+    pub fn write_config(&self, config: Value) {
+        std::fs::write(self.google_dir().join("config.json"), config.to_string())
+            .expect("write config");
+    }
+
+    /// Sets an environment variable for every later invocation.
+    pub fn set_env(&self, key: &str, value: &str) {
+        self.extra_env
+            .borrow_mut()
+            .push((key.to_string(), value.to_string()));
+    }
+
+    /// Puts a `secret-tool` stand-in first on `PATH` that keeps each profile's
+    /// secret in a file under the returned directory, and selects the
+    /// secret-service token store.
+    pub fn use_fake_keyring(&self) -> PathBuf {
+        let bin_dir = self.path("bin");
+        let keyring = self.path("keyring");
+        std::fs::create_dir_all(&bin_dir).expect("create bin dir");
+        std::fs::create_dir_all(&keyring).expect("create keyring dir");
+        let script = bin_dir.join("secret-tool");
+        std::fs::write(
+            &script,
+            r#"#!/bin/sh
+cmd=$1; shift
+profile=""; prev=""
+for arg in "$@"; do [ "$prev" = profile ] && profile=$arg; prev=$arg; done
+case $cmd in
+  store) cat > "$FAKE_KEYRING/$profile" ;;
+  lookup) [ -f "$FAKE_KEYRING/$profile" ] && cat "$FAKE_KEYRING/$profile" || exit 1 ;;
+  clear) rm -f "$FAKE_KEYRING/$profile" ;;
+  search) for f in "$FAKE_KEYRING"/*; do [ -f "$f" ] && echo "attribute.profile = ${f##*/}" >&2; done ;;
+esac
+"#,
+        )
+        .expect("write secret-tool");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))
+                .expect("chmod secret-tool");
+        }
+
+        let path = std::env::var("PATH").unwrap_or_default();
+        self.set_env("PATH", &format!("{}:{path}", bin_dir.display()));
+        self.set_env("FAKE_KEYRING", &keyring.display().to_string());
+        self.set_env("GOOGLE_SKILL_TOKEN_STORE", "secret-service");
+        keyring
+    }
+
+    pub fn read_token(&self) -> Value {
+        let raw = std::fs::read_to_string(self.token_path()).expect("read token");
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&raw).expect("token yaml");
+        let inner = yaml["default"].as_str().expect("default key");
+        serde_json::from_str(inner).expect("token json")
+    }
+
+    pub fn command(&self, bin: &str) -> Command {
+        let mut command = Command::new(bin_path(bin));
+        command
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", self.home.path())
+            .env("GOOGLE_SKILL_API_BASE", self.google.base_url())
+            .env("GOOGLE_SKILL_NO_BROWSER", "1")
+            .env("GOOGLE_SKILL_RETRY_BASE_MS", "1")
+            .env("GOOGLE_SKILL_RETRY_MAX_MS", "5")
+            .envs(self.extra_env.borrow().iter().cloned());
+        command
+    }
+
+    /// Runs `bin` with `args`, piping `stdin` (if any) and parsing the first
+    /// JSON document it prints.
+    pub fn run(&self, bin: &str, args: &[&str], stdin: Option<Value>) -> Output {
+        let mut command = self.command(bin);
+        command
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = command.spawn().expect("spawn binary");
+        {
+            let mut pipe = child.stdin.take().expect("stdin");
+            // Binaries that fail before reading stdin close the pipe early.
+            if let Some(input) = stdin {
+                let _ = pipe.write_all(input.to_string().as_bytes());
+            }
+        }
+        let output = child.wait_with_output().expect("wait for binary");
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let json = serde_json::Deserializer::from_str(&stdout)
+            .into_iter::<Value>()
+            .next()
+            .and_then(Result::ok)
+            .unwrap_or(Value::Null);
+
+        Output {
+            code: output.status.code().unwrap_or(-1),
+            json,
+            stdout,
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }
+    }
+
+    pub fn gws(&self, args: &[&str], stdin: Option<Value>) -> Output {
+        self.run("gws", args, stdin)
+    }
+
+    pub fn docs(&self, args: &[&str], stdin: Option<Value>) -> Output {
+        self.run("docs_manager", args, stdin)
+    }
+
+    pub fn drive(&self, args: &[&str]) -> Output {
+        self.run("drive_manager", args, None)
+    }
+
+    pub fn sheets(&self, command: &str, stdin: Value) -> Output {
+        self.run("sheets_manager", &[command], Some(stdin))
+    }
+
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.home.path().join(relative)
+    }
+}
+
+fn bin_path(bin: &str) -> &'static Path {
+    match bin {
+        "gws" => Path::new(env!("CARGO_BIN_EXE_gws")),
+        "docs_manager" => Path::new(env!("CARGO_BIN_EXE_docs_manager")),
+        "drive_manager" => Path::new(env!("CARGO_BIN_EXE_drive_manager")),
+        "sheets_manager" => Path::new(env!("CARGO_BIN_EXE_sheets_manager")),
+        other => panic!("unknown binary: {other}"),
+    }
+}
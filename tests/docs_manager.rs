@@ -0,0 +1,1573 @@
+mod common;
+
+use common::TestEnv;
+use common::fake_google::REFRESHED_ACCESS_TOKEN;
+use serde_json::{Value, json};
+
+#[test]
+fn create_then_read_round_trips_content() {
+    let env = TestEnv::new();
+
+    let created = env.docs(
+        &["create"],
+        Some(json!({"title": "Notes", "content": "Hello world"})),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    assert_eq!(created.json["status"], "success");
+    let document_id = created.json["document_id"].as_str().unwrap().to_string();
+
+    let read = env.docs(&["read", &document_id], None);
+    assert_eq!(read.code, 0, "{}", read.stdout);
+    assert_eq!(read.json["operation"], "read");
+    assert_eq!(read.json["title"], "Notes");
+    assert!(
+        read.json["content"]
+            .as_str()
+            .unwrap()
+            .contains("Hello world")
+    );
+}
+
+#[test]
+fn append_and_replace_edit_the_document() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Draft", "First line\n");
+
+    let appended = env.docs(
+        &["append"],
+        Some(json!({"document_id": document_id, "text": "Second line"})),
+    );
+    assert_eq!(appended.code, 0, "{}", appended.stdout);
+
+    let replaced = env.docs(
+        &["replace"],
+        Some(json!({"document_id": document_id, "find": "line", "replace": "row"})),
+    );
+    assert_eq!(replaced.code, 0, "{}", replaced.stdout);
+
+    let text = env.google.document(&document_id).plain_text();
+    assert!(text.contains("First row"), "{text:?}");
+    assert!(text.contains("Second row"), "{text:?}");
+}
+
+#[test]
+fn markdown_headings_and_bold_are_styled() {
+    let env = TestEnv::new();
+
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({"title": "Report", "markdown": "# Title\n\nSome **bold** text\n"})),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let rendered = env.google.document(document_id).to_json();
+    let paragraphs: Vec<_> = rendered["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e.get("paragraph"))
+        .collect();
+    assert!(
+        paragraphs
+            .iter()
+            .any(|p| p["paragraphStyle"]["namedStyleType"] == "HEADING_1")
+    );
+    assert!(paragraphs.iter().any(|p| {
+        p["elements"].as_array().unwrap().iter().any(|run| {
+            run["textRun"]["content"] == "bold" && run["textRun"]["textStyle"]["bold"] == true
+        })
+    }));
+}
+
+#[test]
+fn markdown_after_emoji_styles_the_right_text() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Notes", "Party 🎉🎉");
+
+    let inserted = env.docs(
+        &["insert-from-markdown"],
+        Some(json!({
+            "document_id": document_id,
+            "markdown": "🚀 Ship **it** now, *then* 😀 **celebrate**\n"
+        })),
+    );
+    assert_eq!(inserted.code, 0, "{}", inserted.stdout);
+    assert_eq!(inserted.json["text_length"], 34);
+
+    let rendered = env.google.document(&document_id).to_json();
+    let styled: Vec<(String, bool, bool)> = rendered["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["paragraph"]["elements"].as_array())
+        .flatten()
+        .map(|run| {
+            let style = &run["textRun"]["textStyle"];
+            (
+                run["textRun"]["content"].as_str().unwrap().to_string(),
+                style["bold"] == true,
+                style["italic"] == true,
+            )
+        })
+        .filter(|(_, bold, italic)| *bold || *italic)
+        .collect();
+    assert_eq!(
+        styled,
+        vec![
+            ("it".to_string(), true, false),
+            ("then".to_string(), false, true),
+            ("celebrate".to_string(), true, false),
+        ]
+    );
+}
+
+#[test]
+fn markdown_lists_become_native_lists() {
+    let env = TestEnv::new();
+
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({
+            "title": "Plan",
+            "markdown": "- Design\n  - **API** review\n- Build\n\n1. First\n2. Second\n\n| A | B |\n|---|---|\n| x | y |\n"
+        })),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let document = env.google.document(document_id);
+    assert_eq!(
+        document.plain_text(),
+        "Design\nAPI review\nBuild\n\nFirst\nSecond\n\n\nA\nB\nx\ny\n\n\n"
+    );
+    let rendered = document.to_json();
+    let items: Vec<(String, Value)> = rendered["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e.get("paragraph"))
+        .filter_map(|p| {
+            let bullet = p.get("bullet")?;
+            let text = p["elements"][0]["textRun"]["content"].as_str()?;
+            Some((text.to_string(), bullet.clone()))
+        })
+        .collect();
+    let texts: Vec<&str> = items.iter().map(|(text, _)| text.as_str()).collect();
+    assert_eq!(texts, ["Design\n", "API", "Build\n", "First\n", "Second\n"]);
+    assert_eq!(items[1].1["nestingLevel"], 1);
+    assert!(items[0].1.get("nestingLevel").is_none());
+    assert_eq!(items[0].1["listId"], items[2].1["listId"]);
+    assert_ne!(items[0].1["listId"], items[3].1["listId"]);
+
+    let numbered = items[3].1["listId"].as_str().unwrap();
+    assert_eq!(
+        rendered["lists"][numbered]["listProperties"]["nestingLevels"][0]["glyphType"],
+        "DECIMAL"
+    );
+    let bold = &rendered["body"]["content"][2]["paragraph"]["elements"][0]["textRun"];
+    assert_eq!(bold["content"], "API");
+    assert_eq!(bold["textStyle"]["bold"], true);
+}
+
+#[test]
+fn markdown_links_quotes_code_and_images_are_created() {
+    let env = TestEnv::new();
+
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({
+            "title": "Guide",
+            "markdown": "See [docs](https://example.com/docs) ~~now~~\n\n> Quote\n\n```\ncode\n```\n\n- a\n  - ![logo](https://example.com/logo.png) b\n\nEnd\n"
+        })),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let document = env.google.document(document_id);
+    assert_eq!(
+        document.plain_text(),
+        "See docs now\n\nQuote\n\ncode\n\na\n b\n\nEnd\n\n"
+    );
+    let rendered = document.to_json();
+    let paragraphs: Vec<&Value> = rendered["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e.get("paragraph"))
+        .collect();
+    let run = |paragraph: usize, element: usize| &paragraphs[paragraph]["elements"][element];
+
+    assert_eq!(run(0, 1)["textRun"]["content"], "docs");
+    assert_eq!(
+        run(0, 1)["textRun"]["textStyle"]["link"]["url"],
+        "https://example.com/docs"
+    );
+    assert_eq!(run(0, 3)["textRun"]["content"], "now");
+    assert_eq!(run(0, 3)["textRun"]["textStyle"]["strikethrough"], true);
+    assert_eq!(
+        paragraphs[2]["paragraphStyle"]["indentStart"]["magnitude"],
+        36.0
+    );
+    assert_eq!(
+        run(4, 0)["textRun"]["textStyle"]["weightedFontFamily"]["fontFamily"],
+        "Courier New"
+    );
+    assert_eq!(paragraphs[7]["bullet"]["nestingLevel"], 1);
+    assert!(
+        run(7, 0).get("inlineObjectElement").is_some(),
+        "{}",
+        paragraphs[7]
+    );
+    assert_eq!(run(7, 1)["textRun"]["content"], " b\n");
+}
+
+#[test]
+fn markdown_export_round_trips() {
+    let env = TestEnv::new();
+    let markdown = "# Plan\n\nIntro with **bold**, *italic* and `code`, see [docs](https://example.com/docs).\n\n- Design\n    - **API** review\n- Build\n\n1. First\n2. Second\n\n- [ ] Ship ~~it~~\n\n> Quote\n\n```\nfn main() {}\n```\n\n| A | B |\n| --- | --- |\n| x | y |\n\n---\n![](https://example.com/logo.png) End\n";
+
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({"title": "Plan", "markdown": markdown})),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let exported = env.docs(&["export-markdown", document_id], None);
+    assert_eq!(exported.code, 0, "{}", exported.stdout);
+    assert_eq!(exported.json["operation"], "export_markdown");
+    assert_eq!(exported.json["title"], "Plan");
+    assert_eq!(exported.json["markdown"], markdown);
+}
+
+#[test]
+fn markdown_export_keeps_entities_and_indentation_literal() {
+    let env = TestEnv::new();
+    let text = "Write &lt; as <, & so on\n    # not a heading\n  1. not a list\n";
+    let document_id = env.google.add_document("Literal", text);
+
+    let exported = env.docs(&["export-markdown", &document_id], None);
+    assert_eq!(exported.code, 0, "{}", exported.stdout);
+    assert_eq!(
+        exported.json["markdown"],
+        "Write \\&lt; as \\<, \\& so on\n\n&#32;&#32;&#32;&#32;\\# not a heading\n\n&#32;&#32;1\\. not a list\n"
+    );
+
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({"title": "Copy", "markdown": exported.json["markdown"]})),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let copy = created.json["document_id"].as_str().unwrap();
+    assert_eq!(
+        env.google.document(copy).plain_text(),
+        "Write &lt; as <, & so on\n\n    # not a heading\n\n  1. not a list\n\n"
+    );
+}
+
+#[test]
+fn markdown_export_escapes_pipes_in_table_code() {
+    let env = TestEnv::new();
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({
+            "title": "Ops",
+            "markdown": "| Expr | Means |\n| --- | --- |\n| a \\| b | either |\n"
+        })),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let found = env.docs(
+        &["find"],
+        Some(json!({"document_id": document_id, "query": "a | b"})),
+    );
+    assert_eq!(found.code, 0, "{}", found.stdout);
+    let range = &found.json["matches"][0];
+    env.google.apply_to_document(
+        document_id,
+        json!({"updateTextStyle": {
+            "range": {"startIndex": range["start_index"], "endIndex": range["end_index"]},
+            "textStyle": {"weightedFontFamily": {"fontFamily": "Courier New"}},
+            "fields": "weightedFontFamily"
+        }}),
+    );
+
+    let exported = env.docs(&["export-markdown", document_id], None);
+    assert_eq!(exported.code, 0, "{}", exported.stdout);
+    assert_eq!(
+        exported.json["markdown"],
+        "| Expr | Means |\n| --- | --- |\n| `a \\| b` | either |\n"
+    );
+}
+
+#[test]
+fn find_returns_ranges_that_can_be_formatted() {
+    let env = TestEnv::new();
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({"title": "Plan", "markdown": "# Plan 🚀\n\nShip it, then ship again.\n"})),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let found = env.docs(
+        &["find"],
+        Some(json!({"document_id": document_id, "query": "ship"})),
+    );
+    assert_eq!(found.code, 0, "{}", found.stdout);
+    assert_eq!(found.json["operation"], "find");
+    let matches = found.json["matches"].as_array().unwrap();
+    let ranges: Vec<(i64, i64)> = matches
+        .iter()
+        .map(|m| {
+            (
+                m["start_index"].as_i64().unwrap(),
+                m["end_index"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(ranges, [(10, 14), (24, 28)]);
+    assert_eq!(matches[0]["text"], "Ship");
+    assert_eq!(matches[0]["paragraph"]["text"], "Ship it, then ship again.");
+    assert_eq!(matches[0]["heading"]["text"], "Plan 🚀\n");
+    assert_eq!(matches[0]["heading"]["level"], 1);
+
+    for (start, end) in ranges {
+        let formatted = env.docs(
+            &["format"],
+            Some(json!({
+                "document_id": document_id,
+                "start_index": start,
+                "end_index": end,
+                "bold": true
+            })),
+        );
+        assert_eq!(formatted.code, 0, "{}", formatted.stdout);
+    }
+    let rendered = env.google.document(document_id).to_json();
+    let bold: Vec<&str> = rendered["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["paragraph"]["elements"].as_array())
+        .flatten()
+        .filter(|run| run["textRun"]["textStyle"]["bold"] == true)
+        .filter_map(|run| run["textRun"]["content"].as_str())
+        .collect();
+    assert_eq!(bold, ["Ship", "ship"]);
+
+    let regex = env.docs(
+        &["find"],
+        Some(json!({
+            "document_id": document_id,
+            "query": "\\bs\\w+",
+            "regex": true,
+            "match_case": true
+        })),
+    );
+    assert_eq!(regex.code, 0, "{}", regex.stdout);
+    assert_eq!(regex.json["matches"][0]["text"], "ship");
+    assert_eq!(regex.json["matches"].as_array().unwrap().len(), 1);
+
+    let invalid = env.docs(
+        &["find"],
+        Some(json!({"document_id": document_id, "query": "(", "regex": true})),
+    );
+    assert_eq!(invalid.code, 4);
+    assert_eq!(invalid.json["error_code"], "INVALID_PATTERN");
+}
+
+#[test]
+fn anchors_resolve_headings_sections_text_and_tables() {
+    let env = TestEnv::new();
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({
+            "title": "Plan",
+            "markdown": "# Plan\n\nIntro\n\n| A |\n|---|\n| x |\n\n## Risks\nOld risk\n### Detail\nMore\n## Next\nTBD\n"
+        })),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let inserted = env.docs(
+        &["insert"],
+        Some(json!({
+            "document_id": document_id,
+            "text": "New risk\n",
+            "anchor": {"after_heading": "Risks"}
+        })),
+    );
+    assert_eq!(inserted.code, 0, "{}", inserted.stdout);
+
+    let replaced = env.docs(
+        &["insert-from-markdown"],
+        Some(json!({
+            "document_id": document_id,
+            "markdown": "Done\n",
+            "anchor": {"section": "Next"}
+        })),
+    );
+    assert_eq!(replaced.code, 0, "{}", replaced.stdout);
+
+    let deleted = env.docs(
+        &["delete"],
+        Some(json!({"document_id": document_id, "anchor": {"text": "Intro\n"}})),
+    );
+    assert_eq!(deleted.code, 0, "{}", deleted.stdout);
+
+    let after_table = env.docs(
+        &["insert"],
+        Some(json!({
+            "document_id": document_id,
+            "text": "Footer",
+            "anchor": {"after_table": 1}
+        })),
+    );
+    assert_eq!(after_table.code, 0, "{}", after_table.stdout);
+
+    assert_eq!(
+        env.google.document(document_id).plain_text(),
+        "Plan\n\n\n\nA\nx\nFooter\n\nRisks\nNew risk\nOld risk\nDetail\nMore\nNext\nDone\n\n"
+    );
+
+    let missing = env.docs(
+        &["insert"],
+        Some(
+            json!({"document_id": document_id, "text": "x", "anchor": {"after_heading": "Budget"}}),
+        ),
+    );
+    assert_eq!(missing.code, 1);
+    assert_eq!(missing.json["error_code"], "ANCHOR_NOT_FOUND");
+
+    let point = env.docs(
+        &["delete"],
+        Some(json!({"document_id": document_id, "anchor": {"before_heading": "Risks"}})),
+    );
+    assert_eq!(point.code, 1);
+    assert_eq!(point.json["error_code"], "ANCHOR_NOT_RANGE");
+
+    let invalid = env.docs(
+        &["insert"],
+        Some(json!({"document_id": document_id, "text": "x", "anchor": {"near": "Risks"}})),
+    );
+    assert_eq!(invalid.code, 4);
+    assert_eq!(invalid.json["error_code"], "INVALID_ANCHOR");
+
+    let before = env.google.document(document_id).plain_text();
+    let with_index = env.docs(
+        &[
+            "insert",
+            "--document-id",
+            document_id,
+            "--text",
+            "x",
+            "--index",
+            "1",
+            "--anchor",
+            r#"{"after_heading": "Risks"}"#,
+        ],
+        None,
+    );
+    assert_eq!(with_index.code, 4, "{}", with_index.stdout);
+    assert_eq!(with_index.json["error_code"], "INVALID_ARGUMENTS");
+
+    let with_range = env.docs(
+        &["delete"],
+        Some(json!({
+            "document_id": document_id,
+            "anchor": {"section": "Risks"},
+            "start_index": 1,
+            "end_index": 3
+        })),
+    );
+    assert_eq!(with_range.code, 4, "{}", with_range.stdout);
+    assert_eq!(with_range.json["error_code"], "INVALID_ARGUMENTS");
+    assert_eq!(env.google.document(document_id).plain_text(), before);
+}
+
+#[test]
+fn anchor_edits_go_in_one_batch_and_follow_a_closing_heading() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Plan", "Plan\nOld\nRisks");
+    for (start, end) in [(1, 5), (10, 15)] {
+        env.google.apply_to_document(
+            &document_id,
+            json!({"updateParagraphStyle": {
+                "range": {"startIndex": start, "endIndex": end},
+                "paragraphStyle": {"namedStyleType": "HEADING_1"},
+                "fields": "namedStyleType"
+            }}),
+        );
+    }
+
+    let replaced = env.docs(
+        &["insert-from-markdown"],
+        Some(json!({
+            "document_id": document_id,
+            "markdown": "**New**\n",
+            "anchor": {"section": "Plan"}
+        })),
+    );
+    assert_eq!(replaced.code, 0, "{}", replaced.stdout);
+    let batches: Vec<Value> = env
+        .google
+        .requests()
+        .iter()
+        .filter(|request| request.path.ends_with(":batchUpdate"))
+        .map(|request| request.body["requests"].clone())
+        .collect();
+    // The section is deleted in the same batch that inserts its replacement.
+    let [batch] = batches.as_slice() else {
+        panic!("{batches:?}");
+    };
+    assert!(batch[0].get("deleteContentRange").is_some(), "{batch}");
+    assert!(batch[1].get("insertText").is_some(), "{batch}");
+
+    // "Risks" is the last paragraph, so the text goes after it in a new one.
+    let inserted = env.docs(
+        &["insert"],
+        Some(json!({
+            "document_id": document_id,
+            "text": "Vendor delay\n",
+            "anchor": {"after_heading": "Risks"}
+        })),
+    );
+    assert_eq!(inserted.code, 0, "{}", inserted.stdout);
+    assert_eq!(inserted.json["inserted_at"], 16);
+
+    let document = env.google.document(&document_id);
+    assert_eq!(document.plain_text(), "Plan\nNew\nRisks\nVendor delay\n");
+    assert_eq!(
+        document.paragraph_styles(),
+        vec![
+            ("Plan\n".to_string(), Some("HEADING_1".to_string())),
+            ("New\n".to_string(), Some("NORMAL_TEXT".to_string())),
+            ("Risks\n".to_string(), Some("HEADING_1".to_string())),
+            (
+                "Vendor delay\n".to_string(),
+                Some("NORMAL_TEXT".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn replace_section_rewrites_content_under_a_heading_path() {
+    let env = TestEnv::new();
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({
+            "title": "Weekly",
+            "markdown": "# Archive\n## Blockers\nOld news\n# Status\n## Blockers\nLegal review\n### Detail\nWaiting\n## Wins\nShipped\n"
+        })),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let replaced = env.docs(
+        &["replace-section"],
+        Some(json!({
+            "document_id": document_id,
+            "heading": "Status > Blockers",
+            "markdown": "- **Vendor** delay\n- Hiring\n"
+        })),
+    );
+    assert_eq!(replaced.code, 0, "{}", replaced.stdout);
+    assert_eq!(replaced.json["operation"], "replace_section");
+    let start = replaced.json["start_index"].as_i64().unwrap();
+    let end = replaced.json["end_index"].as_i64().unwrap();
+    assert_eq!(end - start, "Vendor delay\nHiring\n".len() as i64);
+    assert_eq!(
+        replaced.json["removed_length"],
+        "Legal review\nDetail\nWaiting\n".len()
+    );
+
+    let document = env.google.document(document_id);
+    assert_eq!(
+        document.plain_text(),
+        "Archive\nBlockers\nOld news\nStatus\nBlockers\nVendor delay\nHiring\nWins\nShipped\n\n"
+    );
+    let rendered = document.to_json();
+    let paragraphs: Vec<(String, Value, bool)> = rendered["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e.get("paragraph"))
+        .map(|p| {
+            let text: String = p["elements"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(|run| run["textRun"]["content"].as_str())
+                .collect();
+            (
+                text,
+                p["paragraphStyle"]["namedStyleType"].clone(),
+                p.get("bullet").is_some(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        paragraphs[5],
+        ("Vendor delay\n".into(), json!("NORMAL_TEXT"), true)
+    );
+    assert_eq!(
+        paragraphs[6],
+        ("Hiring\n".into(), json!("NORMAL_TEXT"), true)
+    );
+    assert_eq!(paragraphs[7], ("Wins\n".into(), json!("HEADING_2"), false));
+
+    let missing = env.docs(
+        &["replace-section"],
+        Some(json!({"document_id": document_id, "heading": "Wins > Blockers", "markdown": "x\n"})),
+    );
+    assert_eq!(missing.code, 1);
+    assert_eq!(missing.json["error_code"], "ANCHOR_NOT_FOUND");
+}
+
+#[test]
+fn replace_section_is_one_batch_even_after_the_last_heading() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Weekly", "Intro\nStatus");
+    env.google.apply_to_document(
+        &document_id,
+        json!({"updateParagraphStyle": {
+            "range": {"startIndex": 7, "endIndex": 13},
+            "paragraphStyle": {"namedStyleType": "HEADING_1"},
+            "fields": "namedStyleType"
+        }}),
+    );
+
+    let replaced = env.docs(
+        &["replace-section"],
+        Some(json!({
+            "document_id": document_id,
+            "heading": "Status",
+            "markdown": "- **Done**\n"
+        })),
+    );
+    assert_eq!(replaced.code, 0, "{}", replaced.stdout);
+    assert_eq!(replaced.json["start_index"], 14);
+    assert_eq!(replaced.json["end_index"], 19);
+    assert_eq!(replaced.json["removed_length"], 0);
+
+    let rewritten = env.docs(
+        &["replace-section"],
+        Some(json!({"document_id": document_id, "heading": "Status", "markdown": "Shipped\n"})),
+    );
+    assert_eq!(rewritten.code, 0, "{}", rewritten.stdout);
+    // The body's final newline stays, so "Done" goes but its paragraph doesn't.
+    assert_eq!(rewritten.json["removed_length"], 4);
+
+    // Delete, insert, style reset and formats travel together, so a failed
+    // batch leaves the old section in place.
+    let batches: Vec<Value> = env
+        .google
+        .requests()
+        .iter()
+        .filter(|request| request.path.ends_with(":batchUpdate"))
+        .map(|request| request.body.clone())
+        .collect();
+    assert_eq!(batches.len(), 2, "{batches:?}");
+    let kinds: Vec<&String> = batches[1]["requests"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|request| request.as_object()?.keys().next())
+        .collect();
+    assert_eq!(
+        kinds,
+        ["deleteContentRange", "insertText", "updateParagraphStyle"]
+    );
+    assert!(batches[1]["writeControl"]["requiredRevisionId"].is_string());
+
+    let document = env.google.document(&document_id);
+    assert_eq!(document.plain_text(), "Intro\nStatus\nShipped\n\n");
+    assert_eq!(
+        document.paragraph_styles()[1..3],
+        [
+            ("Status\n".to_string(), Some("HEADING_1".to_string())),
+            ("Shipped\n".to_string(), Some("NORMAL_TEXT".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn render_template_fills_placeholders_rows_and_markdown() {
+    let env = TestEnv::new();
+    let template = env.docs(
+        &["create-from-markdown"],
+        Some(json!({
+            "title": "Invoice template",
+            "markdown": "Invoice for {{name}}\n\n| Item | Price |\n|---|---|\n| {{#each items}}{{item}} | {{price}}{{/each}} |\n\n{{notes}}\n\nDue {{ due }}, ref {{missing}}\n"
+        })),
+    );
+    assert_eq!(template.code, 0, "{}", template.stdout);
+    let template_id = template.json["document_id"].as_str().unwrap();
+
+    let rendered = env.docs(
+        &["render-template"],
+        Some(json!({
+            "template_id": template_id,
+            "title": "Invoice {{name}}",
+            "data": {
+                "name": "Ada",
+                "due": 30,
+                "items": [{"item": "Widget", "price": 5}, {"item": "Gadget", "price": 7.5}],
+                "notes": {"markdown": "**Paid** in full"}
+            }
+        })),
+    );
+    assert_eq!(rendered.code, 0, "{}", rendered.stdout);
+    assert_eq!(rendered.json["operation"], "render_template");
+    assert_eq!(rendered.json["count"], 1);
+    let copy = &rendered.json["documents"][0];
+    assert_eq!(copy["title"], "Invoice Ada");
+    assert_eq!(copy["rows_rendered"], 2);
+    assert_eq!(copy["replacements"], 3);
+    assert_eq!(copy["unresolved"], json!(["{{missing}}"]));
+
+    let document = env.google.document(copy["document_id"].as_str().unwrap());
+    assert_eq!(
+        document.plain_text(),
+        "Invoice for Ada\n\n\nItem\nPrice\nWidget\n5\nGadget\n7.5\n\n\nPaid in full\n\nDue 30, ref {{missing}}\n\n"
+    );
+    let bold: Vec<String> = document.to_json()["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["paragraph"]["elements"].as_array())
+        .flatten()
+        .filter(|run| run["textRun"]["textStyle"]["bold"] == true)
+        .filter_map(|run| run["textRun"]["content"].as_str().map(str::to_string))
+        .collect();
+    assert_eq!(bold, ["Paid"]);
+    // The Markdown field and the text placeholders are filled in one batch.
+    let fill = env
+        .google
+        .requests()
+        .into_iter()
+        .filter(|request| request.path.ends_with(":batchUpdate"))
+        .find(|request| request.body.to_string().contains("replaceAllText"))
+        .unwrap();
+    let kinds: Vec<String> = fill.body["requests"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|request| request.as_object()?.keys().next().cloned())
+        .collect();
+    assert_eq!(
+        kinds[..3],
+        ["deleteContentRange", "insertText", "updateTextStyle"]
+    );
+    assert!(kinds.ends_with(&["replaceAllText".to_string(), "replaceAllText".to_string()]));
+    assert!(
+        env.google
+            .document(template_id)
+            .plain_text()
+            .contains("{{#each items}}"),
+        "the template itself is left untouched"
+    );
+
+    let sheet = env.sheets("create", json!({"title": "Customers"}));
+    let spreadsheet_id = sheet.json["spreadsheet_id"].as_str().unwrap().to_string();
+    let written = env.sheets(
+        "write",
+        json!({
+            "spreadsheet_id": spreadsheet_id,
+            "range": "Sheet1!A1:B3",
+            "values": [["name", "due"], ["Grace", 14], ["Linus", 7]]
+        }),
+    );
+    assert_eq!(written.code, 0, "{}", written.stdout);
+
+    let batch = env.docs(
+        &[
+            "render-template",
+            template_id,
+            "--title",
+            "Invoice {{name}}",
+            "--spreadsheet-id",
+            &spreadsheet_id,
+            "--range",
+            "Sheet1!A1:B3",
+        ],
+        None,
+    );
+    assert_eq!(batch.code, 0, "{}", batch.stdout);
+    assert_eq!(batch.json["count"], 2);
+    let titles: Vec<&str> = batch.json["documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|copy| copy["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, ["Invoice Grace", "Invoice Linus"]);
+    let second = &batch.json["documents"][1];
+    assert_eq!(second["rows_rendered"], 0);
+    assert_eq!(
+        env.google
+            .document(second["document_id"].as_str().unwrap())
+            .plain_text(),
+        "Invoice for Linus\n\n\nItem\nPrice\n\n\n{{notes}}\n\nDue 7, ref {{missing}}\n\n"
+    );
+
+    let ambiguous = env.docs(
+        &["render-template"],
+        Some(json!({"template_id": template_id, "data": {}, "records": []})),
+    );
+    assert_eq!(ambiguous.code, 4);
+    assert_eq!(ambiguous.json["error_code"], "MISSING_DATA");
+}
+
+#[test]
+fn render_template_reports_each_record() {
+    let env = TestEnv::new();
+    let template_id = env.google.add_document("Letter", "Dear {{name}}\n");
+
+    // The first record's copy is rejected; the second is still made.
+    env.google.fail_next(400, 1, None);
+    let rendered = env.docs(
+        &["render-template"],
+        Some(json!({
+            "template_id": template_id,
+            "title": "Letter to {{name}}",
+            "records": [{"name": "Ada"}, {"name": "Grace"}]
+        })),
+    );
+    assert_eq!(rendered.code, 1, "{}", rendered.stdout);
+    assert_eq!(rendered.json["error_code"], "RENDER_FAILED");
+    assert_eq!(rendered.json["count"], 1);
+    assert_eq!(rendered.json["documents"][0]["title"], "Letter to Grace");
+    assert_eq!(rendered.json["failed"][0]["record"], 0);
+    assert_eq!(rendered.json["failed"][0]["error_code"], "API_ERROR");
+    assert_eq!(rendered.json["failed"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn render_template_asks_for_sheets_only_for_a_sheet_source() {
+    let env = TestEnv::new();
+    env.write_token_with_scopes(
+        "test-access-token",
+        chrono::Utc::now().timestamp_millis() + 3_600_000,
+        &[
+            "https://www.googleapis.com/auth/documents",
+            "https://www.googleapis.com/auth/drive",
+        ],
+    );
+    let template_id = env.google.add_document("Letter", "Dear {{name}}\n");
+
+    let from_data = env.docs(
+        &["render-template"],
+        Some(json!({"template_id": template_id, "data": {"name": "Ada"}})),
+    );
+    assert_eq!(from_data.code, 0, "{}", from_data.stdout);
+
+    let from_sheet = env.docs(
+        &[
+            "render-template",
+            &template_id,
+            "--spreadsheet-id",
+            "sheet123",
+            "--range",
+            "A1:B2",
+        ],
+        None,
+    );
+    assert_eq!(from_sheet.code, 2, "{}", from_sheet.stdout);
+    assert_eq!(from_sheet.json["error_code"], "SCOPES_MISSING");
+    assert_eq!(
+        from_sheet.json["missing_scopes"],
+        json!(["https://www.googleapis.com/auth/spreadsheets"])
+    );
+}
+
+#[test]
+fn html_export_uses_semantic_elements() {
+    let env = TestEnv::new();
+
+    let created = env.docs(
+        &["create-from-markdown"],
+        Some(json!({
+            "title": "Notes",
+            "markdown": "## Intro\n\nA <b> & [**bold** link](https://example.com/?a=1&b=2)\n\n- one\n    - two\n- three\n\n> Quoted\n\n| A | B |\n|---|---|\n| x | y |\n"
+        })),
+    );
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let document_id = created.json["document_id"].as_str().unwrap();
+
+    let exported = env.docs(&["export-html", document_id], None);
+    assert_eq!(exported.code, 0, "{}", exported.stdout);
+    assert_eq!(exported.json["operation"], "export_html");
+    assert_eq!(
+        exported.json["html"],
+        concat!(
+            "<h2>Intro</h2>\n",
+            "<p>A &lt;b&gt; &amp; <a href=\"https://example.com/?a=1&amp;b=2\"><strong>bold</strong> link</a></p>\n",
+            "<ul>\n<li>one\n<ul>\n<li>two</li>\n</ul>\n</li>\n<li>three</li>\n</ul>\n",
+            "<blockquote>\n<p>Quoted</p>\n</blockquote>\n",
+            "<table>\n<thead>\n<tr><th>A</th><th>B</th></tr>\n</thead>\n",
+            "<tbody>\n<tr><td>x</td><td>y</td></tr>\n</tbody>\n</table>\n",
+        )
+    );
+}
+
+#[test]
+fn text_export_maps_offsets_to_document_indices() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Notes", "Intro 🎉\nShip it today");
+
+    let exported = env.docs(&["export-text", &document_id], None);
+    assert_eq!(exported.code, 0, "{}", exported.stdout);
+    let text = exported.json["text"].as_str().unwrap();
+    assert_eq!(text, "Intro 🎉\n\nShip it today\n");
+    let index_map = &exported.json["index_map"];
+    assert_eq!(
+        index_map,
+        &json!([
+            {"offset": 0, "index": 1, "length": 9},
+            {"offset": 10, "index": 10, "length": 14}
+        ])
+    );
+
+    let offset = text[..text.find("it").unwrap()].encode_utf16().count() as i64;
+    let span = &index_map[1];
+    let start = span["index"].as_i64().unwrap() + offset - span["offset"].as_i64().unwrap();
+    let formatted = env.docs(
+        &["format"],
+        Some(json!({
+            "document_id": document_id,
+            "start_index": start,
+            "end_index": start + 2,
+            "bold": true
+        })),
+    );
+    assert_eq!(formatted.code, 0, "{}", formatted.stdout);
+
+    let rendered = env.google.document(&document_id).to_json();
+    let bold: Vec<&str> = rendered["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["paragraph"]["elements"].as_array())
+        .flatten()
+        .filter(|run| run["textRun"]["textStyle"]["bold"] == true)
+        .filter_map(|run| run["textRun"]["content"].as_str())
+        .collect();
+    assert_eq!(bold, ["it"]);
+}
+
+#[test]
+fn insert_table_fills_cells() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Tables", "Intro\n");
+
+    let inserted = env.docs(
+        &["insert-table"],
+        Some(json!({
+            "document_id": document_id,
+            "rows": 2,
+            "cols": 2,
+            "data": [["A", "B"], ["1", "2"]]
+        })),
+    );
+    assert_eq!(inserted.code, 0, "{}", inserted.stdout);
+
+    let structure = env.docs(&["structure", &document_id], None);
+    assert_eq!(structure.code, 0, "{}", structure.stdout);
+    let text = env.google.document(&document_id).plain_text();
+    for cell in ["A", "B", "1", "2"] {
+        assert!(text.contains(cell), "{text:?}");
+    }
+}
+
+#[test]
+fn missing_fields_exit_with_invalid_args() {
+    let env = TestEnv::new();
+
+    let output = env.docs(&["insert"], Some(json!({"text": "no id"})));
+    assert_eq!(output.code, 4);
+    assert_eq!(output.json["error_code"], "MISSING_REQUIRED_FIELDS");
+}
+
+#[test]
+fn unknown_document_is_an_api_error() {
+    let env = TestEnv::new();
+
+    let output = env.docs(&["read", "does-not-exist"], None);
+    assert_eq!(output.code, 3);
+    assert_eq!(output.json["error_code"], "API_ERROR");
+    assert_eq!(output.json["operation"], "read");
+}
+
+#[test]
+fn transient_failures_are_retried_and_reported() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Flaky", "Body\n");
+    env.google.fail_next(503, 2, None);
+
+    let output = env.docs(&["read", &document_id], None);
+    assert_eq!(output.code, 0, "{}", output.stdout);
+    assert_eq!(output.json["retries"], 2);
+}
+
+#[test]
+fn batch_updates_are_not_retried_on_server_errors() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Flaky", "Body\n");
+    env.google.fail_next(503, 2, None);
+
+    let output = env.docs(
+        &["insert"],
+        Some(json!({"document_id": document_id, "text": "Once ", "index": 1})),
+    );
+    assert_eq!(output.code, 3, "{}", output.stdout);
+    assert_eq!(output.json["error_code"], "API_ERROR");
+    assert!(output.json.get("retries").is_none(), "{}", output.stdout);
+    let sent = env
+        .google
+        .requests()
+        .iter()
+        .filter(|request| request.path.ends_with(":batchUpdate"))
+        .count();
+    assert_eq!(sent, 1);
+}
+
+#[test]
+fn missing_token_requires_authorization() {
+    let env = TestEnv::without_token();
+
+    let output = env.docs(&["read", "anything"], None);
+    assert_eq!(output.code, 2);
+    assert_eq!(output.json["error_code"], "AUTH_REQUIRED");
+    assert_eq!(output.json["login_command"], "docs_manager auth login");
+    assert!(output.json.get("auth_url").is_none(), "{}", output.stdout);
+    assert!(env.google.requests().is_empty());
+}
+
+#[test]
+fn expired_token_is_refreshed_and_saved() {
+    let env = TestEnv::new();
+    env.write_token("stale-token", 0);
+    let document_id = env.google.add_document("Doc", "Body\n");
+
+    let output = env.docs(&["read", &document_id], None);
+    assert_eq!(output.code, 0, "{}", output.stdout);
+    assert_eq!(env.read_token()["access_token"], REFRESHED_ACCESS_TOKEN);
+    assert!(
+        env.google
+            .requests()
+            .iter()
+            .any(|r| r.path == "/token" && r.body["grant_type"] == "refresh_token")
+    );
+}
+
+#[test]
+fn auth_login_captures_loopback_redirect_with_pkce() {
+    use base64::Engine;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::process::Stdio;
+
+    let env = TestEnv::without_token();
+    let mut child = env
+        .command("docs_manager")
+        .args(["auth", "login"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stderr = BufReader::new(child.stderr.take().unwrap());
+    let auth_url = loop {
+        let mut line = String::new();
+        assert!(
+            stderr.read_line(&mut line).unwrap() > 0,
+            "no auth URL printed"
+        );
+        if let Some(url) = line.trim().strip_prefix("http") {
+            break url::Url::parse(&format!("http{url}")).unwrap();
+        }
+    };
+    let param = |key: &str| {
+        auth_url
+            .query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+            .unwrap()
+    };
+    let redirect_uri = param("redirect_uri");
+    assert!(redirect_uri.starts_with("http://127.0.0.1:"));
+    assert_eq!(param("code_challenge_method"), "S256");
+
+    let target = format!("/?code=loopback-code&state={}", param("state"));
+    let mut stream =
+        std::net::TcpStream::connect(redirect_uri.trim_start_matches("http://")).unwrap();
+    write!(stream, "GET {target} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n").unwrap();
+    let mut page = String::new();
+    stream.read_to_string(&mut page).unwrap();
+    assert!(page.starts_with("HTTP/1.1 200"), "{page}");
+
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(env.read_token()["access_token"], REFRESHED_ACCESS_TOKEN);
+
+    let exchange = env
+        .google
+        .requests()
+        .into_iter()
+        .find(|r| r.path == "/token")
+        .expect("token exchange");
+    assert_eq!(exchange.body["code"], "loopback-code");
+    assert_eq!(exchange.body["redirect_uri"], redirect_uri.as_str());
+    let verifier = exchange.body["code_verifier"].as_str().unwrap();
+    let digest = ring::digest::digest(&ring::digest::SHA256, verifier.as_bytes());
+    assert_eq!(
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest.as_ref()),
+        param("code_challenge")
+    );
+}
+
+#[test]
+fn profiles_keep_separate_tokens() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Shared", "Body\n");
+
+    let switched = env.docs(&["auth", "use", "work"], None);
+    assert_eq!(switched.code, 0, "{}", switched.stdout);
+    assert_eq!(switched.json["has_token"], false);
+
+    let output = env.docs(&["read", &document_id], None);
+    assert_eq!(output.code, 2, "{}", output.stdout);
+    assert_eq!(output.json["error_code"], "AUTH_REQUIRED");
+
+    let output = env.docs(&["read", &document_id, "--profile", "default"], None);
+    assert_eq!(output.code, 0, "{}", output.stdout);
+
+    let authorized = env.docs(&["auth", "device"], None);
+    assert_eq!(authorized.code, 0, "{}", authorized.stdout);
+    assert!(authorized.stdout.contains("\"profile\": \"work\""));
+
+    let output = env.docs(&["read", &document_id], None);
+    assert_eq!(output.code, 0, "{}", output.stdout);
+    assert_eq!(
+        env.read_token()["access_token"],
+        common::fake_google::ACCESS_TOKEN
+    );
+
+    let listed = env.docs(&["auth", "list-profiles"], None);
+    assert_eq!(listed.code, 0, "{}", listed.stdout);
+    assert_eq!(listed.json["active_profile"], "work");
+    let names: Vec<_> = listed.json["profiles"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| {
+            (
+                p["name"].as_str().unwrap(),
+                p["has_token"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(names, vec![("default", true), ("work", true)]);
+}
+
+#[test]
+fn missing_scope_asks_for_incremental_consent() {
+    let env = TestEnv::new();
+    env.write_token_with_scopes(
+        "test-access-token",
+        chrono::Utc::now().timestamp_millis() + 3_600_000,
+        &["https://www.googleapis.com/auth/drive"],
+    );
+
+    let output = env.docs(&["read", "anything"], None);
+    assert_eq!(output.code, 2, "{}", output.stdout);
+    assert_eq!(output.json["error_code"], "SCOPES_MISSING");
+    assert_eq!(
+        output.json["missing_scopes"],
+        json!(["https://www.googleapis.com/auth/documents"])
+    );
+    assert_eq!(
+        output.json["login_command"],
+        "docs_manager auth login --scope https://www.googleapis.com/auth/documents"
+    );
+    assert!(output.json.get("auth_url").is_none(), "{}", output.stdout);
+    assert_eq!(
+        output.json["instructions"][0],
+        "1. Run: docs_manager auth login --scope https://www.googleapis.com/auth/documents"
+    );
+    assert!(env.google.requests().is_empty());
+}
+
+#[test]
+fn auth_scope_flag_requests_extra_scopes() {
+    let env = TestEnv::without_token();
+
+    let authorized = env.docs(
+        &[
+            "auth",
+            "device",
+            "--scope",
+            "https://www.googleapis.com/auth/drive",
+        ],
+        None,
+    );
+    assert_eq!(authorized.code, 0, "{}", authorized.stdout);
+    let requested = env
+        .google
+        .requests()
+        .into_iter()
+        .find(|r| r.path == "/device/code")
+        .expect("device code request");
+    assert_eq!(
+        requested.body["scope"],
+        "https://www.googleapis.com/auth/documents https://www.googleapis.com/auth/drive"
+    );
+}
+
+#[test]
+fn secret_service_store_migrates_file_token() {
+    let env = TestEnv::new();
+    let keyring = env.use_fake_keyring();
+    let document_id = env.google.add_document("Keyring", "Body\n");
+
+    let output = env.docs(&["read", &document_id], None);
+    assert_eq!(output.code, 0, "{}", output.stdout);
+    assert!(!env.token_path().exists(), "file entry should be migrated");
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(keyring.join("default")).unwrap()).unwrap();
+    assert_eq!(stored["access_token"], common::fake_google::ACCESS_TOKEN);
+
+    let status = env.docs(&["auth", "status"], None);
+    assert_eq!(status.json["token_store"], "secret-service");
+    assert_eq!(status.json["authorized"], true);
+
+    let logout = env.docs(&["auth", "logout"], None);
+    assert_eq!(logout.json["token_removed"], true);
+    assert!(!keyring.join("default").exists());
+}
+
+#[test]
+fn parallel_logins_keep_every_profile() {
+    use std::process::Stdio;
+
+    let env = TestEnv::new();
+    let profiles: Vec<String> = (0..8).map(|i| format!("p{i}")).collect();
+
+    let children: Vec<_> = profiles
+        .iter()
+        .map(|profile| {
+            env.command("docs_manager")
+                .args(["auth", "device", "--profile", profile])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert_eq!(child.wait().unwrap().code(), Some(0));
+    }
+
+    let raw = std::fs::read_to_string(env.token_path()).unwrap();
+    let stored: serde_yaml::Mapping = serde_yaml::from_str(&raw).unwrap();
+    for profile in profiles.iter().map(String::as_str).chain(["default"]) {
+        assert!(stored.contains_key(profile), "{profile} missing from {raw}");
+    }
+}
+
+#[test]
+fn unparseable_token_file_is_not_overwritten() {
+    let env = TestEnv::new();
+    let corrupt = "default: {\"access_token\": [unterminated\n";
+    std::fs::write(env.token_path(), corrupt).unwrap();
+
+    let output = env.docs(&["auth", "device", "--profile", "work"], None);
+    assert_eq!(output.code, 2, "{}", output.stdout);
+    // The device flow prints its pending prompt before the error.
+    assert!(
+        output.stdout.contains("\"error_code\": \"TOKEN_CORRUPT\""),
+        "{}",
+        output.stdout
+    );
+    assert_eq!(std::fs::read_to_string(env.token_path()).unwrap(), corrupt);
+}
+
+#[test]
+fn unavailable_token_store_is_not_reported_as_auth_required() {
+    let env = TestEnv::new();
+    env.write_config(json!({"token_store": {"backend": "secret-service"}}));
+    let empty_path = env.path("no-bin");
+    std::fs::create_dir_all(&empty_path).unwrap();
+    env.set_env("PATH", empty_path.to_str().unwrap());
+
+    let output = env.docs(&["read", "anything"], None);
+    assert_eq!(output.code, 2, "{}", output.stdout);
+    assert_eq!(output.json["error_code"], "TOKEN_STORE_UNAVAILABLE");
+    assert!(output.stdout.contains("secret-tool"), "{}", output.stdout);
+    assert!(env.google.requests().is_empty());
+}
+
+#[test]
+fn parallel_invocations_share_one_refresh() {
+    use std::process::Stdio;
+
+    let env = TestEnv::new();
+    env.write_token("stale-token", 0);
+    let document_id = env.google.add_document("Busy", "Body\n");
+
+    let children: Vec<_> = (0..6)
+        .map(|_| {
+            env.command("docs_manager")
+                .args(["read", &document_id])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert_eq!(child.wait().unwrap().code(), Some(0));
+    }
+
+    let refreshes = env
+        .google
+        .requests()
+        .iter()
+        .filter(|r| r.path == "/token" && r.body["grant_type"] == "refresh_token")
+        .count();
+    assert_eq!(refreshes, 1);
+    assert_eq!(env.read_token()["access_token"], REFRESHED_ACCESS_TOKEN);
+    let leftovers: Vec<_> = std::fs::read_dir(env.google_dir())
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty());
+}
+
+#[test]
+fn stale_revisions_are_rejected_with_revision_conflict() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Draft", "First line\n");
+    let read = env.docs(&["read", &document_id], None);
+    let revision = read.json["revision_id"].as_str().unwrap().to_string();
+
+    let appended = env.docs(
+        &["append"],
+        Some(json!({
+            "document_id": document_id,
+            "text": "Second line",
+            "required_revision_id": revision
+        })),
+    );
+    assert_eq!(appended.code, 0, "{}", appended.stdout);
+    assert_ne!(appended.json["revision_id"], revision);
+
+    let before = env.google.document(&document_id).plain_text();
+    let stale = env.docs(
+        &["insert"],
+        Some(json!({
+            "document_id": document_id,
+            "text": "Late edit ",
+            "index": 1,
+            "required_revision_id": revision
+        })),
+    );
+    assert_eq!(stale.code, 1, "{}", stale.stdout);
+    assert_eq!(stale.json["error_code"], "REVISION_CONFLICT");
+
+    let anchored = env.docs(
+        &["insert-from-markdown"],
+        Some(json!({
+            "document_id": document_id,
+            "markdown": "**Late**\n",
+            "anchor": {"after_text": "First"},
+            "required_revision_id": revision
+        })),
+    );
+    assert_eq!(anchored.json["error_code"], "REVISION_CONFLICT");
+    assert_eq!(env.google.document(&document_id).plain_text(), before);
+
+    // A bad request at the current revision is an API error, not a conflict.
+    let current = env.google.document(&document_id).revision_id();
+    let out_of_range = env.docs(
+        &["insert"],
+        Some(json!({
+            "document_id": document_id,
+            "text": "Revision note",
+            "index": 999,
+            "required_revision_id": current
+        })),
+    );
+    assert_eq!(out_of_range.code, 3, "{}", out_of_range.stdout);
+    assert_ne!(out_of_range.json["error_code"], "REVISION_CONFLICT");
+
+    let table = env.docs(
+        &["insert-table"],
+        Some(json!({"document_id": document_id, "rows": 1, "cols": 2, "data": [["a", "b"]]})),
+    );
+    assert_eq!(table.code, 0, "{}", table.stdout);
+    let write_controls: Vec<Value> = env
+        .google
+        .requests()
+        .iter()
+        .filter(|request| request.path.ends_with(":batchUpdate"))
+        .map(|request| request.body["writeControl"]["requiredRevisionId"].clone())
+        .collect();
+    // The table goes in at the revision its index was read from, and its
+    // cells are filled at the revision their indices were read from.
+    let [.., insert, fill] = write_controls.as_slice() else {
+        panic!("{write_controls:?}");
+    };
+    assert_eq!(insert, &json!(current));
+    let revision = env.google.document(&document_id).revision;
+    assert_eq!(fill, &json!(format!("rev-{}", revision - 1)));
+}
+
+#[test]
+fn apply_rebases_ops_and_sends_one_batch() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Draft", "Hello world");
+
+    let applied = env.docs(
+        &["apply"],
+        Some(json!({
+            "document_id": document_id,
+            "ops": [
+                {"op": "insert", "text": "Big ", "index": 7},
+                {"op": "format", "start_index": 7, "end_index": 12, "bold": true},
+                {"op": "delete", "start_index": 1, "end_index": 7},
+                {"op": "insert_table", "rows": 1, "cols": 2, "data": [["a", "b"]]},
+                {"op": "replace", "find": "Big", "replace": "Small", "match_case": true}
+            ]
+        })),
+    );
+    assert_eq!(applied.code, 0, "{}", applied.stdout);
+    assert_eq!(applied.json["operation"], "apply");
+    let batches = env
+        .google
+        .requests()
+        .iter()
+        .filter(|request| request.path.ends_with(":batchUpdate"))
+        .count();
+    assert_eq!(batches, 1);
+
+    let edits = applied.json["edits"].as_array().unwrap();
+    let ranges: Vec<(Value, Value)> = edits
+        .iter()
+        .map(|edit| (edit["start_index"].clone(), edit["end_index"].clone()))
+        .collect();
+    assert_eq!(
+        ranges,
+        [
+            (json!(1), json!(5)),
+            (json!(5), json!(10)),
+            (json!(1), json!(1)),
+            (json!(10), json!(20)),
+            (Value::Null, Value::Null),
+        ]
+    );
+    assert_eq!(edits[4]["occurrences"], 1);
+
+    let document = env.google.document(&document_id);
+    assert_eq!(document.plain_text(), "Small world\na\nb\n\n");
+    let bold: Vec<String> = document.to_json()["body"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["paragraph"]["elements"].as_array())
+        .flatten()
+        .filter(|run| run["textRun"]["textStyle"]["bold"] == true)
+        .filter_map(|run| run["textRun"]["content"].as_str().map(str::to_string))
+        .collect();
+    assert_eq!(bold, ["world"]);
+
+    let misordered = env.docs(
+        &["apply"],
+        Some(json!({
+            "document_id": document_id,
+            "ops": [
+                {"op": "replace", "find": "world", "replace": "there"},
+                {"op": "insert", "text": "!", "index": 3}
+            ]
+        })),
+    );
+    assert_eq!(misordered.code, 4);
+    assert_eq!(misordered.json["error_code"], "INVALID_OPS");
+
+    let stale = env.docs(
+        &["apply"],
+        Some(json!({
+            "document_id": document_id,
+            "ops": [{"op": "delete", "start_index": 1, "end_index": 3}],
+            "required_revision_id": "rev-1"
+        })),
+    );
+    assert_eq!(stale.json["error_code"], "REVISION_CONFLICT");
+    assert_eq!(
+        env.google.document(&document_id).plain_text(),
+        document.plain_text()
+    );
+
+    // Nothing to send is still checked against the revision.
+    let stale_noop = env.docs(
+        &["apply"],
+        Some(json!({
+            "document_id": document_id,
+            "ops": [{"op": "insert", "text": "", "index": 1}],
+            "required_revision_id": "rev-1"
+        })),
+    );
+    assert_eq!(stale_noop.code, 1, "{}", stale_noop.stdout);
+    assert_eq!(stale_noop.json["error_code"], "REVISION_CONFLICT");
+}
+
+#[test]
+fn apply_lays_out_a_table_inside_a_paragraph() {
+    let env = TestEnv::new();
+    let applied_id = env.google.add_document("Applied", "Hello world");
+    let inserted_id = env.google.add_document("Inserted", "Hello world");
+
+    // The table splits "Hello world"; the insert after it only lands after
+    // "world" if the table's length was worked out right.
+    let applied = env.docs(
+        &["apply"],
+        Some(json!({
+            "document_id": applied_id,
+            "ops": [
+                {"op": "insert_table", "rows": 2, "cols": 2, "index": 6, "data": [["a", "b"], ["", "d"]]},
+                {"op": "insert", "text": "!", "index": 12}
+            ]
+        })),
+    );
+    assert_eq!(applied.code, 0, "{}", applied.stdout);
+    assert_eq!(applied.json["edits"][0]["start_index"], 6);
+    assert_eq!(applied.json["edits"][0]["end_index"], 6 + 13 + 3);
+    assert_eq!(applied.json["edits"][1]["start_index"], 12 + 13 + 3);
+
+    // The same table through insert-table, which reads the cells' indices
+    // back from the document instead.
+    let inserted = env.docs(
+        &["insert-table"],
+        Some(json!({
+            "document_id": inserted_id,
+            "rows": 2,
+            "cols": 2,
+            "index": 6,
+            "data": [["a", "b"], ["", "d"]]
+        })),
+    );
+    assert_eq!(inserted.code, 0, "{}", inserted.stdout);
+
+    let applied = env.google.document(&applied_id);
+    assert_eq!(applied.plain_text(), "Hello\na\nb\n\nd\n world!\n");
+    let inserted = env.google.document(&inserted_id);
+    assert_eq!(inserted.plain_text(), "Hello\na\nb\n\nd\n world\n");
+    let table = &applied.to_json()["body"]["content"][2]["table"];
+    assert!(table.is_object(), "{table}");
+    assert_eq!(table, &inserted.to_json()["body"]["content"][2]["table"]);
+}
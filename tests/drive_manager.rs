@@ -0,0 +1,239 @@
+mod common;
+
+use common::TestEnv;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+#[test]
+fn upload_then_download_preserves_bytes() {
+    let env = TestEnv::new();
+    let source = env.path("report.txt");
+    std::fs::write(&source, b"quarterly numbers").unwrap();
+
+    let uploaded = env.drive(&["upload", "--file", source.to_str().unwrap()]);
+    assert_eq!(uploaded.code, 0, "{}", uploaded.stdout);
+    assert_eq!(uploaded.json["status"], "success");
+    let file_id = uploaded.json["file"]["id"]
+        .as_str()
+        .or_else(|| uploaded.json["file_id"].as_str())
+        .expect("uploaded file id")
+        .to_string();
+    assert_eq!(
+        env.google.file(&file_id).unwrap().content,
+        b"quarterly numbers"
+    );
+
+    let target = env.path("downloaded.txt");
+    let downloaded = env.drive(&[
+        "download",
+        "--file-id",
+        &file_id,
+        "--output",
+        target.to_str().unwrap(),
+    ]);
+    assert_eq!(downloaded.code, 0, "{}", downloaded.stdout);
+    assert_eq!(std::fs::read(&target).unwrap(), b"quarterly numbers");
+}
+
+#[test]
+fn folders_move_and_list() {
+    let env = TestEnv::new();
+    let file_id = env.google.add_file("notes.md", "text/markdown", b"# notes");
+
+    let folder = env.drive(&["create-folder", "--name", "Archive"]);
+    assert_eq!(folder.code, 0, "{}", folder.stdout);
+    let folder_id = folder.json["folder"]["id"]
+        .as_str()
+        .or_else(|| folder.json["folder_id"].as_str())
+        .expect("folder id")
+        .to_string();
+
+    let moved = env.drive(&["move", "--file-id", &file_id, "--folder-id", &folder_id]);
+    assert_eq!(moved.code, 0, "{}", moved.stdout);
+
+    let listed = env.drive(&["list", "--folder-id", &folder_id]);
+    assert_eq!(listed.code, 0, "{}", listed.stdout);
+    assert!(listed.stdout.contains("notes.md"), "{}", listed.stdout);
+}
+
+#[test]
+fn copy_and_permanent_delete() {
+    let env = TestEnv::new();
+    let file_id = env.google.add_file("plan.pdf", "application/pdf", b"%PDF");
+
+    let copied = env.drive(&["copy", "--file-id", &file_id, "--name", "plan (copy).pdf"]);
+    assert_eq!(copied.code, 0, "{}", copied.stdout);
+    assert!(copied.stdout.contains("plan (copy).pdf"));
+
+    let deleted = env.drive(&["delete", "--file-id", &file_id, "--permanent", "true"]);
+    assert_eq!(deleted.code, 0, "{}", deleted.stdout);
+    assert!(env.google.file(&file_id).is_none());
+}
+
+#[test]
+fn share_records_permission() {
+    let env = TestEnv::new();
+    let file_id = env.google.add_file("deck.pdf", "application/pdf", b"%PDF");
+
+    let shared = env.drive(&[
+        "share",
+        "--file-id",
+        &file_id,
+        "--email",
+        "teammate@example.test",
+        "--role",
+        "writer",
+    ]);
+    assert_eq!(shared.code, 0, "{}", shared.stdout);
+
+    let request = env
+        .google
+        .requests()
+        .into_iter()
+        .find(|r| r.path.ends_with("/permissions"))
+        .expect("permissions request");
+    assert_eq!(request.body["emailAddress"], "teammate@example.test");
+    assert_eq!(request.body["role"], "writer");
+}
+
+#[test]
+fn missing_arguments_exit_with_invalid_args() {
+    let env = TestEnv::new();
+
+    let output = env.drive(&["get-metadata"]);
+    assert_eq!(output.code, 4);
+    assert_eq!(output.json["error_code"], "MISSING_FILE_ID");
+}
+
+#[test]
+fn missing_local_file_is_an_operation_failure() {
+    let env = TestEnv::new();
+    let missing = env.path("nope.txt");
+
+    let output = env.drive(&["upload", "--file", missing.to_str().unwrap()]);
+    assert_eq!(output.code, 1, "{}", output.stdout);
+    assert_eq!(output.json["status"], "error");
+}
+
+#[test]
+fn rate_limits_are_retried() {
+    let env = TestEnv::new();
+    let file_id = env.google.add_file("a.txt", "text/plain", b"a");
+    env.google.fail_next(429, 1, Some("0"));
+
+    let output = env.drive(&["get-metadata", "--file-id", &file_id]);
+    assert_eq!(output.code, 0, "{}", output.stdout);
+    assert_eq!(output.json["retries"], 1);
+}
+
+#[test]
+fn retry_after_is_capped_at_the_maximum_backoff() {
+    let env = TestEnv::new();
+    let file_id = env.google.add_file("a.txt", "text/plain", b"a");
+    env.google.fail_next(429, 1, Some("86400"));
+
+    let started = std::time::Instant::now();
+    let output = env.drive(&["get-metadata", "--file-id", &file_id]);
+    assert_eq!(output.code, 0, "{}", output.stdout);
+    assert_eq!(output.json["retries"], 1);
+    assert!(started.elapsed() < std::time::Duration::from_secs(30));
+}
+
+#[test]
+fn exhausted_retries_surface_api_error() {
+    let env = TestEnv::new();
+    env.google.fail_next(500, 10, None);
+
+    let output = env.drive(&["list"]);
+    assert_eq!(output.code, 3, "{}", output.stdout);
+    assert_eq!(output.json["error_code"], "API_ERROR");
+    assert_eq!(output.json["retries"], 4);
+}
+
+#[test]
+fn auth_device_polls_until_approved() {
+    let env = TestEnv::without_token();
+    env.google.state().device_pending_polls = 2;
+
+    let output = env.drive(&["auth", "device"]);
+    assert_eq!(output.code, 0, "{}", output.stdout);
+    assert_eq!(output.json["status"], "pending");
+    assert_eq!(output.json["user_code"], "ABCD-EFGH");
+    assert!(output.stdout.contains("\"status\": \"success\""));
+
+    let polls = env
+        .google
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == "/token")
+        .count();
+    assert_eq!(polls, 3);
+    assert_eq!(env.read_token()["refresh_token"], "test-refresh-token");
+}
+
+#[test]
+fn auth_status_whoami_and_logout() {
+    let env = TestEnv::new();
+
+    let status = env.drive(&["auth", "status"]);
+    assert_eq!(status.code, 0, "{}", status.stdout);
+    assert_eq!(status.json["authorized"], true);
+    assert_eq!(status.json["expired"], false);
+    assert!(status.json["scopes"].is_array());
+    assert!(env.google.requests().is_empty());
+
+    let whoami = env.drive(&["auth", "whoami"]);
+    assert_eq!(whoami.code, 0, "{}", whoami.stdout);
+    assert_eq!(whoami.json["email"], "owner@example.test");
+
+    let logout = env.drive(&["auth", "logout"]);
+    assert_eq!(logout.code, 0, "{}", logout.stdout);
+    assert_eq!(logout.json["revoked"], true);
+    assert_eq!(logout.json["token_removed"], true);
+    let revoke = env
+        .google
+        .requests()
+        .into_iter()
+        .find(|r| r.path == "/revoke")
+        .expect("revoke request");
+    assert_eq!(revoke.body["token"], "test-refresh-token");
+    assert!(!env.token_path().exists());
+
+    let status = env.drive(&["auth", "status"]);
+    assert_eq!(status.json["authorized"], false);
+}
+
+#[test]
+fn an_open_stdin_does_not_block_drive() {
+    let env = TestEnv::new();
+    env.google.add_file("notes.md", "text/markdown", b"# notes");
+
+    for (bin, args) in [
+        ("drive_manager", &["list"][..]),
+        ("gws", &["drive", "list"]),
+    ] {
+        let mut child = env
+            .command(bin)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn binary");
+        // Keep the write end open, as cron or a parent process would.
+        let _stdin = child.stdin.take();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let status = loop {
+            if let Some(status) = child.try_wait().expect("poll binary") {
+                break status;
+            }
+            if Instant::now() > deadline {
+                let _ = child.kill();
+                panic!("{bin} {args:?} waited on stdin");
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        assert!(status.success(), "{bin} {args:?} exited with {status}");
+    }
+}
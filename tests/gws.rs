@@ -0,0 +1,164 @@
+mod common;
+
+use common::TestEnv;
+use serde_json::json;
+
+#[test]
+fn docs_operation_accepts_flags() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Notes", "World");
+
+    let inserted = env.gws(
+        &[
+            "docs",
+            "insert",
+            "--document-id",
+            &document_id,
+            "--text",
+            "Hello ",
+            "--index",
+            "1",
+        ],
+        None,
+    );
+    assert_eq!(inserted.code, 0, "{}", inserted.stdout);
+    assert_eq!(inserted.json["operation"], "insert");
+    assert_eq!(
+        env.google.document(&document_id).plain_text(),
+        "Hello World\n"
+    );
+
+    let read = env.gws(&["docs", "read", &document_id], None);
+    assert_eq!(read.code, 0, "{}", read.stdout);
+    assert_eq!(read.json["content"], "Hello World\n");
+}
+
+#[test]
+fn input_file_is_overlaid_by_flags() {
+    let env = TestEnv::new();
+    let spreadsheet_id = env.google.add_spreadsheet("Budget", &["Sheet1", "Sheet2"]);
+    let input = env.path("write.json");
+    std::fs::write(
+        &input,
+        json!({
+            "spreadsheet_id": spreadsheet_id,
+            "range": "Sheet1!A1",
+            "values": [["from file"]]
+        })
+        .to_string(),
+    )
+    .expect("write input");
+
+    let written = env.gws(
+        &[
+            "sheets",
+            "write",
+            "--input",
+            input.to_str().unwrap(),
+            "--range",
+            "Sheet2!A1",
+        ],
+        None,
+    );
+    assert_eq!(written.code, 0, "{}", written.stdout);
+
+    let read = env.sheets(
+        "read",
+        json!({"spreadsheet_id": spreadsheet_id, "range": "Sheet2!A1"}),
+    );
+    assert_eq!(read.json["values"], json!([["from file"]]));
+}
+
+#[test]
+fn stdin_still_works_without_flags() {
+    let env = TestEnv::new();
+    let document_id = env.google.add_document("Notes", "Body");
+
+    let appended = env.gws(
+        &["docs", "append"],
+        Some(json!({"document_id": document_id, "text": " more"})),
+    );
+    assert_eq!(appended.code, 0, "{}", appended.stdout);
+    assert_eq!(
+        env.google.document(&document_id).plain_text(),
+        "Body more\n"
+    );
+}
+
+#[test]
+fn invalid_usage_exits_with_invalid_args() {
+    let env = TestEnv::new();
+
+    let unknown = env.gws(&["docs", "frobnicate"], None);
+    assert_eq!(unknown.code, 4);
+    assert_eq!(unknown.json["error_code"], "INVALID_COMMAND");
+    let valid = unknown.json["valid_commands"].as_array().unwrap();
+    assert!(valid.contains(&json!("insert")));
+    assert!(valid.contains(&json!("create-from-markdown")));
+    assert!(!valid.contains(&json!("upload")));
+
+    let unknown_group = env.gws(&["calendar"], None);
+    assert_eq!(unknown_group.json["error_code"], "INVALID_COMMAND");
+    assert_eq!(
+        unknown_group.json["valid_commands"],
+        json!(["auth", "docs", "drive", "sheets", "completions"])
+    );
+
+    let alias = env.docs(&["frobnicate"], None);
+    assert_eq!(alias.code, 4);
+    assert_eq!(alias.json["error_code"], "INVALID_COMMAND");
+    let valid = alias.json["valid_commands"].as_array().unwrap();
+    assert_eq!(valid.first(), Some(&json!("auth")));
+    assert!(valid.contains(&json!("read")));
+
+    let bad_value = env.gws(&["drive", "list", "--max-results", "many"], None);
+    assert_eq!(bad_value.code, 4);
+    assert_eq!(bad_value.json["error_code"], "INVALID_ARGS");
+}
+
+#[test]
+fn auth_codes_need_the_code_action() {
+    let env = TestEnv::without_token();
+
+    for output in [
+        env.gws(&["auth", "stauts"], None),
+        env.docs(&["auth", "stauts"], None),
+    ] {
+        assert_eq!(output.code, 4, "{}", output.stdout);
+        assert_eq!(output.json["error_code"], "INVALID_COMMAND");
+        let valid = output.json["valid_commands"].as_array().unwrap();
+        assert!(valid.contains(&json!("status")), "{valid:?}");
+        assert!(valid.contains(&json!("code")), "{valid:?}");
+    }
+    assert!(env.google.requests().is_empty());
+
+    let missing = env.gws(&["auth", "code"], None);
+    assert_eq!(missing.code, 4, "{}", missing.stdout);
+    assert_eq!(missing.json["error_code"], "MISSING_CODE");
+
+    let authorized = env.gws(&["auth", "code", "4/pasted-code"], None);
+    assert_eq!(authorized.code, 0, "{}", authorized.stdout);
+    assert_eq!(authorized.json["profile"], "default");
+    let exchanged = env
+        .google
+        .requests()
+        .into_iter()
+        .find(|r| r.path == "/token")
+        .expect("token request");
+    assert_eq!(exchanged.body["code"], "4/pasted-code");
+}
+
+#[test]
+fn completions_cover_every_service() {
+    let env = TestEnv::new();
+
+    let output = env.run("gws", &["completions", "bash"], None);
+    assert_eq!(output.code, 0, "{}", output.stderr);
+    for operation in [
+        "insert-from-markdown",
+        "create-folder",
+        "add-conditional-format",
+    ] {
+        assert!(output.stdout.contains(operation), "missing {operation}");
+    }
+}
@@ -0,0 +1,193 @@
+use google_docs_rust::docs::markdown::{
+    FormatInfo, FormatType, ImageInfo, LinkInfo, ListInfo, parse_markdown,
+};
+use google_docs_rust::models::docs::{BulletGlyphPreset, Document, NamedStyleType, Request};
+use google_docs_rust::models::sheets::GridRange;
+use google_docs_rust::sheets::parse_a1_to_grid_range;
+use serde_json::json;
+
+#[test]
+fn a1_ranges_become_grid_ranges() {
+    assert_eq!(
+        parse_a1_to_grid_range("Sheet1!B2:D10", 7),
+        GridRange {
+            sheet_id: 7,
+            start_row_index: Some(1),
+            end_row_index: Some(10),
+            start_column_index: Some(1),
+            end_column_index: Some(4),
+        }
+    );
+    assert_eq!(
+        json!(parse_a1_to_grid_range("Sheet1!B2:D10", 7)),
+        json!({
+            "sheetId": 7,
+            "startColumnIndex": 1,
+            "startRowIndex": 1,
+            "endColumnIndex": 4,
+            "endRowIndex": 10
+        })
+    );
+    assert_eq!(
+        json!(parse_a1_to_grid_range("A:C", 0)),
+        json!({"sheetId": 0, "startColumnIndex": 0, "endColumnIndex": 3})
+    );
+}
+
+#[test]
+fn markdown_is_parsed_without_a_client() {
+    let parsed = parse_markdown("# Title\n\nSome **bold** text\n");
+
+    assert!(parsed.text.starts_with("Title\n"));
+    assert!(parsed.tables.is_empty());
+    let kinds = parsed
+        .formats
+        .iter()
+        .map(|format| format.format_type)
+        .collect::<Vec<_>>();
+    assert!(kinds.contains(&FormatType::Heading1));
+    assert!(kinds.contains(&FormatType::Bold));
+}
+
+#[test]
+fn markdown_indices_count_utf16_code_units() {
+    let parsed = parse_markdown(
+        "# 🎉 Launch\n\nWe 🚀 **shipped** it, *finally* 👍 `v2`\n| A | B |\n|---|---|\n| 😀 | x |\n\nAfter **end**\n",
+    );
+
+    let format = |format_type, start, end| FormatInfo {
+        format_type,
+        start,
+        end,
+    };
+    assert_eq!(
+        parsed.formats,
+        vec![
+            format(FormatType::Heading1, 1, 10),
+            format(FormatType::Bold, 18, 25),
+            format(FormatType::Italic, 30, 37),
+            format(FormatType::Code, 41, 43),
+            format(FormatType::Bold, 52, 55),
+        ]
+    );
+    assert_eq!(parsed.tables[0].insert_index, 44);
+}
+
+#[test]
+fn markdown_lists_nest_by_indentation() {
+    let parsed =
+        parse_markdown("- a\n  - b\n    - c\n- d\n\n1. one\n\n2. two\n\n- [ ] todo\n- [x] done\n");
+
+    assert_eq!(parsed.text, "a\n\tb\n\t\tc\nd\n\none\ntwo\n\ntodo\ndone\n");
+    let list = |preset, start, end, nesting_tabs| ListInfo {
+        preset,
+        start,
+        end,
+        nesting_tabs,
+    };
+    assert_eq!(
+        parsed.lists,
+        vec![
+            list(BulletGlyphPreset::BulletDiscCircleSquare, 1, 11, 3),
+            list(BulletGlyphPreset::NumberedDecimalAlphaRoman, 13, 20, 0),
+            list(BulletGlyphPreset::BulletCheckbox, 22, 31, 0),
+        ]
+    );
+    assert_eq!(parsed.final_len(), 28);
+}
+
+#[test]
+fn commonmark_blocks_and_spans_are_parsed() {
+    let parsed = parse_markdown(concat!(
+        "#### Four\n##### Five\n###### Six\n\n",
+        "A [link](https://example.com) and ~~gone~~ \\*not italic\\* ***both***\n\n",
+        "> quoted\n\n",
+        "```\nlet x = 1;\n```\n\n",
+        "![alt](https://example.com/a.png) after\n",
+    ));
+
+    assert_eq!(
+        parsed.text,
+        "Four\nFive\nSix\n\nA link and gone *not italic* both\n\nquoted\n\nlet x = 1;\n\n after\n"
+    );
+    let format = |format_type, start, end| FormatInfo {
+        format_type,
+        start,
+        end,
+    };
+    assert_eq!(
+        parsed.formats,
+        vec![
+            format(FormatType::Heading4, 1, 5),
+            format(FormatType::Heading5, 6, 10),
+            format(FormatType::Heading6, 11, 14),
+            format(FormatType::Strikethrough, 27, 31),
+            format(FormatType::Bold, 45, 49),
+            format(FormatType::Italic, 45, 49),
+            format(FormatType::Blockquote, 51, 57),
+            format(FormatType::CodeBlock, 59, 69),
+        ]
+    );
+    assert_eq!(
+        parsed.links,
+        vec![LinkInfo {
+            url: "https://example.com".to_string(),
+            start: 18,
+            end: 22
+        }]
+    );
+    assert_eq!(
+        parsed.images,
+        vec![ImageInfo {
+            url: "https://example.com/a.png".to_string(),
+            index: 71
+        }]
+    );
+}
+
+#[test]
+fn docs_models_match_the_api_wire_format() {
+    assert_eq!(
+        json!(Request::insert_text("Hi", 5)),
+        json!({"insertText": {"text": "Hi", "location": {"index": 5}}})
+    );
+
+    let document: Document = serde_json::from_value(json!({
+        "documentId": "doc-1",
+        "title": "Notes",
+        "body": {"content": [
+            {"startIndex": 1, "endIndex": 7, "paragraph": {
+                "elements": [{"startIndex": 1, "endIndex": 7, "textRun": {"content": "Intro\n"}}],
+                "paragraphStyle": {"namedStyleType": "HEADING_2"}
+            }}
+        ]}
+    }))
+    .unwrap();
+
+    assert_eq!(document.end_index(), Some(7));
+    let paragraph = document.body.content[0].paragraph.as_ref().unwrap();
+    assert_eq!(paragraph.text(), "Intro\n");
+    assert_eq!(
+        paragraph.paragraph_style.named_style_type,
+        Some(NamedStyleType::Heading2)
+    );
+}
+
+#[test]
+fn docs_models_reject_missing_required_fields() {
+    let misspelled = serde_json::from_value::<Document>(json!({
+        "documentID": "doc-1",
+        "body": {"content": []}
+    }));
+    assert!(misspelled.is_err());
+
+    let range = serde_json::from_value::<google_docs_rust::models::docs::Range>(json!({
+        "endIndex": 4
+    }));
+    assert!(range.is_err());
+
+    // Fields Google leaves out when empty still default.
+    let document: Document = serde_json::from_value(json!({"documentId": "doc-1"})).unwrap();
+    assert!(document.body.content.is_empty());
+    assert_eq!(document.title, "");
+}
@@ -0,0 +1,233 @@
+mod common;
+
+use common::TestEnv;
+use serde_json::json;
+
+#[test]
+fn write_then_read_values() {
+    let env = TestEnv::new();
+    let spreadsheet_id = env.google.add_spreadsheet("Budget", &["Sheet1"]);
+
+    let written = env.sheets(
+        "write",
+        json!({
+            "spreadsheet_id": spreadsheet_id,
+            "range": "Sheet1!A1:B2",
+            "values": [["Item", "Cost"], ["Coffee", 3]]
+        }),
+    );
+    assert_eq!(written.code, 0, "{}", written.stdout);
+    assert_eq!(written.json["status"], "success");
+
+    let read = env.sheets(
+        "read",
+        json!({"spreadsheet_id": spreadsheet_id, "range": "Sheet1!A1:B2"}),
+    );
+    assert_eq!(read.code, 0, "{}", read.stdout);
+    assert_eq!(
+        read.json["values"],
+        json!([["Item", "Cost"], ["Coffee", "3"]])
+    );
+}
+
+#[test]
+fn append_adds_rows_after_existing_data() {
+    let env = TestEnv::new();
+    let spreadsheet_id = env.google.add_spreadsheet("Log", &["Sheet1"]);
+
+    for row in [json!([["first"]]), json!([["second"]])] {
+        let appended = env.sheets(
+            "append",
+            json!({"spreadsheet_id": spreadsheet_id, "range": "Sheet1!A1", "values": row}),
+        );
+        assert_eq!(appended.code, 0, "{}", appended.stdout);
+    }
+
+    let sheet = &env.google.spreadsheet(&spreadsheet_id).sheets[0];
+    assert_eq!(
+        sheet.values,
+        vec![vec![json!("first")], vec![json!("second")]]
+    );
+}
+
+#[test]
+fn sheet_management_round_trip() {
+    let env = TestEnv::new();
+
+    let created = env.sheets("create", json!({"title": "Ops"}));
+    assert_eq!(created.code, 0, "{}", created.stdout);
+    let spreadsheet_id = created.json["spreadsheet_id"].as_str().unwrap().to_string();
+
+    let added = env.sheets(
+        "add-sheet",
+        json!({"spreadsheet_id": spreadsheet_id, "title": "Q1"}),
+    );
+    assert_eq!(added.code, 0, "{}", added.stdout);
+
+    let metadata = env.sheets("get-metadata", json!({"spreadsheet_id": spreadsheet_id}));
+    assert_eq!(metadata.code, 0, "{}", metadata.stdout);
+    assert!(metadata.stdout.contains("Q1"), "{}", metadata.stdout);
+}
+
+#[test]
+fn missing_fields_exit_with_invalid_args() {
+    let env = TestEnv::new();
+
+    let output = env.sheets("read", json!({"range": "A1"}));
+    assert_eq!(output.code, 4, "{}", output.stdout);
+    assert_eq!(output.json["status"], "error");
+}
+
+#[test]
+fn unknown_spreadsheet_is_an_api_error() {
+    let env = TestEnv::new();
+
+    let output = env.sheets(
+        "read",
+        json!({"spreadsheet_id": "missing", "range": "Sheet1!A1"}),
+    );
+    assert_eq!(output.code, 3, "{}", output.stdout);
+    assert_eq!(output.json["error_code"], "API_ERROR");
+}
+
+#[test]
+fn rejected_token_is_an_api_error() {
+    let env = TestEnv::new();
+    env.write_token(
+        "revoked-token",
+        chrono::Utc::now().timestamp_millis() + 3_600_000,
+    );
+    let spreadsheet_id = env.google.add_spreadsheet("Locked", &["Sheet1"]);
+
+    let output = env.sheets(
+        "read",
+        json!({"spreadsheet_id": spreadsheet_id, "range": "Sheet1!A1"}),
+    );
+    assert_eq!(output.code, 3, "{}", output.stdout);
+    assert!(
+        output.json["message"]
+            .as_str()
+            .unwrap()
+            .contains("invalid authentication credentials"),
+        "{}",
+        output.stdout
+    );
+}
+
+#[test]
+fn service_account_signs_assertion_and_caches_token() {
+    use base64::Engine;
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    use ring::signature::{KeyPair, RSA_PKCS1_2048_8192_SHA256, RsaKeyPair, UnparsedPublicKey};
+
+    let env = TestEnv::new();
+    env.use_service_account();
+    env.write_config(json!({"service_account": {"subject": "alex@example.test"}}));
+    let spreadsheet_id = env.google.add_spreadsheet("Automation", &["Sheet1"]);
+
+    for _ in 0..2 {
+        let output = env.sheets(
+            "read",
+            json!({"spreadsheet_id": spreadsheet_id, "range": "Sheet1!A1"}),
+        );
+        assert_eq!(output.code, 0, "{}", output.stdout);
+    }
+
+    let exchanges: Vec<_> = env
+        .google
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == "/token")
+        .collect();
+    assert_eq!(
+        exchanges.len(),
+        1,
+        "second run should reuse the cached token"
+    );
+    assert_eq!(
+        exchanges[0].body["grant_type"],
+        "urn:ietf:params:oauth:grant-type:jwt-bearer"
+    );
+
+    let assertion = exchanges[0].body["assertion"].as_str().unwrap();
+    let (signing_input, signature) = assertion.rsplit_once('.').unwrap();
+    let claims: serde_json::Value = serde_json::from_slice(
+        &URL_SAFE_NO_PAD
+            .decode(signing_input.split('.').nth(1).unwrap())
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        claims["iss"],
+        "automation@skill-tests.iam.gserviceaccount.com"
+    );
+    assert_eq!(claims["sub"], "alex@example.test");
+    assert_eq!(claims["aud"], format!("{}/token", env.google.base_url()));
+
+    let key: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(env.google_dir().join("client_secret.json")).unwrap(),
+    )
+    .unwrap();
+    let pem: String = key["private_key"]
+        .as_str()
+        .unwrap()
+        .lines()
+        .filter(|l| !l.starts_with("-----"))
+        .collect();
+    let key_pair = RsaKeyPair::from_pkcs8(&STANDARD.decode(pem).unwrap()).unwrap();
+    UnparsedPublicKey::new(&RSA_PKCS1_2048_8192_SHA256, key_pair.public_key().as_ref())
+        .verify(
+            signing_input.as_bytes(),
+            &URL_SAFE_NO_PAD.decode(signature).unwrap(),
+        )
+        .expect("assertion signature verifies");
+
+    let token = env.read_token();
+    assert_eq!(
+        token["client_id"],
+        "automation@skill-tests.iam.gserviceaccount.com"
+    );
+    assert_eq!(token["subject"], "alex@example.test");
+}
+
+#[test]
+fn encrypted_token_file_is_private_and_needs_passphrase() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let env = TestEnv::new();
+    env.write_config(json!({"token_store": {"encrypt": true}}));
+    let spreadsheet_id = env.google.add_spreadsheet("Secrets", &["Sheet1"]);
+    let read = json!({"spreadsheet_id": spreadsheet_id, "range": "Sheet1!A1"});
+
+    let output = env.sheets("read", read.clone());
+    assert_eq!(output.code, 2, "{}", output.stdout);
+    assert_eq!(output.json["error_code"], "TOKEN_PASSPHRASE");
+    assert!(output.stdout.contains("GOOGLE_SKILL_TOKEN_PASSPHRASE"));
+
+    env.set_env("GOOGLE_SKILL_TOKEN_PASSPHRASE", "correct horse");
+    let output = env.sheets("read", read.clone());
+    assert_eq!(output.code, 0, "{}", output.stdout);
+
+    let raw = std::fs::read_to_string(env.token_path()).unwrap();
+    assert!(raw.contains("enc:v1:"), "{raw}");
+    assert!(!raw.contains(common::fake_google::ACCESS_TOKEN));
+    let mode = std::fs::metadata(env.token_path())
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let output = env.sheets("read", read.clone());
+    assert_eq!(output.code, 0, "{}", output.stdout);
+
+    // A wrong passphrase is not a missing token: logging in again won't help.
+    env.set_env("GOOGLE_SKILL_TOKEN_PASSPHRASE", "wrong horse");
+    let output = env.sheets("read", read);
+    assert_eq!(output.code, 2, "{}", output.stdout);
+    assert_eq!(output.json["error_code"], "TOKEN_PASSPHRASE");
+    assert!(
+        output.json.get("login_command").is_none(),
+        "{}",
+        output.stdout
+    );
+}